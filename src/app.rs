@@ -1,7 +1,35 @@
-use crate::helpers::evaluate_new_index;
-use crate::http_request::{make_http_request, HttpMethod};
-use serde_json::value::Value as JsonValue;
+use crate::clipboard::copy_to_clipboard;
+use crate::config::{save_layout, Config, LayoutMode};
+use crate::diff::{diff_json, diff_lines, JsonChange, LineChange};
+use crate::filter::apply_filter;
+use crate::graphql::{response_errors, GraphqlRequest, Schema, SchemaItem, INTROSPECTION_QUERY};
+use crate::har::{har_entry, save_har, HarEntry};
+use crate::helpers::{clamp_index, evaluate_new_index};
+use crate::http_file::{
+    load_http_file, resolve_variables, save_to_http_file, HttpFile, HttpFileSource,
+};
+use crate::http_request::{make_http_request, HttpMethod, HttpResponse, RequestBody};
+use crate::json_tree::{flatten, JsonTreeState};
+use crate::keymap::{Action, Keymap, KeymapContext};
+use crate::line_editor::LineEditor;
+use crate::notification::Notifications;
+use crate::pager::{PagerContent, PagerState};
+use crate::request_file::{curl_command, RequestFile};
+use crate::storage::{
+    load_request, request_exists, save_request, validate_request_name, SavedRequest, SavedTab,
+    SavedTabs,
+};
+use crate::text_editor::TextEditor;
+use crate::theme::Theme;
+use crate::variables::{substitute, substitute_values};
+use crate::websocket::{build_close_message, build_message, WebSocketState};
+use ratatui::layout::Rect;
+use serde_json::{json, value::Value as JsonValue};
+use std::cell::{Cell, Ref, RefCell};
 use std::collections::HashMap;
+use std::fmt::Display;
+use std::path::PathBuf;
+use std::time::Duration;
 
 pub enum PairInputMode {
     Add,
@@ -11,44 +39,182 @@ pub enum PairInputMode {
 pub struct PairInputState {
     pub mode: PairInputMode,
     pub selected_item: KeyValuePair,
-    pub key: String,
-    pub value: String,
+    pub key: LineEditor,
+    pub value: LineEditor,
 }
 
+pub struct BodyEditorState {
+    pub editor: TextEditor,
+    pub message: Option<String>, // Shown in the status line, e.g. if $EDITOR fails
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum GraphqlField {
+    Query,
+    Variables,
+    OperationName,
+}
+
+pub struct GraphqlEditorState {
+    pub query: TextEditor,
+    pub variables: TextEditor,
+    pub operation_name: LineEditor,
+    pub focus: GraphqlField,
+    pub message: Option<String>, // Shown in the status line, e.g. the fields which could complete
+}
+
+pub struct SchemaBrowserState {
+    pub path: Vec<(String, usize)>, // The types opened, each with the entry it was opened from
+    pub selected: usize,
+}
+
+pub struct FilterInputState {
+    pub expression: LineEditor,
+    pub history_index: Option<usize>,
+}
+
+pub struct SaveInputState {
+    pub name: LineEditor,
+    pub then_quit: bool, // Whether saving is part of quitting
+}
+
+#[derive(Clone, Copy)]
 pub enum Section {
-    RequestBody(Option<u8>),
-    RequestHeaders(Option<u8>),
-    ResponseBody(Option<u8>),
-    ResponseHeaders(Option<u8>),
+    RequestBody(Option<usize>),
+    RequestHeaders(Option<usize>),
+    ResponseBody(Option<usize>),
+    ResponseHeaders(Option<usize>),
 }
 
-impl ToString for Section {
-    fn to_string(&self) -> String {
+impl Section {
+    pub fn is_request_section(&self) -> bool {
+        matches!(self, Section::RequestBody(_) | Section::RequestHeaders(_))
+    }
+}
+
+impl Display for Section {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Section::RequestBody(_) => "Request Body",
-            Section::RequestHeaders(_) => "Request Headers",
-            Section::ResponseBody(_) => "Response Body",
-            Section::ResponseHeaders(_) => "Response Headers",
+            Section::RequestBody(_) => write!(f, "Request Body"),
+            Section::RequestHeaders(_) => write!(f, "Request Headers"),
+            Section::ResponseBody(_) => write!(f, "Response Body"),
+            Section::ResponseHeaders(_) => write!(f, "Response Headers"),
         }
-        .to_string()
     }
 }
 
+#[derive(Clone)]
 pub struct SectionValues {
     pub request_body: HashMap<String, JsonValue>,
+    pub raw_request_body: Option<String>, // Sent instead of the request body pairs when set
+    pub graphql: Option<GraphqlRequest>,  // Sent instead of either in GraphQL mode
     pub request_headers: HashMap<String, JsonValue>,
-    pub response_body: HashMap<String, JsonValue>,
+    pub response_body: Option<JsonValue>, // None until a response which is JSON arrives
     pub response_headers: HashMap<String, JsonValue>,
 }
 
+impl SectionValues {
+    /// The response body, or an empty object when there isn't a JSON one
+    pub fn response_json(&self) -> JsonValue {
+        self.response_body
+            .clone()
+            .unwrap_or(JsonValue::Object(serde_json::Map::new()))
+    }
+}
+
+pub struct HistoryEntry {
+    pub method: HttpMethod,
+    pub endpoint: String,
+    pub section_values: SectionValues,
+    pub response: HttpResponse, // With the request as sent, for exporting
+}
+
+/// The response body as displayed, kept until the response, the filter or the mode changes
+pub struct DisplayedResponse {
+    pub key: Option<(u64, Option<String>, bool)>, // The response version, filter and GraphQL mode
+    pub body: Result<JsonValue, String>,
+    pub graphql_errors: Vec<String>,
+}
+
+pub struct DiffState {
+    pub baseline_index: usize, // The history entry being compared against
+    pub scroll: usize,
+}
+
+/// The request and response of a tab which is not currently active
+pub struct Tab {
+    pub endpoint: String,
+    pub method: HttpMethod,
+    pub section_values: SectionValues,
+    pub selected_section: Section,
+    pub unfocused_section: Section,
+    pub response_filter: Option<String>,
+    pub response_tree: JsonTreeState,
+    pub last_response: Option<HttpResponse>,
+    pub saved_name: Option<String>,
+    pub saved_request: Option<SavedRequest>, // As last saved, to tell whether it has changed
+    pub http_file: Option<HttpFileSource>,
+}
+
+impl Tab {
+    pub fn new() -> Tab {
+        Tab {
+            endpoint: String::new(),
+            method: HttpMethod::GET,
+            section_values: SectionValues {
+                request_body: HashMap::new(),
+                raw_request_body: None,
+                graphql: None,
+                request_headers: HashMap::new(),
+                response_body: None,
+                response_headers: HashMap::new(),
+            },
+            selected_section: Section::RequestBody(None),
+            unfocused_section: Section::ResponseBody(None),
+            response_filter: None,
+            response_tree: JsonTreeState::new(),
+            last_response: None,
+            saved_name: None,
+            saved_request: None,
+            http_file: None,
+        }
+    }
+}
+
 pub enum CurrentScreen {
     Main,
-    EndpointInput(String), // value before edit
+    EndpointInput(LineEditor),
     PairInput(PairInputState),
+    FilterInput(FilterInputState),
+    BodyEditor(BodyEditorState),
+    GraphqlEditor(GraphqlEditorState),
+    SchemaBrowser(SchemaBrowserState),
+    Pager(PagerState),
+    Diff(DiffState),
+    AlertLog(usize), // Scroll position
+    SaveInput(SaveInputState),
+    ExitPrompt,                      // Shown when quitting with unsaved changes
+    OverwritePrompt(SaveInputState), // Shown before saving over another saved request
+    HarEntries(usize),               // The selected entry
+    HarExportInput(LineEditor),
+    HarOverwritePrompt(LineEditor), // Shown before exporting over an existing file
+    WebSocket(WebSocketState),
     // TODO: Add a loading screen
     Loading,
 }
 
+/// Something which can be clicked, recorded with its area while drawing
+#[derive(Clone, Copy)]
+pub enum MouseTarget {
+    Tab(usize),
+    Method,
+    Endpoint,
+    SendButton,
+    SectionName,
+    Pane(bool),            // Whether it shows a request section
+    ListItem(bool, usize), // The pane and the index of the item
+}
+
 pub enum KeyValuePair {
     Key,
     Value,
@@ -60,6 +226,36 @@ pub struct App {
     pub method: HttpMethod,
     pub section_values: SectionValues,
     pub selected_section: Section,
+    pub unfocused_section: Section, // The section shown in the other pane of the split layout
+    pub response_filter: Option<String>,
+    pub filter_history: Vec<String>,
+    pub response_version: u64, // Increased whenever the response body is replaced
+    pub displayed_response: RefCell<DisplayedResponse>,
+    pub response_tree: JsonTreeState,
+    pub horizontal_scroll: usize,
+    pub max_horizontal_scroll: Cell<usize>, // How far the widest list can scroll, set when drawn
+    pub wrap_values: bool,
+    pub last_response: Option<HttpResponse>,
+    pub saved_name: Option<String>,
+    pub saved_request: Option<SavedRequest>,
+    pub http_file: Option<HttpFileSource>, // When the request was opened from or saved to one
+    pub har_entries: Vec<HarEntry>,        // From a HAR file opened on the command line
+    pub schemas: HashMap<String, Schema>,  // GraphQL schemas by endpoint
+    pub history: Vec<HistoryEntry>,
+    pub pinned_baseline: Option<usize>, // Index of a history entry to diff against
+    // The active tab is held in the fields above, and its entry here is a placeholder
+    pub tabs: Vec<Tab>,
+    pub active_tab: usize,
+    pub config: Config,
+    pub config_loaded: bool, // Only then is the layout saved back to the config file
+    pub keymap: Keymap,
+    pub theme: Theme,
+    pub notifications: Notifications,
+    pub show_help: bool,
+    pub print_on_exit: bool, // Print the request and response after quitting
+    pub environment: Option<String>, // The name of the environment whose variables are used
+    pub mouse_captured: bool,
+    pub mouse_targets: RefCell<Vec<(Rect, MouseTarget)>>, // Replaced every time the UI is drawn
 }
 
 impl App {
@@ -70,11 +266,46 @@ impl App {
             method: HttpMethod::GET,
             section_values: SectionValues {
                 request_body: HashMap::new(),
+                raw_request_body: None,
+                graphql: None,
                 request_headers: HashMap::new(),
-                response_body: HashMap::new(),
+                response_body: None,
                 response_headers: HashMap::new(),
             },
             selected_section: Section::RequestBody(None),
+            unfocused_section: Section::ResponseBody(None),
+            response_filter: None,
+            filter_history: Vec::new(),
+            response_version: 0,
+            displayed_response: RefCell::new(DisplayedResponse {
+                key: None,
+                body: Ok(JsonValue::Null),
+                graphql_errors: Vec::new(),
+            }),
+            response_tree: JsonTreeState::new(),
+            horizontal_scroll: 0,
+            max_horizontal_scroll: Cell::new(0),
+            wrap_values: false,
+            last_response: None,
+            saved_name: None,
+            saved_request: None,
+            http_file: None,
+            har_entries: Vec::new(),
+            schemas: HashMap::new(),
+            history: Vec::new(),
+            pinned_baseline: None,
+            tabs: vec![Tab::new()],
+            active_tab: 0,
+            config: Config::default(),
+            config_loaded: false,
+            keymap: Keymap::new(),
+            theme: Theme::dark(),
+            notifications: Notifications::new(),
+            show_help: false,
+            print_on_exit: false,
+            environment: None,
+            mouse_captured: true,
+            mouse_targets: RefCell::new(Vec::new()),
         }
     }
 
@@ -134,170 +365,216 @@ impl App {
     }
 
     pub fn increment_selection(&mut self, reverse: bool) {
-        match self.current_screen {
-            CurrentScreen::Main => {
-                match self.selected_section {
-                    Section::RequestBody(ref index) => {
-                        let selected_section_length = self.section_values.request_body.len() as u8;
-                        let selected_index = index.clone();
-                        let new_index =
-                            evaluate_new_index(selected_index, selected_section_length, reverse);
-                        self.selected_section = Section::RequestBody(Some(new_index));
-                    }
-                    Section::RequestHeaders(ref index) => {
-                        let selected_section_length =
-                            self.section_values.request_headers.len() as u8;
-                        let selected_index = index.clone();
-                        let new_index =
-                            evaluate_new_index(selected_index, selected_section_length, reverse);
-                        self.selected_section = Section::RequestHeaders(Some(new_index));
-                    }
-                    Section::ResponseBody(ref index) => {
-                        let selected_section_length = self.section_values.response_body.len() as u8;
-                        let selected_index = index.clone();
-                        let new_index =
-                            evaluate_new_index(selected_index, selected_section_length, reverse);
-                        self.selected_section = Section::ResponseBody(Some(new_index));
-                    }
-                    Section::ResponseHeaders(ref index) => {
-                        let selected_section_length =
-                            self.section_values.response_headers.len() as u8;
-                        let selected_index = index.clone();
-                        let new_index =
-                            evaluate_new_index(selected_index, selected_section_length, reverse);
-                        self.selected_section = Section::ResponseHeaders(Some(new_index));
-                    }
-                };
-            }
-            _ => return,
+        if let CurrentScreen::Main = self.current_screen {
+            match self.selected_section {
+                Section::RequestBody(ref index) => {
+                    let selected_section_length = match (
+                        &self.section_values.graphql,
+                        &self.section_values.raw_request_body,
+                    ) {
+                        (Some(graphql), _) => graphql.query.lines().count(),
+                        (None, Some(raw_body)) => raw_body.lines().count(),
+                        (None, None) => self.section_values.request_body.len(),
+                    };
+                    let selected_index = *index;
+                    let new_index =
+                        evaluate_new_index(selected_index, selected_section_length, reverse);
+                    self.selected_section = Section::RequestBody(new_index);
+                }
+                Section::RequestHeaders(ref index) => {
+                    let selected_section_length = self.section_values.request_headers.len();
+                    let selected_index = *index;
+                    let new_index =
+                        evaluate_new_index(selected_index, selected_section_length, reverse);
+                    self.selected_section = Section::RequestHeaders(new_index);
+                }
+                Section::ResponseBody(ref index) => {
+                    // The response body is displayed as a tree, so count its visible lines
+                    let selected_section_length = match *self.displayed_response_body() {
+                        Ok(ref response_body) => flatten(response_body, &self.response_tree).len(),
+                        Err(_) => 0,
+                    };
+                    let selected_index = *index;
+                    let new_index =
+                        evaluate_new_index(selected_index, selected_section_length, reverse);
+                    self.selected_section = Section::ResponseBody(new_index);
+                }
+                Section::ResponseHeaders(ref index) => {
+                    let selected_section_length = self.section_values.response_headers.len();
+                    let selected_index = *index;
+                    let new_index =
+                        evaluate_new_index(selected_index, selected_section_length, reverse);
+                    self.selected_section = Section::ResponseHeaders(new_index);
+                }
+            };
         }
     }
 
+    pub fn scroll_horizontal(&mut self, reverse: bool) {
+        let max_scroll = self.max_horizontal_scroll.get();
+        self.horizontal_scroll = match reverse {
+            false => (self.horizontal_scroll + 4).min(max_scroll),
+            true => self.horizontal_scroll.min(max_scroll).saturating_sub(4),
+        };
+    }
+
+    pub fn toggle_wrap(&mut self) {
+        self.wrap_values = !self.wrap_values;
+        self.horizontal_scroll = 0;
+    }
+
     pub fn add_item(&mut self) {
-        match self.current_screen {
-            CurrentScreen::Main => match self.selected_section {
+        if let (Section::RequestBody(_), Some(_)) =
+            (self.selected_section, &self.section_values.graphql)
+        {
+            self.open_body_editor();
+            return;
+        }
+        if let (Section::RequestBody(_), Some(_)) =
+            (self.selected_section, &self.section_values.raw_request_body)
+        {
+            self.open_body_editor();
+            return;
+        }
+        if let CurrentScreen::Main = self.current_screen {
+            match self.selected_section {
                 Section::RequestBody(_) => {
                     self.current_screen = CurrentScreen::PairInput(PairInputState {
                         mode: PairInputMode::Add,
                         selected_item: KeyValuePair::Key,
-                        key: String::new(),
-                        value: String::new(),
+                        key: LineEditor::new(""),
+                        value: LineEditor::new(""),
                     });
                 }
                 Section::RequestHeaders(_) => {
                     self.current_screen = CurrentScreen::PairInput(PairInputState {
                         mode: PairInputMode::Add,
                         selected_item: KeyValuePair::Key,
-                        key: String::new(),
-                        value: String::new(),
+                        key: LineEditor::new(""),
+                        value: LineEditor::new(""),
                     });
                 }
-                _ => return,
-            },
-            _ => return,
+                _ => {}
+            }
         }
     }
 
     pub fn edit_item(&mut self) {
-        let selected_index = match self.selected_section {
-            Section::RequestBody(ref index) => index.clone(),
-            Section::RequestHeaders(ref index) => index.clone(),
+        if let (Section::RequestBody(_), Some(_)) =
+            (self.selected_section, &self.section_values.graphql)
+        {
+            self.open_body_editor();
+            return;
+        }
+        if let (Section::RequestBody(_), Some(_)) =
+            (self.selected_section, &self.section_values.raw_request_body)
+        {
+            self.open_body_editor();
+            return;
+        }
+        let (selected_section_values, selected_index) = match self.selected_section {
+            Section::RequestBody(index) => (&self.section_values.request_body, index),
+            Section::RequestHeaders(index) => (&self.section_values.request_headers, index),
             _ => return,
         };
 
-        match selected_index {
-            Some(edit_index) => {
-                let edit_key = self
-                    .section_values
-                    .request_body
-                    .keys()
-                    .nth(edit_index as usize)
-                    .unwrap()
-                    .clone();
-                let edit_value = self
-                    .section_values
-                    .request_body
-                    .get(&edit_key)
-                    .unwrap()
-                    .clone();
-                self.current_screen = CurrentScreen::PairInput(PairInputState {
-                    mode: PairInputMode::Edit(edit_key.clone()),
-                    selected_item: KeyValuePair::Key,
-                    key: edit_key,
-                    value: edit_value.to_string(),
-                });
-            }
-            None => return,
+        let selected_item =
+            selected_index.and_then(|index| selected_section_values.iter().nth(index));
+        if let Some((edit_key, edit_value)) = selected_item {
+            // Values are written back as strings, so edit them without their quotes
+            let edit_value = match edit_value {
+                JsonValue::String(value) => value.clone(),
+                value => value.to_string(),
+            };
+            self.current_screen = CurrentScreen::PairInput(PairInputState {
+                mode: PairInputMode::Edit(edit_key.clone()),
+                selected_item: KeyValuePair::Key,
+                key: LineEditor::new(edit_key),
+                value: LineEditor::new(&edit_value),
+            });
         }
     }
 
     pub fn write_item(&mut self) {
-        match self.current_screen {
-            CurrentScreen::PairInput(ref mut input_state) => {
-                let selected_section_values = match self.selected_section {
-                    Section::RequestBody(_) => &mut self.section_values.request_body,
-                    Section::RequestHeaders(_) => &mut self.section_values.request_headers,
-                    _ => return,
-                };
+        if let CurrentScreen::PairInput(ref mut input_state) = self.current_screen {
+            let selected_section_values = match self.selected_section {
+                Section::RequestBody(_) => &mut self.section_values.request_body,
+                Section::RequestHeaders(_) => &mut self.section_values.request_headers,
+                _ => return,
+            };
 
-                match input_state.mode {
-                    PairInputMode::Add => {
-                        selected_section_values.insert(
-                            input_state.key.clone(),
-                            JsonValue::String(input_state.value.clone()),
-                        );
-                    }
-                    PairInputMode::Edit(ref key) => {
-                        if input_state.key != *key {
-                            selected_section_values.remove(key);
-                        }
-                        selected_section_values.insert(
-                            input_state.key.clone(),
-                            JsonValue::String(input_state.value.clone()),
-                        );
+            match input_state.mode {
+                PairInputMode::Add => {
+                    selected_section_values.insert(
+                        input_state.key.text().to_string(),
+                        JsonValue::String(input_state.value.text().to_string()),
+                    );
+                }
+                PairInputMode::Edit(ref key) => {
+                    if input_state.key.text() != key {
+                        selected_section_values.remove(key);
                     }
-                };
-            }
-            _ => return,
+                    selected_section_values.insert(
+                        input_state.key.text().to_string(),
+                        JsonValue::String(input_state.value.text().to_string()),
+                    );
+                }
+            };
         }
     }
 
     pub fn delete_item(&mut self) {
+        if let (Section::RequestBody(_), Some(_)) =
+            (self.selected_section, &self.section_values.raw_request_body)
+        {
+            return;
+        }
+        if let (Section::RequestBody(_), Some(_)) =
+            (self.selected_section, &self.section_values.graphql)
+        {
+            return;
+        }
         let delete_index = match self.selected_section {
-            Section::RequestBody(ref index) => index.clone(),
-            Section::RequestHeaders(ref index) => index.clone(),
+            Section::RequestBody(ref index) => *index,
+            Section::RequestHeaders(ref index) => *index,
             _ => return,
         };
 
-        match delete_index {
-            Some(delete_index) => match self.selected_section {
+        if let Some(delete_index) = delete_index {
+            match self.selected_section {
                 Section::RequestBody(_) => {
                     let delete_key = self
                         .section_values
                         .request_body
                         .keys()
-                        .nth(delete_index as usize)
+                        .nth(delete_index)
                         .unwrap()
                         .clone();
                     self.section_values.request_body.remove(&delete_key);
+                    let new_length = self.section_values.request_body.len();
+                    self.selected_section =
+                        Section::RequestBody(clamp_index(Some(delete_index), new_length));
                 }
                 Section::RequestHeaders(_) => {
                     let delete_key = self
                         .section_values
                         .request_headers
                         .keys()
-                        .nth(delete_index as usize)
+                        .nth(delete_index)
                         .unwrap()
                         .clone();
                     self.section_values.request_headers.remove(&delete_key);
+                    let new_length = self.section_values.request_headers.len();
+                    self.selected_section =
+                        Section::RequestHeaders(clamp_index(Some(delete_index), new_length));
                 }
-                _ => return,
-            },
-            None => return,
+                _ => {}
+            }
         }
     }
 
     pub fn increment_section(&mut self, reverse: bool) {
+        let previous_section = self.selected_section;
         match self.selected_section {
             Section::RequestBody(_) => match reverse {
                 false => {
@@ -332,17 +609,93 @@ impl App {
                 }
             },
         }
+        // Keep the other pane of the split layout showing the section we moved away from
+        if previous_section.is_request_section() != self.selected_section.is_request_section() {
+            self.unfocused_section = previous_section;
+        }
     }
 
-    pub fn toggle_input_field(&mut self) {
-        match self.current_screen {
-            CurrentScreen::PairInput(ref mut input_state) => {
-                match input_state.selected_item {
-                    KeyValuePair::Key => input_state.selected_item = KeyValuePair::Value,
-                    KeyValuePair::Value => input_state.selected_item = KeyValuePair::Key,
-                };
+    /// The topmost target at a position on the screen
+    pub fn mouse_target_at(&self, column: u16, row: u16) -> Option<MouseTarget> {
+        self.mouse_targets
+            .borrow()
+            .iter()
+            .rev()
+            .find(|(area, _)| {
+                column >= area.x && column < area.right() && row >= area.y && row < area.bottom()
+            })
+            .map(|(_, target)| *target)
+    }
+
+    /// Focus the request or response pane, switching to it if it isn't visible
+    pub fn focus_pane(&mut self, is_request_pane: bool) {
+        if self.selected_section.is_request_section() == is_request_pane {
+            return;
+        }
+        match self.config.layout.mode {
+            LayoutMode::Split => self.switch_pane(),
+            LayoutMode::Single => {
+                self.selected_section = match is_request_pane {
+                    true => Section::RequestBody(None),
+                    false => Section::ResponseBody(None),
+                }
             }
-            _ => return,
+        }
+    }
+
+    pub fn select_item(&mut self, index: usize) {
+        self.selected_section = match self.selected_section {
+            Section::RequestBody(_) => Section::RequestBody(Some(index)),
+            Section::RequestHeaders(_) => Section::RequestHeaders(Some(index)),
+            Section::ResponseBody(_) => Section::ResponseBody(Some(index)),
+            Section::ResponseHeaders(_) => Section::ResponseHeaders(Some(index)),
+        };
+    }
+
+    pub fn switch_pane(&mut self) {
+        if let (CurrentScreen::Main, LayoutMode::Split) =
+            (&self.current_screen, &self.config.layout.mode)
+        {
+            std::mem::swap(&mut self.selected_section, &mut self.unfocused_section);
+        }
+    }
+
+    pub fn toggle_layout(&mut self) {
+        self.config.layout.mode = match self.config.layout.mode {
+            LayoutMode::Single => LayoutMode::Split,
+            LayoutMode::Split => LayoutMode::Single,
+        };
+        self.save_layout();
+    }
+
+    pub fn resize_panes(&mut self, reverse: bool) {
+        if let LayoutMode::Split = self.config.layout.mode {
+            let split_percent = self.config.layout.split_percent;
+            self.config.layout.split_percent = match reverse {
+                false => (split_percent + 5).min(80),
+                true => split_percent.saturating_sub(5).max(20),
+            };
+            self.save_layout();
+        }
+    }
+
+    fn save_layout(&mut self) {
+        // Saving over a config file which couldn't be loaded would lose what's in it
+        if !self.config_loaded {
+            return;
+        }
+        if let Err(err) = save_layout(&self.config.layout) {
+            self.notifications
+                .error("Unable to save the layout", Some(err.to_string()));
+        }
+    }
+
+    pub fn toggle_input_field(&mut self) {
+        if let CurrentScreen::PairInput(ref mut input_state) = self.current_screen {
+            match input_state.selected_item {
+                KeyValuePair::Key => input_state.selected_item = KeyValuePair::Value,
+                KeyValuePair::Value => input_state.selected_item = KeyValuePair::Key,
+            };
         };
     }
 
@@ -354,48 +707,1489 @@ impl App {
             _ => return,
         };
 
-        let endpoint = self.endpoint.clone();
-        let method = self.method.clone();
-        let headers = self.section_values.request_headers.clone();
-        let body = self.section_values.request_body.clone();
-
-        if endpoint.is_empty() {
+        if self.endpoint.is_empty() {
             self.current_screen = CurrentScreen::Main;
-            // TODO: Implement alert popup and display one here
+            self.notifications
+                .error("Enter an endpoint before sending the request", None);
             return;
         }
 
-        let api_response = make_http_request(endpoint, method, headers, body).await;
+        let variables = self.variables();
+        let mut unknown_variables = Vec::<String>::new();
+        let endpoint = substitute(&self.endpoint, &variables, &mut unknown_variables);
+        let method = self.method.clone();
+        let headers = substitute_values(
+            &self.section_values.request_headers,
+            &variables,
+            &mut unknown_variables,
+        );
+        let body = match (
+            &self.section_values.graphql,
+            &self.section_values.raw_request_body,
+        ) {
+            (Some(graphql), _) => match graphql.body() {
+                Ok(body) => RequestBody::Raw(substitute(&body, &variables, &mut unknown_variables)),
+                Err(err) => {
+                    self.current_screen = CurrentScreen::Main;
+                    self.notifications
+                        .error("Unable to send the GraphQL request", Some(err));
+                    return;
+                }
+            },
+            (None, Some(raw_body)) => {
+                RequestBody::Raw(substitute(raw_body, &variables, &mut unknown_variables))
+            }
+            (None, None) => RequestBody::Pairs(substitute_values(
+                &self.section_values.request_body,
+                &variables,
+                &mut unknown_variables,
+            )),
+        };
+        if !unknown_variables.is_empty() {
+            self.notifications.warning(format!(
+                "Sent without values for {}",
+                unknown_variables
+                    .iter()
+                    .map(|name| format!("{{{{{}}}}}", name))
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ));
+        }
+
+        let timeout = Duration::from_secs(self.config.request.timeout_seconds);
+        let api_response = make_http_request(endpoint, method, headers, body, timeout).await;
+        self.response_tree.reset();
+        if let Section::ResponseBody(_) = self.selected_section {
+            self.selected_section = Section::ResponseBody(None);
+        }
 
         match api_response {
             Ok(api_response) => {
-                let response_body = serde_json::from_str(&api_response);
+                let reason = reqwest::StatusCode::from_u16(api_response.status)
+                    .ok()
+                    .and_then(|status| status.canonical_reason())
+                    .unwrap_or_default();
+                let status = format!("{} {}", api_response.status, reason);
+                match api_response.status {
+                    400.. => self.notifications.warning(status),
+                    _ => self.notifications.success(status),
+                }
+                self.section_values.response_headers = api_response
+                    .headers
+                    .iter()
+                    .map(|(key, value)| (key.clone(), JsonValue::String(value.clone())))
+                    .collect();
+                let response_body = serde_json::from_str(&api_response.body);
                 match response_body {
                     Ok(response_body) => {
-                        self.section_values.response_body = response_body;
+                        self.section_values.response_body = Some(response_body);
+                        self.response_version += 1;
+                        let error_count = self.graphql_errors().len();
+                        match error_count {
+                            0 => {}
+                            1 => self
+                                .notifications
+                                .warning("The response has a GraphQL error"),
+                            count => self
+                                .notifications
+                                .warning(format!("The response has {} GraphQL errors", count)),
+                        }
                     }
                     Err(err) => {
-                        // TODO: Implement alert popup and display one here
-                        self.section_values.response_body.insert(
-                            "Error parsing response".to_string(),
-                            JsonValue::String(format!("{:?}", err)),
-                        );
-                        self.section_values.response_body.insert(
-                            "Response string".to_string(),
-                            JsonValue::String(format!("{:?}", api_response)),
-                        );
+                        self.section_values.response_body = None;
+                        self.response_version += 1;
+                        if !api_response.body.is_empty() {
+                            self.notifications.warning(format!(
+                                "The response body isn't JSON ({}), view it with {}",
+                                err,
+                                self.keymap
+                                    .key_for(KeymapContext::Main, Action::ViewResponse)
+                                    .unwrap_or_default()
+                            ));
+                        }
                     }
                 }
+                self.history.push(HistoryEntry {
+                    method: self.method.clone(),
+                    endpoint: self.endpoint.clone(),
+                    section_values: self.section_values.clone(),
+                    response: api_response.clone(),
+                });
+                // Drop the oldest entries, which each hold a whole response
+                let excess = self
+                    .history
+                    .len()
+                    .saturating_sub(self.config.history.max_entries.max(1));
+                if excess > 0 {
+                    self.history.drain(..excess);
+                    self.pinned_baseline = self
+                        .pinned_baseline
+                        .and_then(|pinned_index| pinned_index.checked_sub(excess));
+                }
+                self.last_response = Some(api_response);
             }
             Err(err) => {
-                // TODO: Implement alert popup and display one here
-                self.section_values.response_body.insert(
-                    "Error making request".to_string(),
-                    JsonValue::String(format!("{:?}", err)),
-                );
+                // Don't leave the previous response on show as if it were this one's
+                self.section_values.response_body = None;
+                self.section_values.response_headers = HashMap::new();
+                self.response_version += 1;
+                self.last_response = None;
+                self.notifications
+                    .error(err.to_string(), Some(err.details()));
+                self.current_screen = CurrentScreen::Main;
+                return;
             }
         }
 
         self.current_screen = CurrentScreen::Main;
     }
+
+    /// The {{variables}} of the environment, and of the .http file if there is one
+    fn variables(&self) -> HashMap<String, String> {
+        let variables = match self.environment {
+            Some(ref name) => self.config.environments.get(name).cloned(),
+            None => None,
+        }
+        .unwrap_or_default();
+        match self.http_file {
+            Some(ref http_file) => resolve_variables(&http_file.variables, &variables),
+            None => variables,
+        }
+    }
+
+    /// Switch between sending the body as it is and sending a GraphQL query
+    pub fn toggle_graphql(&mut self) {
+        if let CurrentScreen::Main = self.current_screen {
+            match self.section_values.graphql {
+                Some(ref graphql) => {
+                    // Keep the query as the body it would have been sent as
+                    match graphql.body() {
+                        Ok(_) if graphql.query.trim().is_empty() => {}
+                        Ok(body) => self.section_values.raw_request_body = Some(body),
+                        Err(err) => {
+                            self.notifications
+                                .error("Unable to switch to a plain request body", Some(err));
+                            return;
+                        }
+                    }
+                    self.section_values.graphql = None;
+                    self.notifications.info("Switched to a plain request body");
+                }
+                None => {
+                    // Carry on with a GraphQL body which was written by hand
+                    let graphql = self
+                        .section_values
+                        .raw_request_body
+                        .as_deref()
+                        .and_then(GraphqlRequest::from_body)
+                        .unwrap_or_default();
+                    self.section_values.graphql = Some(graphql);
+                    self.method = HttpMethod::POST;
+                    self.selected_section = Section::RequestBody(None);
+                    self.notifications.info(format!(
+                        "Switched to GraphQL, edit the query with {}",
+                        self.keymap
+                            .key_for(KeymapContext::Main, Action::EditRawBody)
+                            .unwrap_or_default()
+                    ));
+                }
+            }
+        }
+    }
+
+    pub fn open_graphql_editor(&mut self) {
+        if let Some(ref graphql) = self.section_values.graphql {
+            self.current_screen = CurrentScreen::GraphqlEditor(GraphqlEditorState {
+                query: TextEditor::new(&graphql.query),
+                variables: TextEditor::new(&graphql.variables),
+                operation_name: LineEditor::new(&graphql.operation_name),
+                focus: GraphqlField::Query,
+                message: None,
+            });
+        }
+    }
+
+    pub fn save_graphql_editor(&mut self) {
+        if let CurrentScreen::GraphqlEditor(ref editor_state) = self.current_screen {
+            self.section_values.graphql = Some(GraphqlRequest {
+                query: editor_state.query.text(),
+                variables: editor_state.variables.text(),
+                operation_name: editor_state.operation_name.text().trim().to_string(),
+            });
+            self.current_screen = CurrentScreen::Main;
+        }
+    }
+
+    pub fn switch_graphql_field(&mut self) {
+        if let CurrentScreen::GraphqlEditor(ref mut editor_state) = self.current_screen {
+            editor_state.focus = match editor_state.focus {
+                GraphqlField::Query => GraphqlField::Variables,
+                GraphqlField::Variables => GraphqlField::OperationName,
+                GraphqlField::OperationName => GraphqlField::Query,
+            };
+        }
+    }
+
+    pub fn schema(&self) -> Option<&Schema> {
+        self.schemas.get(&self.endpoint)
+    }
+
+    /// Introspect the endpoint's schema, with the request's headers in case it needs them
+    pub async fn fetch_schema(&mut self) -> bool {
+        let variables = self.variables();
+        let mut unknown_variables = Vec::<String>::new();
+        let endpoint = substitute(&self.endpoint, &variables, &mut unknown_variables);
+        let headers = substitute_values(
+            &self.section_values.request_headers,
+            &variables,
+            &mut unknown_variables,
+        );
+        let body = json!({ "query": INTROSPECTION_QUERY }).to_string();
+        let response = make_http_request(
+            endpoint,
+            HttpMethod::POST,
+            headers,
+            RequestBody::Raw(body),
+            Duration::from_secs(self.config.request.timeout_seconds),
+        )
+        .await;
+        let schema = match response {
+            Ok(response) => serde_json::from_str::<JsonValue>(&response.body)
+                .map_err(|err| format!("The response isn't JSON: {}", err))
+                .and_then(|body| Schema::from_introspection(&body)),
+            Err(err) => Err(format!("{}\n\n{}", err, err.details())),
+        };
+        match schema {
+            Ok(schema) => {
+                self.notifications.success(format!(
+                    "Fetched the GraphQL schema, with {} types",
+                    schema.types.len()
+                ));
+                self.schemas.insert(self.endpoint.clone(), schema);
+                true
+            }
+            Err(err) => {
+                self.notifications
+                    .error("Unable to fetch the GraphQL schema", Some(err));
+                false
+            }
+        }
+    }
+
+    /// Complete the field being typed in the query, or list the fields it could be
+    pub fn complete_graphql_field(&mut self) {
+        let Some(schema) = self.schemas.get(&self.endpoint) else {
+            return;
+        };
+        let CurrentScreen::GraphqlEditor(ref mut editor_state) = self.current_screen else {
+            return;
+        };
+        if editor_state.focus != GraphqlField::Query {
+            return;
+        }
+        let (partial, fields) = schema.completions(&editor_state.query.text_before_cursor());
+        let names: Vec<&str> = fields.iter().map(|field| field.name.as_str()).collect();
+        // Complete as far as all the candidates agree
+        let common_prefix = names.iter().skip(1).fold(
+            names.first().copied().unwrap_or_default(),
+            |prefix, name| {
+                let length = prefix
+                    .chars()
+                    .zip(name.chars())
+                    .take_while(|(a, b)| a == b)
+                    .map(|(a, _)| a.len_utf8())
+                    .sum();
+                &prefix[..length]
+            },
+        );
+        if common_prefix.len() > partial.len() {
+            editor_state
+                .query
+                .insert_str(&common_prefix[partial.len()..]);
+        }
+        editor_state.message = Some(match fields.as_slice() {
+            [] => "Nothing to complete here".to_string(),
+            [field] => format!("{}: {}", field.name, field.type_name),
+            _ => names.join("  "),
+        });
+    }
+
+    pub fn open_schema_browser(&mut self) {
+        if self.schema().is_some() {
+            self.current_screen = CurrentScreen::SchemaBrowser(SchemaBrowserState {
+                path: Vec::new(),
+                selected: 0,
+            });
+        }
+    }
+
+    /// The entries shown in the schema browser
+    pub fn schema_items(&self) -> Vec<SchemaItem> {
+        let type_name = match self.current_screen {
+            CurrentScreen::SchemaBrowser(ref browser_state) => browser_state.path.last(),
+            _ => None,
+        };
+        match self.schema() {
+            Some(schema) => schema.browse(type_name.map(|(type_name, _)| type_name.as_str())),
+            None => Vec::new(),
+        }
+    }
+
+    pub fn select_schema_item(&mut self, index: usize) {
+        let last = self.schema_items().len().saturating_sub(1);
+        if let CurrentScreen::SchemaBrowser(ref mut browser_state) = self.current_screen {
+            browser_state.selected = index.min(last);
+        }
+    }
+
+    /// Show the fields of the selected entry's type
+    pub fn open_schema_item(&mut self) {
+        let items = self.schema_items();
+        if let CurrentScreen::SchemaBrowser(ref mut browser_state) = self.current_screen {
+            if let Some(target) = items
+                .get(browser_state.selected)
+                .and_then(|item| item.target.clone())
+            {
+                browser_state.path.push((target, browser_state.selected));
+                browser_state.selected = 0;
+            }
+        }
+    }
+
+    /// Go back to the type the current one was opened from
+    pub fn close_schema_item(&mut self) {
+        if let CurrentScreen::SchemaBrowser(ref mut browser_state) = self.current_screen {
+            if let Some((_, selected)) = browser_state.path.pop() {
+                browser_state.selected = selected;
+            }
+        }
+    }
+
+    /// The errors of a GraphQL response, which are shown apart from its data
+    pub fn graphql_errors(&self) -> Ref<'_, Vec<String>> {
+        self.update_displayed_response();
+        Ref::map(self.displayed_response.borrow(), |displayed| {
+            &displayed.graphql_errors
+        })
+    }
+
+    /// Edit the request body as raw text, starting from the pairs if there isn't any yet
+    pub fn open_body_editor(&mut self) {
+        if let (CurrentScreen::Main, Section::RequestBody(_)) =
+            (&self.current_screen, &self.selected_section)
+        {
+            if self.section_values.graphql.is_some() {
+                self.open_graphql_editor();
+                return;
+            }
+            let text = match self.section_values.raw_request_body {
+                Some(ref raw_body) => raw_body.clone(),
+                None if self.section_values.request_body.is_empty() => String::new(),
+                None => serde_json::to_string_pretty(&self.section_values.request_body)
+                    .unwrap_or_default(),
+            };
+            self.current_screen = CurrentScreen::BodyEditor(BodyEditorState {
+                editor: TextEditor::new(&text),
+                message: None,
+            });
+        }
+    }
+
+    /// Save the raw body, or go back to editing pairs if it was emptied
+    pub fn save_body_editor(&mut self) {
+        if let CurrentScreen::BodyEditor(ref editor_state) = self.current_screen {
+            let text = editor_state.editor.text();
+            self.section_values.raw_request_body = match text.trim().is_empty() {
+                true => None,
+                false => Some(text),
+            };
+            self.section_values.request_body.clear();
+            self.selected_section = Section::RequestBody(None);
+            self.current_screen = CurrentScreen::Main;
+        }
+    }
+
+    /// The active request as text, for editing outside of tapir
+    pub fn request_file(&self) -> RequestFile {
+        let mut headers: Vec<(String, String)> = self
+            .section_values
+            .request_headers
+            .iter()
+            .map(|(key, value)| match value {
+                JsonValue::String(value) => (key.clone(), value.clone()),
+                _ => (key.clone(), value.to_string()),
+            })
+            .collect();
+        headers.sort();
+        let graphql_body = self
+            .section_values
+            .graphql
+            .as_ref()
+            .map(|graphql| graphql.body().unwrap_or_default());
+        let body = match graphql_body.or(self.section_values.raw_request_body.clone()) {
+            Some(raw_body) => raw_body,
+            None if self.section_values.request_body.is_empty() => String::new(),
+            None => {
+                serde_json::to_string_pretty(&self.section_values.request_body).unwrap_or_default()
+            }
+        };
+        RequestFile {
+            method: self.method.clone(),
+            endpoint: self.endpoint.clone(),
+            headers,
+            body,
+        }
+    }
+
+    /// The request and the last response as one JSON document, for printing on exit
+    pub fn session_json(&self) -> JsonValue {
+        // Repeated headers, such as Set-Cookie, become arrays
+        let headers_json = |pairs: &[(String, String)]| {
+            let mut headers = serde_json::Map::new();
+            for (key, value) in pairs.iter() {
+                let value = JsonValue::String(value.clone());
+                match headers.get_mut(key) {
+                    Some(JsonValue::Array(values)) => values.push(value),
+                    Some(existing) => *existing = JsonValue::Array(vec![existing.take(), value]),
+                    None => {
+                        headers.insert(key.clone(), value);
+                    }
+                }
+            }
+            JsonValue::Object(headers)
+        };
+        // The request as it was sent if it has been, otherwise as it would be
+        let request = match self.last_response {
+            Some(ref response) => json!({
+                "method": response.request.method,
+                "endpoint": response.request.url,
+                "headers": headers_json(&response.request.headers),
+                "body": match response.request.body {
+                    Some(ref body) => json_or_string(body),
+                    None => JsonValue::Null,
+                },
+            }),
+            None => {
+                let request = self.substituted_request_file(&mut Vec::new());
+                json!({
+                    "method": request.method.to_string(),
+                    "endpoint": request.endpoint,
+                    "headers": headers_json(&request.headers),
+                    "body": match request.body.is_empty() {
+                        true => JsonValue::Null,
+                        false => json_or_string(&request.body),
+                    },
+                })
+            }
+        };
+        let response = match self.last_response {
+            Some(ref response) => json!({
+                "status": response.status,
+                "headers": headers_json(&response.headers),
+                "body": json_or_string(&response.body),
+            }),
+            None => JsonValue::Null,
+        };
+        json!({
+            "request": request,
+            "response": response,
+        })
+    }
+
+    /// The request file with the variables filled in, noting any which have no value
+    fn substituted_request_file(&self, unknown_variables: &mut Vec<String>) -> RequestFile {
+        let variables = self.variables();
+        let mut request = self.request_file();
+        request.endpoint = substitute(&request.endpoint, &variables, unknown_variables);
+        request.headers = request
+            .headers
+            .iter()
+            .map(|(key, value)| {
+                (
+                    key.clone(),
+                    substitute(value, &variables, unknown_variables),
+                )
+            })
+            .collect();
+        request.body = match (
+            &self.section_values.graphql,
+            &self.section_values.raw_request_body,
+        ) {
+            // Pairs are filled in before encoding, as when sending
+            (None, None) if !self.section_values.request_body.is_empty() => {
+                serde_json::to_string_pretty(&substitute_values(
+                    &self.section_values.request_body,
+                    &variables,
+                    unknown_variables,
+                ))
+                .unwrap_or_default()
+            }
+            _ => substitute(&request.body, &variables, unknown_variables),
+        };
+        request
+    }
+
+    /// Replace the active request, keeping the body as pairs if it is a flat object of strings
+    pub fn load_request_file(&mut self, request: RequestFile) {
+        // A GraphQL body stays a query while it can be read as one
+        let graphql = match self.section_values.graphql {
+            Some(_) => GraphqlRequest::from_body(&request.body),
+            None => None,
+        };
+        let request = saved_request_from_file(request);
+        self.method = request.method;
+        self.endpoint = request.endpoint;
+        self.section_values.request_headers = request.request_headers;
+        self.section_values.request_body = request.request_body;
+        self.section_values.raw_request_body = request.raw_request_body;
+        self.section_values.graphql = graphql;
+
+        match self.selected_section {
+            Section::RequestBody(_) => self.selected_section = Section::RequestBody(None),
+            Section::RequestHeaders(_) => self.selected_section = Section::RequestHeaders(None),
+            _ => {}
+        }
+    }
+
+    /// The text input which has focus, if any
+    pub fn active_line_editor(&mut self) -> Option<&mut LineEditor> {
+        match self.current_screen {
+            CurrentScreen::EndpointInput(ref mut editor) => Some(editor),
+            CurrentScreen::FilterInput(ref mut input_state) => Some(&mut input_state.expression),
+            CurrentScreen::SaveInput(ref mut input_state) => Some(&mut input_state.name),
+            CurrentScreen::HarExportInput(ref mut editor) => Some(editor),
+            CurrentScreen::GraphqlEditor(ref mut editor_state)
+                if editor_state.focus == GraphqlField::OperationName =>
+            {
+                Some(&mut editor_state.operation_name)
+            }
+            CurrentScreen::PairInput(ref mut input_state) => match input_state.selected_item {
+                KeyValuePair::Key => Some(&mut input_state.key),
+                KeyValuePair::Value => Some(&mut input_state.value),
+            },
+            CurrentScreen::Pager(ref mut pager_state) if pager_state.is_searching => {
+                Some(&mut pager_state.search)
+            }
+            _ => None,
+        }
+    }
+
+    pub fn open_filter_input(&mut self) {
+        if let (CurrentScreen::Main, Section::ResponseBody(_)) =
+            (&self.current_screen, &self.selected_section)
+        {
+            self.current_screen = CurrentScreen::FilterInput(FilterInputState {
+                expression: LineEditor::new(self.response_filter.as_deref().unwrap_or_default()),
+                history_index: None,
+            });
+        }
+    }
+
+    pub fn apply_filter_input(&mut self) {
+        if let CurrentScreen::FilterInput(ref input_state) = self.current_screen {
+            let expression = input_state.expression.text().trim().to_string();
+            if expression.is_empty() {
+                self.response_filter = None;
+            } else {
+                self.filter_history
+                    .retain(|previous| *previous != expression);
+                self.filter_history.push(expression.clone());
+                self.response_filter = Some(expression);
+            }
+            self.response_tree.reset();
+            self.selected_section = Section::ResponseBody(None);
+            self.current_screen = CurrentScreen::Main;
+        }
+    }
+
+    pub fn cycle_filter_history(&mut self, reverse: bool) {
+        if let CurrentScreen::FilterInput(ref mut input_state) = self.current_screen {
+            if self.filter_history.is_empty() {
+                return;
+            }
+            let last_index = self.filter_history.len() - 1;
+            input_state.history_index = match (input_state.history_index, reverse) {
+                (None, true) => Some(last_index),
+                (None, false) => None,
+                (Some(0), true) => Some(0),
+                (Some(index), true) => Some(index - 1),
+                (Some(index), false) if index < last_index => Some(index + 1),
+                (Some(_), false) => None,
+            };
+            match input_state.history_index {
+                Some(index) => input_state.expression.set_text(&self.filter_history[index]),
+                None => input_state.expression.clear(),
+            };
+        }
+    }
+
+    /// Filter the response body again if it, the filter or the mode has changed since last time
+    fn update_displayed_response(&self) {
+        let key = (
+            self.response_version,
+            self.response_filter.clone(),
+            self.section_values.graphql.is_some(),
+        );
+        if self.displayed_response.borrow().key.as_ref() == Some(&key) {
+            return;
+        }
+        let mut response_body = self.section_values.response_json();
+        let graphql_errors = match key.2 {
+            true => response_errors(&response_body),
+            false => Vec::new(),
+        };
+        let body = match self.response_filter {
+            Some(ref expression) => apply_filter(expression, &response_body),
+            None => {
+                // GraphQL errors are shown above the data instead
+                if let (false, JsonValue::Object(ref mut fields)) =
+                    (graphql_errors.is_empty(), &mut response_body)
+                {
+                    fields.remove("errors");
+                }
+                Ok(response_body)
+            }
+        };
+        *self.displayed_response.borrow_mut() = DisplayedResponse {
+            key: Some(key),
+            body,
+            graphql_errors,
+        };
+    }
+
+    /// The response body as it should be displayed, with the active filter applied
+    pub fn displayed_response_body(&self) -> Ref<'_, Result<JsonValue, String>> {
+        self.update_displayed_response();
+        Ref::map(self.displayed_response.borrow(), |displayed| {
+            &displayed.body
+        })
+    }
+
+    pub fn toggle_tree_node(&mut self) {
+        let selected_index = match self.selected_section {
+            Section::ResponseBody(Some(index)) => index,
+            _ => return,
+        };
+        self.update_displayed_response();
+        if let Ok(ref response_body) = self.displayed_response.borrow().body {
+            let lines = flatten(response_body, &self.response_tree);
+            if let Some(line) = lines.get(selected_index) {
+                let path = line.path.clone();
+                self.response_tree.toggle(&path);
+                // Keep the cursor on the toggled node rather than its closing bracket
+                let lines = flatten(response_body, &self.response_tree);
+                self.selected_section =
+                    Section::ResponseBody(lines.iter().position(|line| line.path == path));
+            }
+        }
+    }
+
+    pub fn expand_all_tree_nodes(&mut self) {
+        self.response_tree.expand_all();
+    }
+
+    pub fn collapse_all_tree_nodes(&mut self) {
+        self.update_displayed_response();
+        if let Ok(ref response_body) = self.displayed_response.borrow().body {
+            self.response_tree.collapse_all(response_body);
+            if let Section::ResponseBody(_) = self.selected_section {
+                self.selected_section = Section::ResponseBody(Some(0));
+            }
+        }
+    }
+
+    /// The value of the selected item: a pair's value, a raw body line or a JSON node
+    pub fn selected_value(&self) -> Option<String> {
+        let value_text = |value: &JsonValue| match value {
+            JsonValue::String(text) => text.clone(),
+            _ => serde_json::to_string_pretty(value).unwrap_or_default(),
+        };
+        match self.selected_section {
+            Section::RequestBody(Some(index)) => match (
+                &self.section_values.graphql,
+                &self.section_values.raw_request_body,
+            ) {
+                (Some(graphql), _) => graphql.query.lines().nth(index).map(str::to_string),
+                (None, Some(raw_body)) => raw_body.lines().nth(index).map(str::to_string),
+                (None, None) => self
+                    .section_values
+                    .request_body
+                    .values()
+                    .nth(index)
+                    .map(value_text),
+            },
+            Section::RequestHeaders(Some(index)) => self
+                .section_values
+                .request_headers
+                .values()
+                .nth(index)
+                .map(value_text),
+            Section::ResponseBody(Some(index)) => {
+                let response_body = self.displayed_response_body();
+                let lines = flatten(response_body.as_ref().ok()?, &self.response_tree);
+                lines.get(index).map(|line| value_text(line.value))
+            }
+            Section::ResponseHeaders(Some(index)) => self
+                .section_values
+                .response_headers
+                .values()
+                .nth(index)
+                .map(value_text),
+            _ => None,
+        }
+    }
+
+    /// The whole of the selected section, as a body or as header lines
+    pub fn selected_section_text(&self) -> Option<String> {
+        let header_lines = |headers: &HashMap<String, JsonValue>| {
+            let mut lines: Vec<String> = headers
+                .iter()
+                .map(|(key, value)| format!("{}: {}", key, value.as_str().unwrap_or_default()))
+                .collect();
+            lines.sort();
+            lines.join("\n")
+        };
+        match self.selected_section {
+            Section::RequestBody(_) => match (
+                &self.section_values.graphql,
+                &self.section_values.raw_request_body,
+            ) {
+                (Some(graphql), _) => graphql.body().ok(),
+                (None, Some(raw_body)) => Some(raw_body.clone()),
+                (None, None) => {
+                    serde_json::to_string_pretty(&self.section_values.request_body).ok()
+                }
+            },
+            Section::RequestHeaders(_) => Some(header_lines(&self.section_values.request_headers)),
+            Section::ResponseBody(_) => {
+                serde_json::to_string_pretty(self.displayed_response_body().as_ref().ok()?).ok()
+            }
+            Section::ResponseHeaders(_) => match self.last_response {
+                // Keep the order in which the headers were received
+                Some(ref response) => Some(
+                    response
+                        .headers
+                        .iter()
+                        .map(|(key, value)| format!("{}: {}", key, value))
+                        .collect::<Vec<String>>()
+                        .join("\n"),
+                ),
+                None => Some(header_lines(&self.section_values.response_headers)),
+            },
+        }
+    }
+
+    pub fn copy_selected_value(&mut self) {
+        if let Some(text) = self.selected_value() {
+            self.copy(&text);
+        }
+    }
+
+    pub fn copy_selected_section(&mut self) {
+        if let Some(text) = self.selected_section_text() {
+            self.copy(&text);
+        }
+    }
+
+    pub fn copy_endpoint(&mut self) {
+        if !self.endpoint.is_empty() {
+            self.copy(&self.endpoint.clone());
+        }
+    }
+
+    pub fn copy_as_curl(&mut self) {
+        let request = self.substituted_request_file(&mut Vec::new());
+        self.copy(&curl_command(&request));
+    }
+
+    fn copy(&mut self, text: &str) {
+        match copy_to_clipboard(text, self.config.clipboard) {
+            Ok(()) => self.notifications.info("Copied to the clipboard"),
+            Err(err) => self
+                .notifications
+                .error("Unable to copy to the clipboard", Some(err.to_string())),
+        }
+    }
+
+    pub fn open_pager(&mut self) {
+        if let (CurrentScreen::Main, Some(_)) = (&self.current_screen, &self.last_response) {
+            let mut pager_state = PagerState::new();
+            if let Section::ResponseHeaders(_) = self.selected_section {
+                pager_state.content = PagerContent::Headers;
+            }
+            self.current_screen = CurrentScreen::Pager(pager_state);
+        }
+    }
+
+    pub fn pin_baseline(&mut self) {
+        match self.current_screen {
+            CurrentScreen::Main if !self.history.is_empty() => {
+                self.pinned_baseline = Some(self.history.len() - 1);
+            }
+            CurrentScreen::Diff(ref diff_state) => {
+                self.pinned_baseline = Some(diff_state.baseline_index);
+            }
+            _ => {}
+        }
+    }
+
+    /// Make the active tab an empty one, opening a new tab unless it already is
+    fn open_empty_tab(&mut self) {
+        let is_saved = self.saved_name.is_some() || self.http_file.is_some();
+        if !self.tab_request(self.active_tab).is_empty() || is_saved {
+            self.tabs.insert(self.active_tab + 1, Tab::new());
+            self.select_tab(self.active_tab + 1);
+        }
+    }
+
+    pub fn open_request_file(&mut self, request: RequestFile) {
+        self.open_empty_tab();
+        self.load_request_file(request);
+    }
+
+    /// Open a saved request, switching to it if it's already open
+    pub fn open_saved_request(&mut self, name: &str, request: SavedRequest) {
+        let open_tab = (0..self.tabs.len()).find(|index| match *index == self.active_tab {
+            true => self.saved_name.as_deref() == Some(name),
+            false => self.tabs[*index].saved_name.as_deref() == Some(name),
+        });
+        if let Some(index) = open_tab {
+            self.select_tab(index);
+            return;
+        }
+        self.open_request(request.clone());
+        self.saved_name = Some(name.to_string());
+        self.saved_request = Some(request);
+    }
+
+    /// Open each of the requests in a .http file in a new tab
+    pub fn open_http_file(&mut self, path: PathBuf, http_file: HttpFile) {
+        for skipped in http_file.skipped {
+            self.notifications.warning(skipped);
+        }
+        let mut first_tab = None;
+        for (index, request) in http_file.requests.into_iter().enumerate() {
+            self.open_request_file(request.request);
+            first_tab.get_or_insert(self.active_tab);
+            self.saved_request = Some(self.tab_request(self.active_tab));
+            self.http_file = Some(HttpFileSource {
+                path: path.clone(),
+                index,
+                variables: http_file.variables.clone(),
+            });
+        }
+        if let Some(first_tab) = first_tab {
+            self.select_tab(first_tab);
+        }
+    }
+
+    /// Open a request in a new tab, without a name to save it under
+    pub fn open_request(&mut self, request: SavedRequest) {
+        self.open_empty_tab();
+        self.method = request.method;
+        self.endpoint = request.endpoint;
+        self.section_values.request_headers = request.request_headers;
+        self.section_values.request_body = request.request_body;
+        self.section_values.raw_request_body = request.raw_request_body;
+        self.section_values.graphql = request.graphql;
+    }
+
+    pub fn open_save_input(&mut self, then_quit: bool) {
+        let name = match (&self.saved_name, &self.http_file) {
+            (Some(name), _) => name.clone(),
+            (None, Some(http_file)) => http_file.path.display().to_string(),
+            (None, None) => self.suggested_request_name(),
+        };
+        self.current_screen = CurrentScreen::SaveInput(SaveInputState {
+            name: LineEditor::new(&name),
+            then_quit,
+        });
+    }
+
+    /// Whether saving under a name would replace a saved request other than this tab's own
+    pub fn would_overwrite(&self, name: &str) -> bool {
+        !name.ends_with(".http") && self.saved_name.as_deref() != Some(name) && request_exists(name)
+    }
+
+    /// A name from the method and the end of the endpoint's path, e.g. get-users
+    fn suggested_request_name(&self) -> String {
+        let method = self.method.to_string().to_lowercase();
+        let endpoint = self.endpoint.split(['?', '#']).next().unwrap_or_default();
+        let path = match endpoint.split_once("://") {
+            Some((_, rest)) => rest,
+            None => endpoint,
+        };
+        let last_segment = path
+            .trim_end_matches('/')
+            .rsplit('/')
+            .next()
+            .unwrap_or_default();
+        let words: String = last_segment
+            .chars()
+            .map(|c| match c.is_alphanumeric() {
+                true => c.to_ascii_lowercase(),
+                false => '-',
+            })
+            .collect();
+        match words.trim_matches('-') {
+            "" => method,
+            words => format!("{}-{}", method, words),
+        }
+    }
+
+    /// Save the active tab's request under a name, or in a .http file when the name is the
+    /// path of one, returning whether it worked
+    pub fn save_request_as(&mut self, name: &str) -> bool {
+        if name.ends_with(".http") {
+            let path = std::path::absolute(name).unwrap_or(PathBuf::from(name));
+            // Saving to the file the request came from replaces it there
+            let index = match self.http_file {
+                Some(ref http_file) if http_file.path == path => Some(http_file.index),
+                _ => None,
+            };
+            return self.save_to_http_file(path, index);
+        }
+        if let Err(err) = validate_request_name(name) {
+            self.notifications
+                .error("Unable to save the request", Some(err));
+            return false;
+        }
+        let request = self.tab_request(self.active_tab);
+        match save_request(name, &request) {
+            Ok(()) => {
+                self.notifications.success(format!("Saved as {}", name));
+                self.saved_name = Some(name.to_string());
+                self.saved_request = Some(request);
+                self.http_file = None;
+                true
+            }
+            Err(err) => {
+                self.notifications
+                    .error("Unable to save the request", Some(err.to_string()));
+                false
+            }
+        }
+    }
+
+    fn save_to_http_file(&mut self, path: PathBuf, index: Option<usize>) -> bool {
+        // A new file keeps the variables of the file the request came from
+        let variables = match self.http_file {
+            Some(ref http_file) => http_file.variables.clone(),
+            None => Vec::new(),
+        };
+        match save_to_http_file(&path, index, self.request_file(), &variables) {
+            Ok(index) => {
+                self.notifications
+                    .success(format!("Saved in {}", path.display()));
+                let variables = load_http_file(&path)
+                    .map(|http_file| http_file.variables)
+                    .unwrap_or(variables);
+                self.saved_name = None;
+                self.saved_request = Some(self.tab_request(self.active_tab));
+                self.http_file = Some(HttpFileSource {
+                    path,
+                    index,
+                    variables,
+                });
+                true
+            }
+            Err(err) => {
+                self.notifications
+                    .error("Unable to save the request", Some(err));
+                false
+            }
+        }
+    }
+
+    /// Save every tab with unsaved changes, stopping to ask for a name for any which has never
+    /// been saved. Returns whether everything was saved.
+    pub fn save_dirty_tabs(&mut self) -> bool {
+        let active_tab = self.active_tab;
+        for index in self.dirty_tabs() {
+            self.select_tab(index);
+            let is_saved = match (self.saved_name.clone(), self.http_file.clone()) {
+                (Some(name), _) => self.save_request_as(&name),
+                (None, Some(http_file)) => {
+                    self.save_to_http_file(http_file.path, Some(http_file.index))
+                }
+                (None, None) => {
+                    self.open_save_input(true);
+                    return false;
+                }
+            };
+            if !is_saved {
+                self.current_screen = CurrentScreen::Main;
+                return false;
+            }
+        }
+        self.select_tab(active_tab);
+        true
+    }
+
+    /// Open a HAR file's entries, to choose which to replay
+    pub fn open_har(&mut self, entries: Vec<HarEntry>) {
+        self.har_entries = entries;
+        self.current_screen = CurrentScreen::HarEntries(0);
+    }
+
+    /// Connect to the endpoint as a WebSocket, with the request's headers
+    pub fn open_websocket(&mut self) {
+        if !matches!(self.current_screen, CurrentScreen::Main) {
+            return;
+        }
+        let variables = self.variables();
+        let mut unknown_variables = Vec::<String>::new();
+        let endpoint = substitute(&self.endpoint, &variables, &mut unknown_variables);
+        if !endpoint.starts_with("ws://") && !endpoint.starts_with("wss://") {
+            self.notifications
+                .warning("WebSocket endpoints start with ws:// or wss://");
+            return;
+        }
+        let mut headers: Vec<(String, String)> = substitute_values(
+            &self.section_values.request_headers,
+            &variables,
+            &mut unknown_variables,
+        )
+        .into_iter()
+        .map(|(key, value)| match value {
+            JsonValue::String(value) => (key, value),
+            _ => (key, value.to_string()),
+        })
+        .collect();
+        headers.sort();
+        if !unknown_variables.is_empty() {
+            self.notifications.warning(format!(
+                "Connecting without values for {}",
+                unknown_variables
+                    .iter()
+                    .map(|name| format!("{{{{{}}}}}", name))
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ));
+        }
+        let timeout = Duration::from_secs(self.config.request.timeout_seconds);
+        let mut websocket_state = WebSocketState::new(endpoint, headers, timeout);
+        websocket_state.connect();
+        self.current_screen = CurrentScreen::WebSocket(websocket_state);
+    }
+
+    /// Log the frames which have arrived since the screen was last drawn
+    pub fn receive_websocket_frames(&mut self) {
+        if let CurrentScreen::WebSocket(ref mut websocket_state) = self.current_screen {
+            websocket_state.receive();
+        }
+    }
+
+    /// Send the message being composed, or close the connection with the code and reason
+    pub fn submit_websocket_message(&mut self) {
+        if let CurrentScreen::WebSocket(ref mut websocket_state) = self.current_screen {
+            let text = websocket_state.composer.text().to_string();
+            let message = match websocket_state.is_closing {
+                true => build_close_message(&text),
+                false => build_message(websocket_state.kind, &text),
+            };
+            match message {
+                Ok(message) => {
+                    if websocket_state.send(message) {
+                        websocket_state.composer.clear();
+                        websocket_state.is_closing = false;
+                        websocket_state.scroll = 0;
+                    }
+                }
+                Err(err) => websocket_state.message = Some(err),
+            }
+        }
+    }
+
+    pub fn show_har_entries(&mut self) {
+        if let CurrentScreen::Main = self.current_screen {
+            match self.har_entries.is_empty() {
+                true => self
+                    .notifications
+                    .info("Open a HAR file with tapir -f FILE.har to replay its entries"),
+                false => self.current_screen = CurrentScreen::HarEntries(0),
+            }
+        }
+    }
+
+    /// Open an entry of the HAR file in a new tab, returning whether it worked
+    pub fn open_har_entry(&mut self, index: usize) -> bool {
+        let Some(entry) = self.har_entries.get(index) else {
+            return false;
+        };
+        match entry.request_file() {
+            Ok(request) => {
+                self.open_request_file(request);
+                true
+            }
+            Err(err) => {
+                self.notifications
+                    .error("Unable to replay the entry", Some(err));
+                false
+            }
+        }
+    }
+
+    pub fn open_har_export_input(&mut self) {
+        if let CurrentScreen::Main = self.current_screen {
+            match self.history.is_empty() {
+                true => self
+                    .notifications
+                    .info("There's no history to export until a request has been sent"),
+                false => {
+                    self.current_screen =
+                        CurrentScreen::HarExportInput(LineEditor::new("tapir.har"))
+                }
+            }
+        }
+    }
+
+    /// Export every response received this session as a HAR file, returning whether it worked
+    pub fn export_har(&mut self, path: &str) -> bool {
+        let entries = self
+            .history
+            .iter()
+            .map(|entry| har_entry(&entry.response))
+            .collect();
+        match save_har(std::path::Path::new(path), entries) {
+            Ok(()) => {
+                self.notifications.success(format!(
+                    "Exported {} requests to {}",
+                    self.history.len(),
+                    path
+                ));
+                true
+            }
+            Err(err) => {
+                self.notifications
+                    .error("Unable to export the history", Some(err));
+                false
+            }
+        }
+    }
+
+    pub fn open_alert_log(&mut self) {
+        if let CurrentScreen::Main = self.current_screen {
+            self.current_screen = CurrentScreen::AlertLog(0);
+        }
+    }
+
+    pub fn open_diff(&mut self) {
+        if let CurrentScreen::Main = self.current_screen {
+            // Compare against the pinned response, or otherwise the previous response to the
+            // same request, since the history is shared by every tab
+            let baseline_index = match (self.pinned_baseline, &self.last_response) {
+                (Some(pinned_index), _) => pinned_index,
+                (None, None) => return,
+                (None, Some(response)) => {
+                    let request = &response.request;
+                    let current_index = self
+                        .history
+                        .iter()
+                        .rposition(|entry| {
+                            entry.response.timings.started == response.timings.started
+                        })
+                        .unwrap_or(self.history.len());
+                    let baseline_index = self.history[..current_index].iter().rposition(|entry| {
+                        entry.response.request.method == request.method
+                            && entry.response.request.url == request.url
+                    });
+                    match baseline_index {
+                        Some(baseline_index) => baseline_index,
+                        None => {
+                            self.notifications.info(format!(
+                                "There's no earlier response from {} {} to compare with",
+                                request.method, request.url
+                            ));
+                            return;
+                        }
+                    }
+                }
+            };
+            self.current_screen = CurrentScreen::Diff(DiffState {
+                baseline_index,
+                scroll: 0,
+            });
+        }
+    }
+
+    /// The changes to the body and headers of the response since a history entry's response
+    pub fn response_changes(
+        &self,
+        baseline_index: usize,
+    ) -> Option<(Vec<JsonChange>, Vec<LineChange>)> {
+        let baseline = self.history.get(baseline_index)?;
+        let body_changes = diff_json(
+            &baseline.section_values.response_json(),
+            &self.section_values.response_json(),
+        );
+        let header_lines = |headers: &HashMap<String, JsonValue>| {
+            let mut header_lines: Vec<String> = headers
+                .iter()
+                .map(|(key, value)| format!("{}: {}", key, value.as_str().unwrap_or_default()))
+                .collect();
+            header_lines.sort();
+            header_lines
+        };
+        let header_changes = diff_lines(
+            &header_lines(&baseline.section_values.response_headers),
+            &header_lines(&self.section_values.response_headers),
+        );
+        Some((body_changes, header_changes))
+    }
+
+    pub fn scroll_diff(&mut self, amount: isize) {
+        let baseline_index = match self.current_screen {
+            CurrentScreen::Diff(ref diff_state) => diff_state.baseline_index,
+            _ => return,
+        };
+        // Both headings, the blank line between them and "No changes" if the bodies match
+        let line_count = match self.response_changes(baseline_index) {
+            Some((body_changes, header_changes)) => {
+                body_changes.len().max(1) + header_changes.len() + 3
+            }
+            None => 0,
+        };
+        if let CurrentScreen::Diff(ref mut diff_state) = self.current_screen {
+            diff_state.scroll = diff_state
+                .scroll
+                .saturating_add_signed(amount)
+                .min(line_count.saturating_sub(1));
+        }
+    }
+
+    pub fn increment_diff_baseline(&mut self, reverse: bool) {
+        if let CurrentScreen::Diff(ref mut diff_state) = self.current_screen {
+            if let Some(new_index) =
+                evaluate_new_index(Some(diff_state.baseline_index), self.history.len(), reverse)
+            {
+                diff_state.baseline_index = new_index;
+                diff_state.scroll = 0;
+            }
+        }
+    }
+
+    /// Move the active tab out of the app, leaving an empty request in its place
+    fn take_active_tab(&mut self) -> Tab {
+        let empty_tab = Tab::new();
+        self.response_version += 1;
+        Tab {
+            endpoint: std::mem::replace(&mut self.endpoint, empty_tab.endpoint),
+            method: std::mem::replace(&mut self.method, empty_tab.method),
+            section_values: std::mem::replace(&mut self.section_values, empty_tab.section_values),
+            selected_section: std::mem::replace(
+                &mut self.selected_section,
+                empty_tab.selected_section,
+            ),
+            unfocused_section: std::mem::replace(
+                &mut self.unfocused_section,
+                empty_tab.unfocused_section,
+            ),
+            response_filter: std::mem::replace(
+                &mut self.response_filter,
+                empty_tab.response_filter,
+            ),
+            response_tree: std::mem::replace(&mut self.response_tree, empty_tab.response_tree),
+            last_response: std::mem::replace(&mut self.last_response, empty_tab.last_response),
+            saved_name: std::mem::replace(&mut self.saved_name, empty_tab.saved_name),
+            saved_request: std::mem::replace(&mut self.saved_request, empty_tab.saved_request),
+            http_file: std::mem::replace(&mut self.http_file, empty_tab.http_file),
+        }
+    }
+
+    fn load_tab(&mut self, tab: Tab) {
+        self.response_version += 1;
+        self.endpoint = tab.endpoint;
+        self.method = tab.method;
+        self.section_values = tab.section_values;
+        self.selected_section = tab.selected_section;
+        self.unfocused_section = tab.unfocused_section;
+        self.response_filter = tab.response_filter;
+        self.response_tree = tab.response_tree;
+        self.last_response = tab.last_response;
+        self.saved_name = tab.saved_name;
+        self.saved_request = tab.saved_request;
+        self.http_file = tab.http_file;
+        self.horizontal_scroll = 0;
+    }
+
+    pub fn switch_tab(&mut self, reverse: bool) {
+        if let CurrentScreen::Main = self.current_screen {
+            let new_index = match reverse {
+                false => (self.active_tab + 1) % self.tabs.len(),
+                true => (self.active_tab + self.tabs.len() - 1) % self.tabs.len(),
+            };
+            self.select_tab(new_index);
+        }
+    }
+
+    pub fn select_tab(&mut self, new_index: usize) {
+        if new_index == self.active_tab || new_index >= self.tabs.len() {
+            return;
+        }
+        self.tabs[self.active_tab] = self.take_active_tab();
+        let new_tab = std::mem::replace(&mut self.tabs[new_index], Tab::new());
+        self.load_tab(new_tab);
+        self.active_tab = new_index;
+    }
+
+    pub fn new_tab(&mut self) {
+        if let CurrentScreen::Main = self.current_screen {
+            self.tabs.insert(self.active_tab + 1, Tab::new());
+            self.select_tab(self.active_tab + 1);
+        }
+    }
+
+    pub fn close_tab(&mut self) {
+        if let CurrentScreen::Main = self.current_screen {
+            if self.tabs.len() == 1 {
+                self.load_tab(Tab::new());
+                return;
+            }
+            self.tabs.remove(self.active_tab);
+            let new_index = self.active_tab.min(self.tabs.len() - 1);
+            let new_tab = std::mem::replace(&mut self.tabs[new_index], Tab::new());
+            self.load_tab(new_tab);
+            self.active_tab = new_index;
+        }
+    }
+
+    /// The method and endpoint of every tab, reading the active tab from the app itself
+    pub fn tab_requests(&self) -> Vec<(&HttpMethod, &str)> {
+        self.tabs
+            .iter()
+            .enumerate()
+            .map(|(index, tab)| match index == self.active_tab {
+                true => (&self.method, self.endpoint.as_str()),
+                false => (&tab.method, tab.endpoint.as_str()),
+            })
+            .collect()
+    }
+
+    /// The request of a tab, in the form it's saved in
+    pub fn tab_request(&self, index: usize) -> SavedRequest {
+        let tab = &self.tabs[index];
+        let (method, endpoint, section_values) = match index == self.active_tab {
+            true => (&self.method, &self.endpoint, &self.section_values),
+            false => (&tab.method, &tab.endpoint, &tab.section_values),
+        };
+        SavedRequest {
+            method: method.clone(),
+            endpoint: endpoint.clone(),
+            request_headers: section_values.request_headers.clone(),
+            request_body: section_values.request_body.clone(),
+            raw_request_body: section_values.raw_request_body.clone(),
+            graphql: section_values.graphql.clone(),
+        }
+    }
+
+    /// Whether a tab has changed since it was last saved, ignoring new tabs which are still empty
+    pub fn is_tab_dirty(&self, index: usize) -> bool {
+        let saved_request = match index == self.active_tab {
+            true => &self.saved_request,
+            false => &self.tabs[index].saved_request,
+        };
+        let request = self.tab_request(index);
+        match saved_request {
+            Some(saved_request) => *saved_request != request,
+            None => !request.is_empty(),
+        }
+    }
+
+    pub fn dirty_tabs(&self) -> Vec<usize> {
+        (0..self.tabs.len())
+            .filter(|index| self.is_tab_dirty(*index))
+            .collect()
+    }
+
+    pub fn saved_tabs(&self) -> SavedTabs {
+        let tabs = self
+            .tabs
+            .iter()
+            .enumerate()
+            .map(|(index, tab)| SavedTab {
+                request: self.tab_request(index),
+                saved_name: match index == self.active_tab {
+                    true => self.saved_name.clone(),
+                    false => tab.saved_name.clone(),
+                },
+                http_file: match index == self.active_tab {
+                    true => self.http_file.clone(),
+                    false => tab.http_file.clone(),
+                },
+            })
+            .collect();
+        SavedTabs {
+            tabs,
+            active_tab: self.active_tab,
+        }
+    }
+
+    pub fn restore_tabs(&mut self, saved_tabs: SavedTabs) {
+        if saved_tabs.tabs.is_empty() {
+            return;
+        }
+        self.tabs = saved_tabs
+            .tabs
+            .into_iter()
+            .map(|saved_tab| {
+                let mut tab = Tab::new();
+                let request = saved_tab.request;
+                tab.method = request.method;
+                tab.endpoint = request.endpoint;
+                tab.section_values.request_headers = request.request_headers;
+                tab.section_values.request_body = request.request_body;
+                tab.section_values.raw_request_body = request.raw_request_body;
+                tab.section_values.graphql = request.graphql;
+                // Compare against the saved request as it is now, in case it was changed elsewhere
+                tab.saved_request = match (&saved_tab.saved_name, &saved_tab.http_file) {
+                    (Some(name), _) => load_request(name).ok(),
+                    (None, Some(http_file)) => load_http_file(&http_file.path)
+                        .ok()
+                        .and_then(|file| file.requests.into_iter().nth(http_file.index))
+                        .map(|request| saved_request_from_file(request.request)),
+                    (None, None) => None,
+                };
+                tab.saved_name = saved_tab.saved_name;
+                tab.http_file = saved_tab.http_file;
+                tab
+            })
+            .collect();
+        self.active_tab = saved_tabs.active_tab.min(self.tabs.len() - 1);
+        let active_tab = std::mem::replace(&mut self.tabs[self.active_tab], Tab::new());
+        self.load_tab(active_tab);
+    }
+}
+
+/// Parse text as JSON, falling back to a JSON string of the text itself
+fn json_or_string(text: &str) -> JsonValue {
+    serde_json::from_str(text).unwrap_or(JsonValue::String(text.to_string()))
+}
+
+/// A request as text, with its body as pairs when it's a flat JSON object of strings
+fn saved_request_from_file(request: RequestFile) -> SavedRequest {
+    let request_headers = request
+        .headers
+        .into_iter()
+        .map(|(key, value)| (key, JsonValue::String(value)))
+        .collect();
+    let pairs = match serde_json::from_str::<JsonValue>(&request.body) {
+        Ok(JsonValue::Object(object)) if object.values().all(JsonValue::is_string) => {
+            Some(object.into_iter().collect())
+        }
+        _ if request.body.trim().is_empty() => Some(HashMap::new()),
+        _ => None,
+    };
+    let (request_body, raw_request_body) = match pairs {
+        Some(pairs) => (pairs, None),
+        None => (HashMap::new(), Some(request.body)),
+    };
+    SavedRequest {
+        method: request.method,
+        endpoint: request.endpoint,
+        request_headers,
+        request_body,
+        raw_request_body,
+        graphql: None,
+    }
 }
@@ -0,0 +1,282 @@
+use crate::har::{load_har, HarEntry};
+use crate::http_file::{load_http_file, HttpFile};
+use crate::http_request::HttpMethod;
+use crate::request_file::RequestFile;
+use crate::storage::{load_request_from, validate_request_name, SavedRequest};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+pub const USAGE: &str = "Usage: tapir [METHOD] URL [-H 'Name: value']... [-d BODY] [OPTIONS]
+       tapir SAVED_REQUEST [OPTIONS]
+       tapir -f FILE [OPTIONS]
+       tapir FILE.http [OPTIONS]
+       tapir FILE.har [OPTIONS]
+
+Options:
+  -H, --header 'Name: value'  Add a request header, may be repeated
+  -d, --data BODY             Set the request body (the method defaults to POST)
+  -f, --file FILE             Open a request saved as JSON, or - to read it from stdin.
+                              A .http file opens each of its requests in a tab, and a
+                              .har file lists its entries to choose which to replay.
+      --send                  Send the requests and print the results as JSON, without the TUI
+      --env NAME              Substitute {{variables}} from an environment in the config
+      --collection PATH       Use a directory of saved requests other than the default
+      --help                  Show this help";
+
+/// What to open the TUI with, from the command line
+#[derive(Default)]
+pub struct Args {
+    pub method: Option<HttpMethod>,
+    pub target: Option<String>, // A URL, or the name of a saved request
+    pub headers: Vec<(String, String)>,
+    pub body: Option<String>,
+    pub environment: Option<String>,
+    pub collection: Option<PathBuf>,
+    pub file: Option<PathBuf>, // "-" for stdin
+    pub send: bool,
+    pub show_help: bool,
+}
+
+pub fn parse_args(args: impl IntoIterator<Item = String>) -> Result<Args, String> {
+    let mut parsed_args = Args::default();
+    let mut positional = Vec::<String>::new();
+    let mut args = args.into_iter();
+
+    while let Some(arg) = args.next() {
+        let mut value_for = |option: &str| args.next().ok_or(format!("{} needs a value", option));
+        match arg.as_str() {
+            "-H" | "--header" => {
+                let header = value_for(&arg)?;
+                match header.split_once(':') {
+                    Some((key, value)) if !key.trim().is_empty() => parsed_args
+                        .headers
+                        .push((key.trim().to_string(), value.trim().to_string())),
+                    _ => {
+                        return Err(format!(
+                            "Invalid header \"{}\", expected \"Name: value\"",
+                            header
+                        ))
+                    }
+                }
+            }
+            "-d" | "--data" => parsed_args.body = Some(value_for(&arg)?),
+            "--env" => parsed_args.environment = Some(value_for(&arg)?),
+            "--collection" => parsed_args.collection = Some(PathBuf::from(value_for(&arg)?)),
+            "-f" | "--file" => parsed_args.file = Some(PathBuf::from(value_for(&arg)?)),
+            "--send" => parsed_args.send = true,
+            "--help" => parsed_args.show_help = true,
+            _ if arg.starts_with('-') && arg.len() > 1 => {
+                return Err(format!("Unknown option \"{}\"", arg))
+            }
+            "-" => parsed_args.file = Some(PathBuf::from("-")),
+            _ => positional.push(arg),
+        }
+    }
+    if parsed_args.file.is_some() && !positional.is_empty() {
+        return Err("Give either a file or a URL, not both".to_string());
+    }
+
+    match positional.len() {
+        0 => {}
+        1 => parsed_args.target = positional.pop(),
+        2 => {
+            parsed_args.target = positional.pop();
+            parsed_args.method = Some(positional[0].parse::<HttpMethod>()?);
+        }
+        _ => return Err(format!("Unexpected argument \"{}\"", positional[2])),
+    }
+    Ok(parsed_args)
+}
+
+pub enum InitialRequest {
+    Saved(String, SavedRequest),
+    Loaded(SavedRequest), // From a file, so not saved under a name
+    HttpFile(PathBuf, HttpFile),
+    Har(Vec<HarEntry>),
+    New(RequestFile),
+}
+
+/// Work out what to open from the arguments, loading the saved request if one is named from
+/// the directory of saved requests
+pub fn initial_request(
+    args: &Args,
+    requests_directory: Option<&Path>,
+) -> Result<Option<InitialRequest>, String> {
+    // .http and .har files can be given without -f
+    let path = match (&args.file, &args.target) {
+        (Some(path), _) => Some(path.clone()),
+        (None, Some(target)) if args.method.is_none() && Path::new(target).is_file() => {
+            Some(PathBuf::from(target))
+        }
+        _ => None,
+    };
+    let extension = path
+        .as_ref()
+        .and_then(|path| path.extension())
+        .and_then(|extension| extension.to_str());
+    if let (Some(path), Some(extension @ ("http" | "har"))) = (&path, extension) {
+        if !args.headers.is_empty() || args.body.is_some() {
+            return Err(format!(
+                "-H and -d can't be used with a .{} file",
+                extension
+            ));
+        }
+        if extension == "har" {
+            return Ok(Some(InitialRequest::Har(load_har(path)?)));
+        }
+        let http_file = load_http_file(path)?;
+        if http_file.requests.is_empty() {
+            return Err(match http_file.skipped.is_empty() {
+                true => format!("There are no requests in {}", path.display()),
+                false => format!(
+                    "There are no requests which can be opened in {}\n{}",
+                    path.display(),
+                    http_file.skipped.join("\n")
+                ),
+            });
+        }
+        let path = std::path::absolute(path).unwrap_or(path.clone());
+        return Ok(Some(InitialRequest::HttpFile(path, http_file)));
+    }
+
+    if let Some(ref path) = args.file {
+        let (contents, source) = match path.to_str() {
+            Some("-") => (io::read_to_string(io::stdin()), "stdin".to_string()),
+            _ => (fs::read_to_string(path), path.display().to_string()),
+        };
+        let contents = contents.map_err(|err| format!("Unable to read {}: {}", source, err))?;
+        let request = serde_json::from_str(&contents)
+            .map_err(|err| format!("Invalid request in {}: {}", source, err))?;
+        return Ok(Some(InitialRequest::Loaded(request)));
+    }
+
+    let Some(ref target) = args.target else {
+        if !args.headers.is_empty() || args.body.is_some() {
+            return Err("-H and -d need a URL or a saved request".to_string());
+        }
+        return Ok(None);
+    };
+
+    if args.method.is_none() && validate_request_name(target).is_ok() {
+        let request = match requests_directory {
+            Some(directory) => load_request_from(directory, target),
+            None => Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                "Unable to locate a data directory",
+            )),
+        };
+        match request {
+            Ok(request) => return Ok(Some(InitialRequest::Saved(target.clone(), request))),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+            Err(err) => {
+                return Err(format!(
+                    "Unable to open the saved request \"{}\": {}",
+                    target, err
+                ))
+            }
+        }
+        // Anything which can't be a URL must have been meant as a saved request
+        if !target.contains([':', '/', '.']) {
+            return Err(format!("There's no saved request named \"{}\"", target));
+        }
+    }
+
+    let method = match (&args.method, &args.body) {
+        (Some(method), _) => method.clone(),
+        (None, Some(_)) => HttpMethod::POST,
+        (None, None) => HttpMethod::GET,
+    };
+    Ok(Some(InitialRequest::New(RequestFile {
+        method,
+        endpoint: target.clone(),
+        headers: args.headers.clone(),
+        body: args.body.clone().unwrap_or_default(),
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Args, String> {
+        parse_args(args.iter().map(|arg| arg.to_string()))
+    }
+
+    fn new_request(args: &[&str]) -> RequestFile {
+        match initial_request(&parse(args).unwrap(), None) {
+            Ok(Some(InitialRequest::New(request))) => request,
+            _ => panic!("{:?} should open a new request", args),
+        }
+    }
+
+    #[test]
+    fn method_and_url() {
+        let args = parse(&["put", "https://example.com/users/1"]).unwrap();
+        assert_eq!(args.method, Some(HttpMethod::PUT));
+        assert_eq!(args.target.as_deref(), Some("https://example.com/users/1"));
+
+        assert!(parse(&["FETCH", "https://example.com"]).is_err());
+        assert!(parse(&["GET", "https://example.com", "extra"]).is_err());
+    }
+
+    #[test]
+    fn headers_and_body() {
+        let request = new_request(&[
+            "https://example.com",
+            "-H",
+            "Accept: application/json",
+            "--header",
+            "X-Time:12:00",
+            "-d",
+            "{\"a\": 1}",
+        ]);
+        assert_eq!(request.method, HttpMethod::POST);
+        assert_eq!(
+            request.headers,
+            vec![
+                ("Accept".to_string(), "application/json".to_string()),
+                ("X-Time".to_string(), "12:00".to_string()),
+            ]
+        );
+        assert_eq!(request.body, "{\"a\": 1}");
+
+        assert!(parse(&["https://example.com", "-H", "no colon"]).is_err());
+        assert!(parse(&["https://example.com", "-d"]).is_err());
+    }
+
+    #[test]
+    fn saved_name_or_url() {
+        let directory = tempfile::tempdir().unwrap();
+        let initial_request =
+            |args: &[&str]| initial_request(&parse(args).unwrap(), Some(directory.path()));
+        fs::write(
+            directory.path().join("users.json"),
+            r#"{"method": "DELETE", "endpoint": "https://example.com/users"}"#,
+        )
+        .unwrap();
+
+        match initial_request(&["users"]) {
+            Ok(Some(InitialRequest::Saved(name, request))) => {
+                assert_eq!(name, "users");
+                assert_eq!(request.method, HttpMethod::DELETE);
+            }
+            _ => panic!("users should open the saved request"),
+        }
+        // With a method it can only be a URL
+        assert_eq!(new_request(&["GET", "users"]).endpoint, "users");
+        assert_eq!(new_request(&["example.com"]).endpoint, "example.com");
+        assert!(matches!(
+            initial_request(&["missing"]),
+            Err(err) if err == "There's no saved request named \"missing\""
+        ));
+    }
+
+    #[test]
+    fn dash_reads_stdin() {
+        let args = parse(&["-"]).unwrap();
+        assert_eq!(args.file, Some(PathBuf::from("-")));
+        assert!(args.target.is_none());
+        assert!(parse(&["-", "https://example.com"]).is_err());
+    }
+}
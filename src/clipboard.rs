@@ -0,0 +1,87 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::io::{self, Write};
+use std::process::{Command, Stdio};
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ClipboardMode {
+    #[default]
+    Auto, // OSC 52, and the native clipboard too unless connected over SSH
+    Osc52,
+    Native,
+}
+
+/// Copy text to the clipboard using the OSC 52 terminal escape sequence and/or a native tool
+pub fn copy_to_clipboard(text: &str, mode: ClipboardMode) -> io::Result<()> {
+    match mode {
+        ClipboardMode::Osc52 => copy_with_osc52(text),
+        ClipboardMode::Native => copy_natively(text),
+        ClipboardMode::Auto => {
+            // Over SSH the native clipboard belongs to the remote machine, so isn't useful
+            let is_remote =
+                env::var_os("SSH_CONNECTION").is_some() || env::var_os("SSH_TTY").is_some();
+            if !is_remote {
+                let _ = copy_natively(text);
+            }
+            copy_with_osc52(text)
+        }
+    }
+}
+
+fn copy_with_osc52(text: &str) -> io::Result<()> {
+    let sequence = format!("\x1b]52;c;{}\x07", STANDARD.encode(text));
+    let mut stderr = io::stderr();
+    match env::var_os("TMUX") {
+        // tmux only passes escape sequences through to the outer terminal when wrapped
+        Some(_) => write!(
+            stderr,
+            "\x1bPtmux;{}\x1b\\",
+            sequence.replace('\x1b', "\x1b\x1b")
+        )?,
+        None => write!(stderr, "{}", sequence)?,
+    }
+    stderr.flush()
+}
+
+/// Copy using the first clipboard tool which is available for the platform
+fn copy_natively(text: &str) -> io::Result<()> {
+    let mut candidates: Vec<(&str, &[&str])> = Vec::new();
+    if cfg!(target_os = "macos") {
+        candidates.push(("pbcopy", &[]));
+    }
+    if cfg!(windows) || env::var_os("WSL_DISTRO_NAME").is_some() {
+        candidates.push(("clip.exe", &[]));
+    }
+    if env::var_os("WAYLAND_DISPLAY").is_some() {
+        candidates.push(("wl-copy", &[]));
+    }
+    if env::var_os("DISPLAY").is_some() {
+        candidates.push(("xclip", &["-selection", "clipboard"]));
+        candidates.push(("xsel", &["--clipboard", "--input"]));
+    }
+
+    for (program, arguments) in candidates {
+        let child = Command::new(program)
+            .args(arguments)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn();
+        let mut child = match child {
+            Ok(child) => child,
+            Err(_) => continue,
+        };
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(text.as_bytes())?;
+        }
+        if child.wait()?.success() {
+            return Ok(());
+        }
+    }
+    Err(io::Error::new(
+        io::ErrorKind::NotFound,
+        "No native clipboard tool is available",
+    ))
+}
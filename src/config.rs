@@ -0,0 +1,143 @@
+use crate::clipboard::ClipboardMode;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub enum LayoutMode {
+    Single,
+    Split,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct LayoutConfig {
+    pub mode: LayoutMode,
+    pub split_percent: u16, // Width (or height when stacked) of the request pane
+    pub stack_below_width: u16, // Terminal width below which the panes are stacked
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct RequestConfig {
+    pub timeout_seconds: u64, // How long to wait for a response before giving up, at least 1
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct HistoryConfig {
+    pub max_entries: usize, // Responses kept for diffing and exporting
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(untagged)]
+pub enum KeyChords {
+    One(String),
+    Many(Vec<String>),
+}
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct KeybindingsConfig {
+    pub preset: Option<String>, // "vim" (the default) or "arrows"
+    // Keyed by screen and then by action, e.g. {"main": {"send_request": "ctrl+s"}}
+    pub bindings: HashMap<String, HashMap<String, KeyChords>>,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct ThemeConfig {
+    pub name: Option<String>, // "dark" (the default), "light" or "high-contrast"
+    // Overrides for individual styles, e.g. {"selected": "bold yellow", "input": "black on #ffd700"}
+    pub styles: HashMap<String, String>,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct Config {
+    pub layout: LayoutConfig,
+    pub request: RequestConfig,
+    pub history: HistoryConfig,
+    pub keybindings: KeybindingsConfig,
+    pub theme: ThemeConfig,
+    pub clipboard: ClipboardMode,
+    // Named sets of variables, e.g. {"dev": {"base_url": "http://localhost:8080"}}, which are
+    // substituted for {{base_url}} when sending
+    pub environments: HashMap<String, HashMap<String, String>>,
+}
+
+impl Default for LayoutConfig {
+    fn default() -> LayoutConfig {
+        LayoutConfig {
+            mode: LayoutMode::Single,
+            split_percent: 50,
+            stack_below_width: 100,
+        }
+    }
+}
+
+impl Default for RequestConfig {
+    fn default() -> RequestConfig {
+        RequestConfig {
+            timeout_seconds: 30,
+        }
+    }
+}
+
+impl Default for HistoryConfig {
+    fn default() -> HistoryConfig {
+        HistoryConfig { max_entries: 50 }
+    }
+}
+
+pub fn config_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|directory| directory.join("tapir").join("config.json"))
+}
+
+/// Load the config file, falling back to the defaults if there isn't one
+pub fn load_config() -> Result<Config, String> {
+    let path = match config_path() {
+        Some(path) => path,
+        None => return Ok(Config::default()),
+    };
+    match fs::read_to_string(&path) {
+        Ok(contents) => {
+            let mut config: Config = serde_json::from_str(&contents)
+                .map_err(|err| format!("Invalid config file {}: {}", path.display(), err))?;
+            config.layout.split_percent = config.layout.split_percent.clamp(20, 80);
+            // A timeout of 0 would fail every request straight away
+            config.request.timeout_seconds = config.request.timeout_seconds.max(1);
+            Ok(config)
+        }
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Config::default()),
+        Err(err) => Err(format!("Unable to read {}: {}", path.display(), err)),
+    }
+}
+
+/// Save the layout into the config file, leaving the rest of the file as it is
+pub fn save_layout(layout: &LayoutConfig) -> io::Result<()> {
+    let path = config_path().ok_or(io::Error::new(
+        io::ErrorKind::NotFound,
+        "Unable to locate a config directory",
+    ))?;
+    let mut config = match fs::read_to_string(&path) {
+        Ok(contents) => serde_json::from_str::<serde_json::Value>(&contents)?,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => serde_json::json!({}),
+        Err(err) => return Err(err),
+    };
+    match config.as_object_mut() {
+        Some(config) => config.insert("layout".to_string(), serde_json::to_value(layout)?),
+        None => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{} doesn't hold a JSON object", path.display()),
+            ))
+        }
+    };
+    if let Some(directory) = path.parent() {
+        fs::create_dir_all(directory)?;
+    }
+    fs::write(path, serde_json::to_string_pretty(&config)?)
+}
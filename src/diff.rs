@@ -0,0 +1,201 @@
+use crate::json_tree::key_path;
+use serde_json::value::Value as JsonValue;
+
+pub enum JsonChange {
+    Added(String, JsonValue),
+    Removed(String, JsonValue),
+    Changed(String, JsonValue, JsonValue),
+}
+
+pub enum LineChange {
+    Unchanged(String),
+    Added(String),
+    Removed(String),
+}
+
+/// List the paths which were added, removed or changed between two JSON values
+pub fn diff_json(old: &JsonValue, new: &JsonValue) -> Vec<JsonChange> {
+    let mut changes = Vec::<JsonChange>::new();
+    push_json_changes(old, new, "$".to_string(), &mut changes);
+    changes
+}
+
+fn push_json_changes(
+    old: &JsonValue,
+    new: &JsonValue,
+    path: String,
+    changes: &mut Vec<JsonChange>,
+) {
+    match (old, new) {
+        (JsonValue::Object(old_map), JsonValue::Object(new_map)) => {
+            for (key, old_value) in old_map.iter() {
+                let child_path = key_path(&path, key);
+                match new_map.get(key) {
+                    Some(new_value) => push_json_changes(old_value, new_value, child_path, changes),
+                    None => changes.push(JsonChange::Removed(child_path, old_value.clone())),
+                }
+            }
+            for (key, new_value) in new_map.iter() {
+                if !old_map.contains_key(key) {
+                    changes.push(JsonChange::Added(key_path(&path, key), new_value.clone()));
+                }
+            }
+        }
+        (JsonValue::Array(old_items), JsonValue::Array(new_items)) => {
+            for index in 0..old_items.len().max(new_items.len()) {
+                let child_path = format!("{}[{}]", path, index);
+                match (old_items.get(index), new_items.get(index)) {
+                    (Some(old_value), Some(new_value)) => {
+                        push_json_changes(old_value, new_value, child_path, changes)
+                    }
+                    (Some(old_value), None) => {
+                        changes.push(JsonChange::Removed(child_path, old_value.clone()))
+                    }
+                    (None, Some(new_value)) => {
+                        changes.push(JsonChange::Added(child_path, new_value.clone()))
+                    }
+                    (None, None) => {}
+                }
+            }
+        }
+        _ => {
+            if old != new {
+                changes.push(JsonChange::Changed(path, old.clone(), new.clone()));
+            }
+        }
+    }
+}
+
+/// A line by line diff based on the longest common subsequence of the two texts
+pub fn diff_lines(old: &[String], new: &[String]) -> Vec<LineChange> {
+    // common_lengths[i][j] is the length of the LCS of old[i..] and new[j..]
+    let mut common_lengths = vec![vec![0; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            common_lengths[i][j] = match old[i] == new[j] {
+                true => common_lengths[i + 1][j + 1] + 1,
+                false => common_lengths[i + 1][j].max(common_lengths[i][j + 1]),
+            };
+        }
+    }
+
+    let mut changes = Vec::<LineChange>::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            changes.push(LineChange::Unchanged(old[i].clone()));
+            i += 1;
+            j += 1;
+        } else if common_lengths[i + 1][j] >= common_lengths[i][j + 1] {
+            changes.push(LineChange::Removed(old[i].clone()));
+            i += 1;
+        } else {
+            changes.push(LineChange::Added(new[j].clone()));
+            j += 1;
+        }
+    }
+    changes.extend(old[i..].iter().cloned().map(LineChange::Removed));
+    changes.extend(new[j..].iter().cloned().map(LineChange::Added));
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn describe(changes: Vec<JsonChange>) -> Vec<String> {
+        changes
+            .into_iter()
+            .map(|change| match change {
+                JsonChange::Added(path, value) => format!("+ {} {}", path, value),
+                JsonChange::Removed(path, value) => format!("- {} {}", path, value),
+                JsonChange::Changed(path, old, new) => format!("~ {} {} -> {}", path, old, new),
+            })
+            .collect()
+    }
+
+    fn describe_lines(changes: Vec<LineChange>) -> Vec<String> {
+        changes
+            .into_iter()
+            .map(|change| match change {
+                LineChange::Unchanged(line) => format!("  {}", line),
+                LineChange::Added(line) => format!("+ {}", line),
+                LineChange::Removed(line) => format!("- {}", line),
+            })
+            .collect()
+    }
+
+    fn lines(text: &str) -> Vec<String> {
+        text.split_whitespace().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn object_changes() {
+        let old = json!({"kept": 1, "changed": "a", "removed": true, "nested": {"x": 1}});
+        let new = json!({"kept": 1, "changed": "b", "added": null, "nested": {"x": 2}});
+        assert_eq!(
+            describe(diff_json(&old, &new)),
+            vec![
+                r#"~ $["changed"] "a" -> "b""#,
+                r#"~ $["nested"]["x"] 1 -> 2"#,
+                r#"- $["removed"] true"#,
+                r#"+ $["added"] null"#,
+            ]
+        );
+        assert!(diff_json(&old, &old).is_empty());
+    }
+
+    #[test]
+    fn type_changes() {
+        assert_eq!(
+            describe(diff_json(&json!({"a": [1]}), &json!({"a": {"0": 1}}))),
+            vec![r#"~ $["a"] [1] -> {"0":1}"#]
+        );
+        assert_eq!(
+            describe(diff_json(&json!({"a": 1}), &json!({"a": "1"}))),
+            vec![r#"~ $["a"] 1 -> "1""#]
+        );
+        assert_eq!(
+            describe(diff_json(&json!([1]), &json!({}))),
+            vec![r#"~ $ [1] -> {}"#]
+        );
+    }
+
+    #[test]
+    fn array_changes() {
+        assert_eq!(
+            describe(diff_json(&json!([1, 2, 3]), &json!([1, 5]))),
+            vec!["~ $[1] 2 -> 5", "- $[2] 3"]
+        );
+        assert_eq!(
+            describe(diff_json(
+                &json!([{"id": 1}]),
+                &json!([{"id": 1}, {"id": 2}])
+            )),
+            vec![r#"+ $[1] {"id":2}"#]
+        );
+    }
+
+    #[test]
+    fn keys_with_dots_are_quoted() {
+        assert_eq!(
+            describe(diff_json(&json!({"a.b": 1}), &json!({"a": {"b": 1}}))),
+            vec![r#"- $["a.b"] 1"#, r#"+ $["a"] {"b":1}"#]
+        );
+    }
+
+    #[test]
+    fn line_changes() {
+        assert_eq!(
+            describe_lines(diff_lines(&lines("a b c d"), &lines("a c d e"))),
+            vec!["  a", "- b", "  c", "  d", "+ e"]
+        );
+        assert_eq!(
+            describe_lines(diff_lines(&lines("a b"), &lines("b a"))),
+            vec!["- a", "  b", "+ a"]
+        );
+        assert_eq!(describe_lines(diff_lines(&[], &lines("a"))), vec!["+ a"]);
+        assert_eq!(describe_lines(diff_lines(&lines("a"), &[])), vec!["- a"]);
+    }
+}
@@ -0,0 +1,313 @@
+use serde_json::value::Value as JsonValue;
+
+enum Segment {
+    Key(String),
+    Index(i64),
+    Wildcard,
+    Recursive(Option<String>),
+}
+
+/// Apply a JSONPath (`$.items[0].name`) or jq-style (`.items[].name`) expression to a value.
+/// Expressions which can match more than one node return their matches as an array.
+pub fn apply_filter(expression: &str, value: &JsonValue) -> Result<JsonValue, String> {
+    let segments = parse_expression(expression)?;
+    let is_multi_match = segments
+        .iter()
+        .any(|segment| matches!(segment, Segment::Wildcard | Segment::Recursive(_)));
+
+    let mut matches = vec![value];
+    for segment in segments.iter() {
+        matches = matches
+            .into_iter()
+            .flat_map(|node| select(node, segment))
+            .collect();
+    }
+
+    match is_multi_match {
+        true => Ok(JsonValue::Array(matches.into_iter().cloned().collect())),
+        false => match matches.first() {
+            Some(result) => Ok((*result).clone()),
+            None => Err(format!("No match for {}", expression)),
+        },
+    }
+}
+
+fn select<'a>(node: &'a JsonValue, segment: &Segment) -> Vec<&'a JsonValue> {
+    match segment {
+        Segment::Key(key) => node.get(key).into_iter().collect(),
+        Segment::Index(index) => match node {
+            JsonValue::Array(items) => {
+                let index = match *index < 0 {
+                    true => items.len() as i64 + index,
+                    false => *index,
+                };
+                match index < 0 {
+                    true => vec![],
+                    false => items.get(index as usize).into_iter().collect(),
+                }
+            }
+            _ => vec![],
+        },
+        Segment::Wildcard => children(node),
+        Segment::Recursive(key) => {
+            let mut descendants = vec![node];
+            let mut position = 0;
+            while position < descendants.len() {
+                let mut next = children(descendants[position]);
+                descendants.append(&mut next);
+                position += 1;
+            }
+            match key {
+                Some(key) => descendants
+                    .into_iter()
+                    .filter_map(|descendant| match descendant {
+                        JsonValue::Object(map) => map.get(key),
+                        _ => None,
+                    })
+                    .collect(),
+                None => descendants,
+            }
+        }
+    }
+}
+
+fn children(node: &JsonValue) -> Vec<&JsonValue> {
+    match node {
+        JsonValue::Array(items) => items.iter().collect(),
+        JsonValue::Object(map) => map.values().collect(),
+        _ => vec![],
+    }
+}
+
+fn parse_expression(expression: &str) -> Result<Vec<Segment>, String> {
+    let chars: Vec<char> = expression.trim().chars().collect();
+    let mut segments = Vec::<Segment>::new();
+    let mut position = 0;
+
+    if chars.first() == Some(&'$') {
+        position += 1;
+    }
+
+    while position < chars.len() {
+        match chars[position] {
+            '.' => {
+                position += 1;
+                let recursive = chars.get(position) == Some(&'.');
+                if recursive {
+                    position += 1;
+                }
+                if chars.get(position) == Some(&'*') {
+                    position += 1;
+                    segments.push(match recursive {
+                        true => Segment::Recursive(None),
+                        false => Segment::Wildcard,
+                    });
+                    continue;
+                }
+                let start = position;
+                while position < chars.len() && !matches!(chars[position], '.' | '[' | '|') {
+                    position += 1;
+                }
+                let key: String = chars[start..position].iter().collect();
+                let key = key.trim_end().to_string();
+                match (recursive, key.is_empty()) {
+                    (true, true) => segments.push(Segment::Recursive(None)),
+                    (true, false) => segments.push(Segment::Recursive(Some(key))),
+                    (false, false) => segments.push(Segment::Key(key)),
+                    // A lone `.` is the jq identity filter
+                    (false, true) => {}
+                }
+            }
+            '[' => {
+                position += 1;
+                skip_whitespace(&chars, &mut position);
+                // Quoted keys can contain anything, even brackets, so read them first
+                let segment = match chars.get(position) {
+                    Some(&quote @ ('"' | '\'')) => {
+                        Segment::Key(read_quoted(&chars, &mut position, quote)?)
+                    }
+                    _ => {
+                        let start = position;
+                        while position < chars.len() && chars[position] != ']' {
+                            position += 1;
+                        }
+                        let inner: String = chars[start..position].iter().collect();
+                        let inner = inner.trim();
+                        if inner.is_empty() || inner == "*" {
+                            Segment::Wildcard
+                        } else if let Ok(index) = inner.parse::<i64>() {
+                            Segment::Index(index)
+                        } else {
+                            return Err(format!("Invalid selector [{}]", inner));
+                        }
+                    }
+                };
+                skip_whitespace(&chars, &mut position);
+                if chars.get(position) != Some(&']') {
+                    return Err(format!("Unclosed bracket at position {}", position));
+                }
+                segments.push(segment);
+                position += 1;
+            }
+            // jq pipes from one path to the next, which is the same as joining them
+            '|' => {
+                position += 1;
+                skip_whitespace(&chars, &mut position);
+                if chars.get(position) != Some(&'.') {
+                    return Err(format!("Expected a path after | at position {}", position));
+                }
+            }
+            c if c.is_whitespace()
+                && chars[position..].iter().find(|c| !c.is_whitespace()) == Some(&'|') =>
+            {
+                skip_whitespace(&chars, &mut position);
+            }
+            _ => {
+                // Allow a bare key at the start, e.g. `items[0]`
+                if !segments.is_empty() || position > 1 {
+                    return Err(format!(
+                        "Unexpected character '{}' at position {}",
+                        chars[position], position
+                    ));
+                }
+                let start = position;
+                while position < chars.len() && !matches!(chars[position], '.' | '[' | '|') {
+                    position += 1;
+                }
+                let key: String = chars[start..position].iter().collect();
+                segments.push(Segment::Key(key.trim_end().to_string()));
+            }
+        }
+    }
+
+    Ok(segments)
+}
+
+fn skip_whitespace(chars: &[char], position: &mut usize) {
+    while chars.get(*position).is_some_and(|c| c.is_whitespace()) {
+        *position += 1;
+    }
+}
+
+/// Read a quoted key starting at its opening quote, leaving the position after the closing one.
+/// Double quoted keys are JSON strings, as in the paths of the response tree.
+fn read_quoted(chars: &[char], position: &mut usize, quote: char) -> Result<String, String> {
+    let start = *position;
+    let mut key = String::new();
+    let mut escaped = false;
+    *position += 1;
+    loop {
+        let Some(&c) = chars.get(*position) else {
+            return Err(format!("Unclosed quote at position {}", start));
+        };
+        *position += 1;
+        match (c, escaped) {
+            (c, false) if c == quote => break,
+            ('\\', false) => escaped = true,
+            _ => {
+                if escaped && (quote == '"' || !matches!(c, '\'' | '\\')) {
+                    key.push('\\');
+                }
+                key.push(c);
+                escaped = false;
+            }
+        }
+    }
+    match quote {
+        '"' => serde_json::from_str::<String>(&format!("\"{}\"", key))
+            .map_err(|_| format!("Invalid key at position {}", start)),
+        _ => Ok(key),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn filter(expression: &str) -> Result<JsonValue, String> {
+        let value = json!({
+            "items": [
+                {"id": 1, "name": "a", "tags": {"name": "x"}},
+                {"id": 2, "name": "b"},
+            ],
+            "a]b": 1,
+            "a.b": 2,
+            "it's": 3,
+            "first name": "c",
+        });
+        apply_filter(expression, &value)
+    }
+
+    #[test]
+    fn keys() {
+        assert_eq!(filter("$.items[1].name"), Ok(json!("b")));
+        assert_eq!(filter(".items[0].id"), Ok(json!(1)));
+        assert_eq!(filter("items[0].id"), Ok(json!(1)));
+        assert_eq!(filter("$.first name"), Ok(json!("c")));
+        assert_eq!(
+            filter("$.missing"),
+            Err("No match for $.missing".to_string())
+        );
+    }
+
+    #[test]
+    fn quoted_keys() {
+        assert_eq!(filter(r#"$["a]b"]"#), Ok(json!(1)));
+        assert_eq!(filter(r#"$[ "a.b" ]"#), Ok(json!(2)));
+        assert_eq!(filter(r#"$['it\'s']"#), Ok(json!(3)));
+        assert_eq!(filter(r#"$["items"][0]["name"]"#), Ok(json!("a")));
+        // As written by the response tree
+        assert_eq!(
+            apply_filter(r#"$["say \"hi\""]"#, &json!({"say \"hi\"": 4})),
+            Ok(json!(4))
+        );
+    }
+
+    #[test]
+    fn indexes() {
+        assert_eq!(filter("$.items[-1].id"), Ok(json!(2)));
+        assert_eq!(filter("$.items[-2].id"), Ok(json!(1)));
+        assert!(filter("$.items[-3]").is_err());
+        assert!(filter("$.items[2]").is_err());
+        assert_eq!(apply_filter("$[0].id", &json!([{"id": 7}])), Ok(json!(7)));
+    }
+
+    #[test]
+    fn wildcards() {
+        assert_eq!(filter("$.items[*].id"), Ok(json!([1, 2])));
+        assert_eq!(filter(".items[].id"), Ok(json!([1, 2])));
+        assert_eq!(filter("$.items[0].tags.*"), Ok(json!(["x"])));
+        assert_eq!(filter("$.items[*].missing"), Ok(json!([])));
+    }
+
+    #[test]
+    fn recursive_descent() {
+        assert_eq!(filter("$..name"), Ok(json!(["a", "b", "x"])));
+        assert_eq!(filter("$..id"), Ok(json!([1, 2])));
+        assert_eq!(
+            apply_filter("$..*", &json!({"a": [1]})),
+            Ok(json!([{"a": [1]}, [1], 1]))
+        );
+    }
+
+    #[test]
+    fn jq_form() {
+        let value = json!([{"name": "a"}, {"name": "b"}]);
+        assert_eq!(apply_filter(".[] | .name", &value), Ok(json!(["a", "b"])));
+        assert_eq!(apply_filter(".[0]|.name", &value), Ok(json!("a")));
+        assert_eq!(apply_filter(".", &value), Ok(value.clone()));
+        assert_eq!(filter(".items | .[1] | .id"), Ok(json!(2)));
+    }
+
+    #[test]
+    fn malformed_expressions() {
+        assert!(filter("$.items[0").is_err());
+        assert!(filter("$.items[abc]").is_err());
+        assert!(filter(r#"$["a]b"#).is_err());
+        assert!(filter(r#"$["a" b]"#).is_err());
+        assert!(filter(".items |").is_err());
+        assert!(filter(".items | items").is_err());
+        assert!(filter("$.items[0] id").is_err());
+    }
+}
@@ -0,0 +1,576 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, value::Value as JsonValue};
+use std::collections::BTreeMap;
+
+/// The body of a request in GraphQL mode, sent as `{"query", "variables", "operationName"}`
+#[derive(Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GraphqlRequest {
+    pub query: String,
+    #[serde(default)]
+    pub variables: String, // JSON text, which may be empty
+    #[serde(default)]
+    pub operation_name: String,
+}
+
+impl GraphqlRequest {
+    pub fn body(&self) -> Result<String, String> {
+        let mut body = json!({ "query": self.query });
+        if !self.variables.trim().is_empty() {
+            let variables = serde_json::from_str::<JsonValue>(&self.variables)
+                .map_err(|err| format!("The variables aren't valid JSON: {}", err))?;
+            body["variables"] = variables;
+        }
+        if !self.operation_name.trim().is_empty() {
+            body["operationName"] = JsonValue::String(self.operation_name.trim().to_string());
+        }
+        Ok(serde_json::to_string_pretty(&body).unwrap_or_default())
+    }
+
+    /// Read a body written by `body`, or by another GraphQL client
+    pub fn from_body(text: &str) -> Option<GraphqlRequest> {
+        let body = serde_json::from_str::<JsonValue>(text).ok()?;
+        let query = body.get("query")?.as_str()?.to_string();
+        let variables = match body.get("variables") {
+            None | Some(JsonValue::Null) => String::new(),
+            Some(variables) => serde_json::to_string_pretty(variables).unwrap_or_default(),
+        };
+        let operation_name = body
+            .get("operationName")
+            .and_then(JsonValue::as_str)
+            .unwrap_or_default()
+            .to_string();
+        Some(GraphqlRequest {
+            query,
+            variables,
+            operation_name,
+        })
+    }
+}
+
+/// Describe each entry of a response's `errors` array, with where it happened
+pub fn response_errors(body: &JsonValue) -> Vec<String> {
+    let Some(errors) = body.get("errors").and_then(JsonValue::as_array) else {
+        return Vec::new();
+    };
+    errors
+        .iter()
+        .map(|error| {
+            let mut description = match error.get("message").and_then(JsonValue::as_str) {
+                Some(message) => message.to_string(),
+                None => error.to_string(),
+            };
+            if let Some(path) = error.get("path").and_then(JsonValue::as_array) {
+                let path: Vec<String> = path
+                    .iter()
+                    .map(|segment| match segment {
+                        JsonValue::String(name) => name.clone(),
+                        _ => segment.to_string(),
+                    })
+                    .collect();
+                description.push_str(&format!(" (at {})", path.join(".")));
+            }
+            let location = error
+                .get("locations")
+                .and_then(|locations| locations.get(0))
+                .and_then(|location| Some((location.get("line")?, location.get("column")?)));
+            if let Some((line, column)) = location {
+                description.push_str(&format!(" [line {}, column {}]", line, column));
+            }
+            description
+        })
+        .collect()
+}
+
+pub const INTROSPECTION_QUERY: &str = "query IntrospectionQuery {
+  __schema {
+    queryType { name }
+    mutationType { name }
+    subscriptionType { name }
+    types {
+      kind
+      name
+      description
+      fields(includeDeprecated: true) {
+        name
+        description
+        args { name type { ...TypeRef } }
+        type { ...TypeRef }
+      }
+      inputFields { name description type { ...TypeRef } }
+      enumValues(includeDeprecated: true) { name description }
+    }
+  }
+}
+
+fragment TypeRef on __Type {
+  kind
+  name
+  ofType { kind name ofType { kind name ofType { kind name ofType { kind name } } } }
+}";
+
+/// The parts of an introspected schema needed to complete fields and browse types
+pub struct Schema {
+    pub query_type: Option<String>,
+    pub mutation_type: Option<String>,
+    pub subscription_type: Option<String>,
+    pub types: BTreeMap<String, SchemaType>,
+}
+
+pub struct SchemaType {
+    pub description: Option<String>,
+    pub fields: Vec<SchemaField>, // Including input fields and enum values, which have no type
+}
+
+pub struct SchemaField {
+    pub name: String,
+    pub description: Option<String>,
+    pub type_name: String, // As written in a query, e.g. [User!]!
+    pub base_type: String, // Without the list and non-null wrappers, e.g. User
+    pub arguments: Vec<(String, String)>,
+}
+
+/// An entry in the schema browser
+pub struct SchemaItem {
+    pub label: String,
+    pub type_name: String,
+    pub description: Option<String>,
+    pub target: Option<String>, // The type to show when the entry is opened, if it has fields
+}
+
+impl Schema {
+    /// Read the response to `INTROSPECTION_QUERY`
+    pub fn from_introspection(body: &JsonValue) -> Result<Schema, String> {
+        let schema = match body.pointer("/data/__schema") {
+            Some(schema) => schema,
+            None => {
+                let errors = response_errors(body);
+                return Err(match errors.is_empty() {
+                    true => "The response has no data.__schema".to_string(),
+                    false => errors.join("\n"),
+                });
+            }
+        };
+        let root_name = |key: &str| {
+            schema
+                .pointer(&format!("/{}/name", key))
+                .and_then(JsonValue::as_str)
+                .map(str::to_string)
+        };
+
+        let mut types = BTreeMap::<String, SchemaType>::new();
+        for schema_type in schema
+            .get("types")
+            .and_then(JsonValue::as_array)
+            .into_iter()
+            .flatten()
+        {
+            let Some(name) = schema_type.get("name").and_then(JsonValue::as_str) else {
+                continue;
+            };
+            let list = |key: &str| {
+                schema_type
+                    .get(key)
+                    .and_then(JsonValue::as_array)
+                    .cloned()
+                    .unwrap_or_default()
+            };
+            let fields = list("fields")
+                .iter()
+                .chain(list("inputFields").iter())
+                .chain(list("enumValues").iter())
+                .filter_map(parse_field)
+                .collect();
+            types.insert(
+                name.to_string(),
+                SchemaType {
+                    description: description(schema_type),
+                    fields,
+                },
+            );
+        }
+
+        Ok(Schema {
+            query_type: root_name("queryType"),
+            mutation_type: root_name("mutationType"),
+            subscription_type: root_name("subscriptionType"),
+            types,
+        })
+    }
+
+    /// The entries of a type, or the root operation types when there's no type
+    pub fn browse(&self, type_name: Option<&str>) -> Vec<SchemaItem> {
+        let Some(type_name) = type_name else {
+            return [
+                ("query", &self.query_type),
+                ("mutation", &self.mutation_type),
+                ("subscription", &self.subscription_type),
+            ]
+            .into_iter()
+            .filter_map(|(operation, root)| {
+                let root = root.as_ref()?;
+                Some(SchemaItem {
+                    label: operation.to_string(),
+                    type_name: root.clone(),
+                    description: self
+                        .types
+                        .get(root)
+                        .and_then(|root| root.description.clone()),
+                    target: Some(root.clone()),
+                })
+            })
+            .collect();
+        };
+        let Some(schema_type) = self.types.get(type_name) else {
+            return Vec::new();
+        };
+        schema_type
+            .fields
+            .iter()
+            .map(|field| {
+                let label = match field.arguments.is_empty() {
+                    true => field.name.clone(),
+                    false => format!(
+                        "{}({})",
+                        field.name,
+                        field
+                            .arguments
+                            .iter()
+                            .map(|(name, type_name)| format!("{}: {}", name, type_name))
+                            .collect::<Vec<String>>()
+                            .join(", ")
+                    ),
+                };
+                let has_fields = self
+                    .types
+                    .get(&field.base_type)
+                    .is_some_and(|field_type| !field_type.fields.is_empty());
+                SchemaItem {
+                    label,
+                    type_name: field.type_name.clone(),
+                    description: field.description.clone(),
+                    target: has_fields.then(|| field.base_type.clone()),
+                }
+            })
+            .collect()
+    }
+
+    /// The word being typed at the end of the text, and the fields which could complete it
+    pub fn completions<'a>(&'a self, text: &str) -> (String, Vec<&'a SchemaField>) {
+        let partial: String = {
+            let reversed: String = text
+                .chars()
+                .rev()
+                .take_while(|c| c.is_alphanumeric() || *c == '_')
+                .collect();
+            reversed.chars().rev().collect()
+        };
+        let Some(Some(type_name)) = self.selection_types(text).pop() else {
+            return (partial, Vec::new());
+        };
+        let fields = match self.types.get(&type_name) {
+            Some(schema_type) => schema_type
+                .fields
+                .iter()
+                .filter(|field| !field.type_name.is_empty())
+                .filter(|field| field.name.starts_with(&partial) && field.name != partial)
+                .collect(),
+            None => Vec::new(),
+        };
+        (partial, fields)
+    }
+
+    /// The type of each selection set the end of the text is in, outermost first. A type is
+    /// `None` when it's unknown, e.g. because a field is misspelled.
+    fn selection_types(&self, text: &str) -> Vec<Option<String>> {
+        let mut stack = Vec::<Option<String>>::new();
+        let mut words = Vec::<String>::new(); // Since the last brace at this depth
+        let mut parentheses = 0;
+        let mut chars = text.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '#' => {
+                    // Comments run to the end of the line
+                    while chars.next_if(|c| *c != '\n').is_some() {}
+                }
+                '"' => {
+                    let mut escaped = false;
+                    for c in chars.by_ref() {
+                        match (c, escaped) {
+                            ('"', false) => break,
+                            ('\\', false) => escaped = true,
+                            _ => escaped = false,
+                        }
+                    }
+                }
+                '(' => parentheses += 1,
+                ')' => parentheses -= 1,
+                _ if parentheses > 0 => {}
+                '{' => {
+                    let selection_type = match stack.last() {
+                        None => self.operation_type(&words),
+                        Some(parent) => {
+                            // An inline fragment names its type, otherwise the last word is a field
+                            match words.iter().rev().collect::<Vec<&String>>()[..] {
+                                [type_name, on, ..] if on == "on" => Some(type_name.clone()),
+                                [field, ..] => parent
+                                    .as_ref()
+                                    .and_then(|parent| self.types.get(parent))
+                                    .and_then(|parent| {
+                                        parent.fields.iter().find(|item| item.name == **field)
+                                    })
+                                    .map(|field| field.base_type.clone()),
+                                [] => None,
+                            }
+                        }
+                    };
+                    stack.push(selection_type);
+                    words.clear();
+                }
+                '}' => {
+                    stack.pop();
+                    words.clear();
+                }
+                c if c.is_alphanumeric() || c == '_' => {
+                    let mut word = c.to_string();
+                    while let Some(c) = chars.next_if(|c| c.is_alphanumeric() || *c == '_') {
+                        word.push(c);
+                    }
+                    words.push(word);
+                }
+                _ => {}
+            }
+        }
+        stack
+    }
+
+    /// The root type of an operation from the words before its opening brace
+    fn operation_type(&self, words: &[String]) -> Option<String> {
+        match words.first().map(String::as_str) {
+            Some("mutation") => self.mutation_type.clone(),
+            Some("subscription") => self.subscription_type.clone(),
+            Some("fragment") => match words {
+                [_, _, on, type_name, ..] if on == "on" => Some(type_name.clone()),
+                _ => None,
+            },
+            _ => self.query_type.clone(),
+        }
+    }
+}
+
+fn string_at(value: &JsonValue, key: &str) -> String {
+    value
+        .get(key)
+        .and_then(JsonValue::as_str)
+        .unwrap_or_default()
+        .to_string()
+}
+
+fn description(value: &JsonValue) -> Option<String> {
+    value
+        .get("description")
+        .and_then(JsonValue::as_str)
+        .filter(|description| !description.is_empty())
+        .map(str::to_string)
+}
+
+fn parse_field(field: &JsonValue) -> Option<SchemaField> {
+    let (type_name, base_type) = match field.get("type") {
+        Some(type_ref) => type_names(type_ref),
+        None => (String::new(), String::new()), // An enum value
+    };
+    let arguments = field
+        .get("args")
+        .and_then(JsonValue::as_array)
+        .into_iter()
+        .flatten()
+        .map(|argument| {
+            let (type_name, _) = argument.get("type").map(type_names).unwrap_or_default();
+            (string_at(argument, "name"), type_name)
+        })
+        .collect();
+    Some(SchemaField {
+        name: field.get("name")?.as_str()?.to_string(),
+        description: description(field),
+        type_name,
+        base_type,
+        arguments,
+    })
+}
+
+/// A type reference as written in a query, and the named type inside any wrappers
+fn type_names(type_ref: &JsonValue) -> (String, String) {
+    match (string_at(type_ref, "kind").as_str(), type_ref.get("ofType")) {
+        ("NON_NULL", Some(inner)) => {
+            let (type_name, base_type) = type_names(inner);
+            (format!("{}!", type_name), base_type)
+        }
+        ("LIST", Some(inner)) => {
+            let (type_name, base_type) = type_names(inner);
+            (format!("[{}]", type_name), base_type)
+        }
+        _ => {
+            let name = string_at(type_ref, "name");
+            (name.clone(), name)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn named(name: &str) -> JsonValue {
+        json!({ "kind": "OBJECT", "name": name })
+    }
+
+    fn schema() -> Schema {
+        let field = |name: &str, type_ref: JsonValue| json!({ "name": name, "type": type_ref });
+        let body = json!({
+            "data": {
+                "__schema": {
+                    "queryType": { "name": "Query" },
+                    "types": [
+                        {
+                            "name": "Query",
+                            "fields": [
+                                {
+                                    "name": "user",
+                                    "args": [{
+                                        "name": "id",
+                                        "type": { "kind": "NON_NULL", "ofType": named("ID") },
+                                    }],
+                                    "type": named("User"),
+                                },
+                                field("users", json!({ "kind": "LIST", "ofType": named("User") })),
+                                field("node", named("Node")),
+                            ],
+                        },
+                        {
+                            "name": "User",
+                            "fields": [
+                                field("id", named("ID")),
+                                field("name", named("String")),
+                                field("nickname", named("String")),
+                                field("posts", json!({ "kind": "LIST", "ofType": named("Post") })),
+                            ],
+                        },
+                        {
+                            "name": "Post",
+                            "fields": [field("title", named("String")), field("author", named("User"))],
+                        },
+                        { "name": "Node", "fields": [field("id", named("ID"))] },
+                    ],
+                },
+            },
+        });
+        Schema::from_introspection(&body).unwrap()
+    }
+
+    fn completion_names(text: &str) -> (String, Vec<String>) {
+        let schema = schema();
+        let (partial, fields) = schema.completions(text);
+        (
+            partial,
+            fields.iter().map(|field| field.name.clone()).collect(),
+        )
+    }
+
+    #[test]
+    fn nested_selections() {
+        let schema = schema();
+        assert_eq!(
+            schema.selection_types("query Posts { users { posts { "),
+            vec![
+                Some("Query".to_string()),
+                Some("User".to_string()),
+                Some("Post".to_string()),
+            ]
+        );
+        // Closed selections are left behind
+        assert_eq!(
+            schema.selection_types("{ users { posts { title } } node { "),
+            vec![Some("Query".to_string()), Some("Node".to_string())]
+        );
+        assert_eq!(
+            completion_names("{ users { posts { author { n"),
+            (
+                "n".to_string(),
+                vec!["name".to_string(), "nickname".to_string()]
+            )
+        );
+    }
+
+    #[test]
+    fn arguments_are_skipped() {
+        assert_eq!(
+            completion_names("{ user(id: \"}{\", filter: { a: 1 }) { posts { t"),
+            ("t".to_string(), vec!["title".to_string()])
+        );
+    }
+
+    #[test]
+    fn inline_fragments_and_fragments() {
+        assert_eq!(
+            completion_names("{ node { id ... on User { p"),
+            ("p".to_string(), vec!["posts".to_string()])
+        );
+        assert_eq!(
+            completion_names("fragment Details on Post { a"),
+            ("a".to_string(), vec!["author".to_string()])
+        );
+    }
+
+    #[test]
+    fn comments_are_skipped() {
+        assert_eq!(
+            completion_names("{ users { # posts {\n  n"),
+            (
+                "n".to_string(),
+                vec!["name".to_string(), "nickname".to_string()]
+            )
+        );
+    }
+
+    #[test]
+    fn unknown_types_have_no_completions() {
+        assert_eq!(
+            completion_names("{ missing { n"),
+            ("n".to_string(), Vec::new())
+        );
+        assert_eq!(
+            completion_names("mutation { u"),
+            ("u".to_string(), Vec::new())
+        );
+        // A complete field isn't offered again
+        assert_eq!(
+            completion_names("{ users { name"),
+            ("name".to_string(), Vec::new())
+        );
+    }
+
+    #[test]
+    fn describes_response_errors() {
+        let body = json!({
+            "errors": [
+                {
+                    "message": "Not allowed",
+                    "path": ["users", 1, "email"],
+                    "locations": [{ "line": 3, "column": 5 }],
+                },
+                { "message": "Too complex" },
+                { "code": 500 },
+            ],
+        });
+        assert_eq!(
+            response_errors(&body),
+            vec![
+                "Not allowed (at users.1.email) [line 3, column 5]",
+                "Too complex",
+                "{\"code\":500}",
+            ]
+        );
+        assert!(response_errors(&json!({ "data": {} })).is_empty());
+        assert!(response_errors(&json!({ "errors": "not a list" })).is_empty());
+    }
+}
@@ -0,0 +1,452 @@
+use crate::http_request::{HttpMethod, HttpResponse};
+use crate::request_file::RequestFile;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// An HTTP Archive, as exported by browser devtools: http://www.softwareishard.com/blog/har-12-spec/
+///
+/// Only the fields tapir uses are kept when importing; anything else is ignored.
+#[derive(Serialize, Deserialize)]
+pub struct Har {
+    pub log: HarLog,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct HarLog {
+    #[serde(default)]
+    pub version: String,
+    #[serde(default)]
+    pub creator: HarCreator,
+    pub entries: Vec<HarEntry>,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+pub struct HarCreator {
+    pub name: String,
+    pub version: String,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HarEntry {
+    pub started_date_time: String,
+    #[serde(default)]
+    pub time: f64,
+    pub request: HarRequest,
+    pub response: HarResponse,
+    #[serde(default)]
+    pub cache: serde_json::Map<String, serde_json::Value>,
+    #[serde(default)]
+    pub timings: HarTimings,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HarRequest {
+    pub method: String,
+    pub url: String,
+    #[serde(default)]
+    pub http_version: String,
+    #[serde(default)]
+    pub cookies: Vec<HarPair>,
+    #[serde(default)]
+    pub headers: Vec<HarPair>,
+    #[serde(default)]
+    pub query_string: Vec<HarPair>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub post_data: Option<HarPostData>,
+    #[serde(default = "unknown_size")]
+    pub headers_size: i64,
+    #[serde(default = "unknown_size")]
+    pub body_size: i64,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HarResponse {
+    pub status: u16, // 0 when the browser got no response
+    #[serde(default)]
+    pub status_text: String,
+    #[serde(default)]
+    pub http_version: String,
+    #[serde(default)]
+    pub cookies: Vec<HarPair>,
+    #[serde(default)]
+    pub headers: Vec<HarPair>,
+    #[serde(default)]
+    pub content: HarContent,
+    #[serde(default, rename = "redirectURL")]
+    pub redirect_url: String,
+    #[serde(default = "unknown_size")]
+    pub headers_size: i64,
+    #[serde(default = "unknown_size")]
+    pub body_size: i64,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct HarPair {
+    pub name: String,
+    #[serde(default)]
+    pub value: String,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HarPostData {
+    #[serde(default)]
+    pub mime_type: String,
+    #[serde(default)]
+    pub text: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub params: Vec<HarPair>, // Form fields, given instead of the text by some browsers
+}
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HarContent {
+    #[serde(default)]
+    pub size: i64,
+    #[serde(default)]
+    pub mime_type: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encoding: Option<String>, // "base64" for binary content
+}
+
+/// Times in milliseconds, with -1 for the phases which weren't measured
+#[derive(Clone, Serialize, Deserialize)]
+pub struct HarTimings {
+    #[serde(default = "unknown_time")]
+    pub blocked: f64,
+    #[serde(default = "unknown_time")]
+    pub dns: f64,
+    #[serde(default = "unknown_time")]
+    pub connect: f64,
+    #[serde(default)]
+    pub send: f64,
+    #[serde(default)]
+    pub wait: f64,
+    #[serde(default)]
+    pub receive: f64,
+    #[serde(default = "unknown_time")]
+    pub ssl: f64,
+}
+
+impl Default for HarTimings {
+    fn default() -> HarTimings {
+        HarTimings {
+            blocked: -1.0,
+            dns: -1.0,
+            connect: -1.0,
+            send: 0.0,
+            wait: 0.0,
+            receive: 0.0,
+            ssl: -1.0,
+        }
+    }
+}
+
+fn unknown_size() -> i64 {
+    -1
+}
+
+fn unknown_time() -> f64 {
+    -1.0
+}
+
+// Headers which describe the connection or encoding a browser used rather than the request,
+// and which reqwest sets itself
+const CONNECTION_HEADERS: [&str; 5] = [
+    "host",
+    "content-length",
+    "connection",
+    "accept-encoding",
+    "transfer-encoding",
+];
+
+impl HarEntry {
+    /// The entry's request, ready to be sent again
+    pub fn request_file(&self) -> Result<RequestFile, String> {
+        let method = self.request.method.parse::<HttpMethod>()?;
+        let headers = self
+            .request
+            .headers
+            .iter()
+            // HTTP/2 pseudo headers such as :authority are part of the URL
+            .filter(|header| !header.name.starts_with(':'))
+            .filter(|header| {
+                !CONNECTION_HEADERS
+                    .iter()
+                    .any(|name| header.name.eq_ignore_ascii_case(name))
+            })
+            .map(|header| (header.name.clone(), header.value.clone()))
+            .collect();
+        let body = match self.request.post_data {
+            Some(ref post_data) if post_data.text.is_empty() => {
+                url::form_urlencoded::Serializer::new(String::new())
+                    .extend_pairs(
+                        post_data
+                            .params
+                            .iter()
+                            .map(|param| (&param.name, &param.value)),
+                    )
+                    .finish()
+            }
+            Some(ref post_data) => post_data.text.clone(),
+            None => String::new(),
+        };
+        Ok(RequestFile {
+            method,
+            endpoint: self.request.url.clone(),
+            headers,
+            body,
+        })
+    }
+}
+
+/// Record a response, and the request which was sent for it, as a HAR entry
+pub fn har_entry(response: &HttpResponse) -> HarEntry {
+    let header_value = |headers: &[(String, String)], name: &str| {
+        headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.clone())
+            .unwrap_or_default()
+    };
+    let pairs = |headers: &[(String, String)]| {
+        headers
+            .iter()
+            .map(|(name, value)| HarPair {
+                name: name.clone(),
+                value: value.clone(),
+            })
+            .collect::<Vec<HarPair>>()
+    };
+    let request = &response.request;
+    // Binary bodies can't be kept as text without losing bytes
+    let (text, encoding) = match std::str::from_utf8(&response.body_bytes) {
+        Ok(text) => (text.to_string(), None),
+        Err(_) => (
+            STANDARD.encode(&response.body_bytes),
+            Some("base64".to_string()),
+        ),
+    };
+    let query_string = match Url::parse(&request.url) {
+        Ok(url) => url
+            .query_pairs()
+            .map(|(name, value)| HarPair {
+                name: name.to_string(),
+                value: value.to_string(),
+            })
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+    let milliseconds = |duration: std::time::Duration| duration.as_secs_f64() * 1000.0;
+    let timings = HarTimings {
+        wait: milliseconds(response.timings.wait),
+        receive: milliseconds(response.timings.receive),
+        ..HarTimings::default()
+    };
+
+    HarEntry {
+        started_date_time: format_timestamp(response.timings.started),
+        time: timings.wait + timings.receive,
+        request: HarRequest {
+            method: request.method.clone(),
+            url: request.url.clone(),
+            http_version: response.http_version.clone(),
+            cookies: Vec::new(),
+            headers: pairs(&request.headers),
+            query_string,
+            post_data: request.body.as_ref().map(|body| HarPostData {
+                mime_type: header_value(&request.headers, "content-type"),
+                text: body.clone(),
+                params: Vec::new(),
+            }),
+            headers_size: -1,
+            body_size: request.body.as_ref().map_or(0, |body| body.len() as i64),
+        },
+        response: HarResponse {
+            status: response.status,
+            status_text: reqwest::StatusCode::from_u16(response.status)
+                .ok()
+                .and_then(|status| status.canonical_reason())
+                .unwrap_or_default()
+                .to_string(),
+            http_version: response.http_version.clone(),
+            cookies: Vec::new(),
+            headers: pairs(&response.headers),
+            content: HarContent {
+                size: response.body_bytes.len() as i64,
+                mime_type: header_value(&response.headers, "content-type"),
+                text: Some(text),
+                encoding,
+            },
+            redirect_url: header_value(&response.headers, "location"),
+            headers_size: -1,
+            body_size: response.body_bytes.len() as i64,
+        },
+        cache: serde_json::Map::new(),
+        timings,
+    }
+}
+
+pub fn save_har(path: &Path, entries: Vec<HarEntry>) -> Result<(), String> {
+    let har = Har {
+        log: HarLog {
+            version: "1.2".to_string(),
+            creator: HarCreator {
+                name: "tapir".to_string(),
+                version: env!("CARGO_PKG_VERSION").to_string(),
+            },
+            entries,
+        },
+    };
+    let text = serde_json::to_string_pretty(&har).map_err(|err| err.to_string())?;
+    fs::write(path, text).map_err(|err| format!("Unable to write {}: {}", path.display(), err))
+}
+
+pub fn load_har(path: &Path) -> Result<Vec<HarEntry>, String> {
+    let text = fs::read_to_string(path)
+        .map_err(|err| format!("Unable to read {}: {}", path.display(), err))?;
+    let har: Har = serde_json::from_str(&text)
+        .map_err(|err| format!("Invalid HAR file {}: {}", path.display(), err))?;
+    Ok(har.log.entries)
+}
+
+/// An ISO 8601 timestamp in UTC, such as 2023-11-05T14:03:09.125Z
+fn format_timestamp(time: SystemTime) -> String {
+    let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let seconds = since_epoch.as_secs();
+    let (days, seconds_of_day) = (seconds / 86400, seconds % 86400);
+
+    // Convert days since the epoch to a civil date, after Howard Hinnant's civil_from_days
+    let days = days as i64 + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = match month_index < 10 {
+        true => month_index + 3,
+        false => month_index - 9,
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        year,
+        month,
+        day,
+        seconds_of_day / 3600,
+        seconds_of_day % 3600 / 60,
+        seconds_of_day % 60,
+        since_epoch.subsec_millis()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http_request::{SentRequest, Timings};
+    use std::time::Duration;
+
+    fn timestamp(seconds: u64, milliseconds: u64) -> String {
+        format_timestamp(
+            UNIX_EPOCH + Duration::from_secs(seconds) + Duration::from_millis(milliseconds),
+        )
+    }
+
+    #[test]
+    fn formats_timestamps() {
+        assert_eq!(timestamp(0, 0), "1970-01-01T00:00:00.000Z");
+        assert_eq!(timestamp(1699193189, 125), "2023-11-05T14:06:29.125Z");
+        assert_eq!(timestamp(1719792000, 0), "2024-07-01T00:00:00.000Z");
+        assert_eq!(timestamp(4102444800, 999), "2100-01-01T00:00:00.999Z");
+    }
+
+    #[test]
+    fn formats_leap_days() {
+        assert_eq!(timestamp(951782400, 0), "2000-02-29T00:00:00.000Z");
+        assert_eq!(timestamp(1709251199, 0), "2024-02-29T23:59:59.000Z");
+    }
+
+    #[test]
+    fn encodes_form_params() {
+        let entry: HarEntry = serde_json::from_value(serde_json::json!({
+            "startedDateTime": "2023-11-05T14:06:29.125Z",
+            "request": {
+                "method": "POST",
+                "url": "https://example.com/login",
+                "postData": {
+                    "mimeType": "application/x-www-form-urlencoded",
+                    "params": [
+                        {"name": "user", "value": "a&b=c"},
+                        {"name": "note", "value": "hello world ✓"},
+                    ],
+                },
+            },
+            "response": {"status": 200},
+        }))
+        .unwrap();
+        assert_eq!(
+            entry.request_file().unwrap().body,
+            "user=a%26b%3Dc&note=hello+world+%E2%9C%93"
+        );
+    }
+
+    fn response(headers: &[(&str, &str)], body_bytes: &[u8]) -> HttpResponse {
+        HttpResponse {
+            status: 200,
+            http_version: "HTTP/1.1".to_string(),
+            headers: headers
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect(),
+            body: String::from_utf8_lossy(body_bytes).to_string(),
+            body_bytes: body_bytes.to_vec(),
+            request: SentRequest {
+                method: "GET".to_string(),
+                url: "https://example.com/image?size=2".to_string(),
+                headers: Vec::new(),
+                body: None,
+            },
+            timings: Timings {
+                started: UNIX_EPOCH,
+                wait: Duration::from_millis(20),
+                receive: Duration::from_millis(5),
+            },
+        }
+    }
+
+    #[test]
+    fn exports_text_bodies_as_they_are() {
+        let entry = har_entry(&response(
+            &[("Content-Type", "text/plain")],
+            "héllo\r\n".as_bytes(),
+        ));
+        assert_eq!(entry.response.content.text.as_deref(), Some("héllo\r\n"));
+        assert_eq!(entry.response.content.encoding, None);
+        assert_eq!(entry.response.content.size, 8);
+        assert_eq!(entry.response.content.mime_type, "text/plain");
+        assert_eq!(entry.time, 25.0);
+        assert_eq!(entry.request.query_string[0].name, "size");
+    }
+
+    #[test]
+    fn exports_binary_bodies_as_base64() {
+        let bytes = [0x89, b'P', b'N', b'G', 0xff, 0x00];
+        let entry = har_entry(&response(&[("Content-Type", "image/png")], &bytes));
+        assert_eq!(entry.response.content.text.as_deref(), Some("iVBOR/8A"));
+        assert_eq!(entry.response.content.encoding.as_deref(), Some("base64"));
+        assert_eq!(entry.response.content.size, 6);
+        assert_eq!(entry.response.body_size, 6);
+    }
+}
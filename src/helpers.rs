@@ -1,5 +1,8 @@
-pub fn evaluate_new_index(current: Option<u8>, length: u8, reverse: bool) -> u8 {
-    match reverse {
+pub fn evaluate_new_index(current: Option<usize>, length: usize, reverse: bool) -> Option<usize> {
+    if length == 0 {
+        return None;
+    }
+    let new_index = match reverse {
         false => match current {
             Some(current_index) => {
                 if current_index < length - 1 {
@@ -13,12 +16,22 @@ pub fn evaluate_new_index(current: Option<u8>, length: u8, reverse: bool) -> u8
         true => match current {
             Some(current_index) => {
                 if current_index > 0 {
-                    current_index - 1
+                    (current_index - 1).min(length - 1)
                 } else {
                     0
                 }
             }
             None => length - 1,
         },
+    };
+    Some(new_index)
+}
+
+/// Keep a selected index within the bounds of a list which may have shrunk
+pub fn clamp_index(current: Option<usize>, length: usize) -> Option<usize> {
+    match (current, length) {
+        (_, 0) => None,
+        (Some(current_index), _) => Some(current_index.min(length - 1)),
+        (None, _) => None,
     }
 }
@@ -0,0 +1,552 @@
+use crate::http_request::HttpMethod;
+use crate::request_file::RequestFile;
+use crate::variables::substitute;
+use serde::{Deserialize, Serialize};
+use serde_json::value::Value as JsonValue;
+use std::collections::HashMap;
+use std::fs;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+/// Requests in the format of the VS Code REST Client and the JetBrains HTTP Client:
+///
+/// ```text
+/// @host = https://example.com
+///
+/// ### List the items
+/// GET {{host}}/items
+///
+/// ###
+/// # @name create-item
+/// POST {{host}}/items
+/// Content-Type: application/json
+///
+/// {"name": "tapir"}
+/// ```
+pub struct HttpFile {
+    pub variables: Vec<(String, String)>,
+    pub requests: Vec<HttpFileRequest>,
+    pub skipped: Vec<String>, // Why each request which can't be opened was left out
+}
+
+pub struct HttpFileRequest {
+    pub name: Option<String>,
+    pub request: RequestFile,
+    spans: RequestSpans,
+}
+
+/// Where each part of a request is in the text of its file, as byte ranges, so that saving
+/// it only has to replace the parts which changed
+#[derive(Clone, Default)]
+struct RequestSpans {
+    request_line: Range<usize>, // Including any query continuation lines
+    headers: Range<usize>,      // Empty, at the end of the request line, without headers
+    body: Range<usize>,         // Empty, at the end of the headers, without a body
+    version: Option<String>,
+}
+
+/// Where a tab's request came from in a .http file, so it can be saved back there
+#[derive(Clone, Serialize, Deserialize)]
+pub struct HttpFileSource {
+    pub path: PathBuf,
+    pub index: usize,
+    #[serde(default)]
+    pub variables: Vec<(String, String)>,
+}
+
+impl std::fmt::Display for HttpFile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (name, value) in self.variables.iter() {
+            writeln!(f, "@{} = {}", name, value)?;
+        }
+        for (index, request) in self.requests.iter().enumerate() {
+            if index > 0 || !self.variables.is_empty() {
+                writeln!(f)?;
+            }
+            match request.name {
+                Some(ref name) => writeln!(f, "### {}", name)?,
+                None if index > 0 => writeln!(f, "###")?,
+                None => {}
+            }
+            write!(f, "{}", request.request)?;
+        }
+        Ok(())
+    }
+}
+
+/// Parse the requests in a .http file, which are separated by lines starting with `###`
+pub fn parse_http_file(text: &str) -> Result<HttpFile, String> {
+    let mut variables = Vec::<(String, String)>::new();
+    let mut requests = Vec::<HttpFileRequest>::new();
+    let mut skipped = Vec::<String>::new();
+
+    // Each line with the offset it starts at
+    let mut blocks = vec![(None, Vec::<(usize, &str)>::new())];
+    let mut offset = 0;
+    for line in text.split_inclusive('\n') {
+        let line_start = offset;
+        offset += line.len();
+        let line = line.strip_suffix('\n').unwrap_or(line);
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        match line.trim_start().strip_prefix("###") {
+            Some(name) => {
+                let name = name.trim();
+                blocks.push(((!name.is_empty()).then(|| name.to_string()), Vec::new()));
+            }
+            None => blocks.last_mut().unwrap().1.push((line_start, line)),
+        }
+    }
+    let line_end = |(start, line): (usize, &str)| start + line.len();
+
+    for (mut name, lines) in blocks {
+        let mut lines = lines.into_iter();
+
+        // Variables and comments come before the request line
+        let mut request_line = None;
+        for (start, line) in lines.by_ref() {
+            let line = line.trim();
+            if let Some(comment) = line.strip_prefix('#').or(line.strip_prefix("//")) {
+                if let Some(request_name) = comment.trim().strip_prefix("@name") {
+                    name = Some(
+                        request_name
+                            .trim_start_matches([' ', '='])
+                            .trim()
+                            .to_string(),
+                    );
+                }
+            } else if let Some(definition) = line.strip_prefix('@') {
+                match definition.split_once('=') {
+                    Some((variable, value)) if !variable.trim().is_empty() => {
+                        variables.push((variable.trim().to_string(), value.trim().to_string()))
+                    }
+                    _ => {
+                        return Err(format!(
+                            "Invalid variable \"{}\", expected \"@name = value\"",
+                            line
+                        ))
+                    }
+                }
+            } else if !line.is_empty() {
+                request_line = Some((start, line));
+                break;
+            }
+        }
+        let Some((request_start, request_line)) = request_line else {
+            continue;
+        };
+        let mut spans = RequestSpans {
+            request_line: request_start..request_start + request_line.len(),
+            ..Default::default()
+        };
+
+        // The method is optional, as is the HTTP version after the URL
+        let (method, endpoint) = match request_line.split_once(char::is_whitespace) {
+            Some((method, endpoint)) if method.chars().all(|c| c.is_ascii_alphabetic()) => {
+                match method.parse::<HttpMethod>() {
+                    Ok(method) => (method, endpoint.trim()),
+                    Err(err) => {
+                        skipped.push(format!("Skipped {}: {}", request_line, err));
+                        continue;
+                    }
+                }
+            }
+            _ => (HttpMethod::GET, request_line),
+        };
+        let mut endpoint = match endpoint.rsplit_once(char::is_whitespace) {
+            Some((url, version)) if version.starts_with("HTTP/") => {
+                spans.version = Some(version.to_string());
+                url.trim_end().to_string()
+            }
+            _ => endpoint.to_string(),
+        };
+
+        let mut headers = Vec::<(String, String)>::new();
+        let mut lines = lines.peekable();
+        // Long query strings can continue over the following lines
+        while let Some(line) = lines.next_if(|(_, line)| line.trim_start().starts_with(['?', '&']))
+        {
+            endpoint.push_str(line.1.trim());
+            spans.request_line.end = line_end(line);
+        }
+        spans.headers = spans.request_line.end..spans.request_line.end;
+        for (start, line) in lines.by_ref() {
+            if line.trim().is_empty() {
+                break;
+            }
+            if spans.headers.is_empty() {
+                spans.headers.start = start;
+            }
+            spans.headers.end = line_end((start, line));
+            let line = line.trim();
+            if line.starts_with('#') || line.starts_with("//") {
+                continue;
+            }
+            match line.split_once(':') {
+                Some((key, value)) if !key.trim().is_empty() => {
+                    headers.push((key.trim().to_string(), value.trim().to_string()))
+                }
+                _ => {
+                    return Err(format!(
+                        "Invalid header \"{}\" in the request to {}, expected \"Name: value\"",
+                        line, endpoint
+                    ))
+                }
+            }
+        }
+
+        let body_lines: Vec<(usize, &str)> = lines
+            .skip_while(|(_, line)| line.trim().is_empty())
+            .collect();
+        let last_line = body_lines
+            .iter()
+            .rposition(|(_, line)| !line.trim().is_empty());
+        spans.body = match (body_lines.first(), last_line) {
+            (Some((start, _)), Some(last_line)) => *start..line_end(body_lines[last_line]),
+            _ => spans.headers.end..spans.headers.end,
+        };
+        let body = text[spans.body.clone()].trim().replace("\r\n", "\n");
+        requests.push(HttpFileRequest {
+            name,
+            request: RequestFile {
+                method,
+                endpoint,
+                headers,
+                body,
+            },
+            spans,
+        });
+    }
+
+    Ok(HttpFile {
+        variables,
+        requests,
+        skipped,
+    })
+}
+
+/// A file's variables added to the environment's. Their values can use the environment's
+/// variables, or those defined before them.
+pub fn resolve_variables(
+    variables: &[(String, String)],
+    environment: &HashMap<String, String>,
+) -> HashMap<String, String> {
+    let mut resolved = environment.clone();
+    for (name, value) in variables.iter() {
+        let value = substitute(value, &resolved, &mut Vec::new());
+        resolved.insert(name.clone(), value);
+    }
+    resolved
+}
+
+pub fn load_http_file(path: &Path) -> Result<HttpFile, String> {
+    let text = fs::read_to_string(path)
+        .map_err(|err| format!("Unable to read {}: {}", path.display(), err))?;
+    parse_http_file(&text).map_err(|err| format!("Invalid request in {}: {}", path.display(), err))
+}
+
+/// Replace a request in a .http file, or add it when there's no request at that index.
+/// A new file starts with the variables given. Returns the index the request was saved at.
+///
+/// Only the parts of the request which changed are rewritten, leaving the rest of the file,
+/// with its comments and variables, as it was.
+pub fn save_to_http_file(
+    path: &Path,
+    index: Option<usize>,
+    request: RequestFile,
+    variables: &[(String, String)],
+) -> Result<usize, String> {
+    if !path.exists() {
+        let http_file = HttpFile {
+            variables: variables.to_vec(),
+            requests: vec![HttpFileRequest {
+                name: None,
+                request,
+                spans: RequestSpans::default(),
+            }],
+            skipped: Vec::new(),
+        };
+        fs::write(path, http_file.to_string())
+            .map_err(|err| format!("Unable to write {}: {}", path.display(), err))?;
+        return Ok(0);
+    }
+
+    let text = fs::read_to_string(path)
+        .map_err(|err| format!("Unable to read {}: {}", path.display(), err))?;
+    let http_file = parse_http_file(&text)
+        .map_err(|err| format!("Invalid request in {}: {}", path.display(), err))?;
+    let (text, index) = match index {
+        Some(index) if index < http_file.requests.len() => (
+            replace_request(&text, &http_file.requests[index], &request),
+            index,
+        ),
+        _ => (append_request(&text, &request), http_file.requests.len()),
+    };
+    fs::write(path, text).map_err(|err| format!("Unable to write {}: {}", path.display(), err))?;
+    Ok(index)
+}
+
+/// The text of a file with one of its requests replaced
+fn replace_request(text: &str, old: &HttpFileRequest, new: &RequestFile) -> String {
+    let newline = line_ending(text);
+    let spans = &old.spans;
+    let mut text = text.to_string();
+
+    // From the end, so that the earlier spans stay where they were
+    let same_body = match (
+        serde_json::from_str::<JsonValue>(&old.request.body),
+        serde_json::from_str::<JsonValue>(&new.body),
+    ) {
+        (Ok(old_body), Ok(new_body)) => old_body == new_body,
+        _ => old.request.body == new.body,
+    };
+    if !same_body {
+        let body = new.body.replace('\n', newline);
+        match (old.request.body.is_empty(), new.body.is_empty()) {
+            (true, true) => {}
+            (false, true) => text.replace_range(spans.headers.end..spans.body.end, ""),
+            (true, false) => text.insert_str(spans.body.end, &format!("{0}{0}{1}", newline, body)),
+            (false, false) => text.replace_range(spans.body.clone(), &body),
+        }
+    }
+
+    let mut old_headers = old.request.headers.clone();
+    let mut new_headers = new.headers.clone();
+    old_headers.sort();
+    new_headers.sort();
+    if old_headers != new_headers {
+        let headers = new
+            .headers
+            .iter()
+            .map(|(key, value)| format!("{}: {}", key, value))
+            .collect::<Vec<String>>()
+            .join(newline);
+        match (spans.headers.is_empty(), new.headers.is_empty()) {
+            (_, true) => text.replace_range(spans.request_line.end..spans.headers.end, ""),
+            (true, false) => text.insert_str(spans.headers.end, &format!("{}{}", newline, headers)),
+            (false, false) => text.replace_range(spans.headers.clone(), &headers),
+        }
+    }
+
+    if old.request.method != new.method || old.request.endpoint != new.endpoint {
+        let request_line = match spans.version {
+            Some(ref version) => format!("{} {} {}", new.method, new.endpoint, version),
+            None => format!("{} {}", new.method, new.endpoint),
+        };
+        text.replace_range(spans.request_line.clone(), &request_line);
+    }
+    text
+}
+
+/// The text of a file with a request added at the end
+fn append_request(text: &str, request: &RequestFile) -> String {
+    let newline = line_ending(text);
+    let mut text = text.to_string();
+    if !text.trim().is_empty() {
+        if !text.ends_with('\n') {
+            text.push_str(newline);
+        }
+        text.push_str(newline);
+        text.push_str("###");
+        text.push_str(newline);
+    }
+    text.push_str(&request.to_string().replace('\n', newline));
+    text
+}
+
+fn line_ending(text: &str) -> &'static str {
+    match text.contains("\r\n") {
+        true => "\r\n",
+        false => "\n",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::request_file::parse_request;
+
+    const FILE: &str = "@host = https://example.com
+@token = secret
+
+### List the items
+GET {{host}}/items HTTP/1.1
+Accept: application/json
+
+###
+# Creates an item
+# @name create-item
+POST {{host}}/items
+  ?draft=true
+  &notify=false
+Content-Type: application/json
+# Only while testing
+// X-Debug: 1
+Authorization: Bearer {{token}}
+
+{\"name\": \"tapir\"}
+
+###
+
+### Checks it's there
+HEAD {{host}}/items/1
+
+###
+DELETE {{host}}/items/1
+";
+
+    fn save(text: &str, index: Option<usize>, change: impl Fn(&mut RequestFile)) -> String {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("requests.http");
+        fs::write(&path, text).unwrap();
+        let mut request = match index {
+            Some(index) => {
+                parse_http_file(text)
+                    .unwrap()
+                    .requests
+                    .remove(index)
+                    .request
+            }
+            None => parse_request("GET https://example.com/new").unwrap(),
+        };
+        change(&mut request);
+        save_to_http_file(&path, index, request, &[]).unwrap();
+        fs::read_to_string(&path).unwrap()
+    }
+
+    #[test]
+    fn parses_requests() {
+        let http_file = parse_http_file(FILE).unwrap();
+        assert_eq!(
+            http_file.variables,
+            vec![
+                ("host".to_string(), "https://example.com".to_string()),
+                ("token".to_string(), "secret".to_string()),
+            ]
+        );
+        let requests = &http_file.requests;
+        assert_eq!(requests.len(), 3);
+
+        assert_eq!(requests[0].name.as_deref(), Some("List the items"));
+        assert_eq!(requests[0].request.method, HttpMethod::GET);
+        assert_eq!(requests[0].request.endpoint, "{{host}}/items");
+        assert_eq!(
+            requests[0].request.headers,
+            vec![("Accept".to_string(), "application/json".to_string())]
+        );
+        assert_eq!(requests[0].request.body, "");
+
+        assert_eq!(requests[1].name.as_deref(), Some("create-item"));
+        assert_eq!(requests[1].request.method, HttpMethod::POST);
+        assert_eq!(
+            requests[1].request.endpoint,
+            "{{host}}/items?draft=true&notify=false"
+        );
+        assert_eq!(
+            requests[1].request.headers,
+            vec![
+                ("Content-Type".to_string(), "application/json".to_string()),
+                ("Authorization".to_string(), "Bearer {{token}}".to_string()),
+            ]
+        );
+        assert_eq!(requests[1].request.body, "{\"name\": \"tapir\"}");
+
+        assert_eq!(requests[2].name, None);
+        assert_eq!(requests[2].request.method, HttpMethod::DELETE);
+    }
+
+    #[test]
+    fn skips_unsupported_methods() {
+        let http_file = parse_http_file(FILE).unwrap();
+        assert_eq!(
+            http_file.skipped,
+            vec!["Skipped HEAD {{host}}/items/1: Unsupported HTTP method \"HEAD\""]
+        );
+    }
+
+    #[test]
+    fn method_is_optional() {
+        let http_file = parse_http_file("https://example.com\n").unwrap();
+        assert_eq!(http_file.requests[0].request.method, HttpMethod::GET);
+        assert_eq!(
+            http_file.requests[0].request.endpoint,
+            "https://example.com"
+        );
+    }
+
+    #[test]
+    fn invalid_lines() {
+        assert!(parse_http_file("@ = value\nGET https://example.com").is_err());
+        assert!(parse_http_file("GET https://example.com\nnot a header").is_err());
+    }
+
+    #[test]
+    fn saving_an_unchanged_request_keeps_the_file() {
+        for index in 0..3 {
+            assert_eq!(save(FILE, Some(index), |_| {}), FILE);
+        }
+    }
+
+    #[test]
+    fn saving_replaces_only_what_changed() {
+        let text = save(FILE, Some(0), |request| {
+            request.endpoint = "{{host}}/items?page=2".to_string();
+        });
+        assert_eq!(
+            text,
+            FILE.replace(
+                "GET {{host}}/items HTTP/1.1",
+                "GET {{host}}/items?page=2 HTTP/1.1"
+            )
+        );
+
+        // Pretty printing the same JSON isn't a change
+        let text = save(FILE, Some(1), |request| {
+            request.body = "{\n  \"name\": \"tapir\"\n}".to_string();
+            request.headers.reverse();
+        });
+        assert_eq!(text, FILE);
+
+        let text = save(FILE, Some(1), |request| {
+            request.body = "{\"name\": \"capybara\"}".to_string();
+        });
+        assert_eq!(text, FILE.replace("\"tapir\"", "\"capybara\""));
+
+        let text = save(FILE, Some(2), |request| {
+            request.headers = vec![("Accept".to_string(), "text/plain".to_string())];
+            request.body = "gone".to_string();
+        });
+        assert!(text.ends_with("###\nDELETE {{host}}/items/1\nAccept: text/plain\n\ngone\n"));
+        assert!(text.starts_with(&FILE[..FILE.rfind("###").unwrap()]));
+    }
+
+    #[test]
+    fn saving_removes_headers_and_body() {
+        let text = save(FILE, Some(1), |request| {
+            request.headers.clear();
+            request.body.clear();
+        });
+        assert!(text
+            .contains("POST {{host}}/items\n  ?draft=true\n  &notify=false\n\n###\n\n### Checks"));
+    }
+
+    #[test]
+    fn saving_keeps_windows_line_endings() {
+        let file = "GET https://example.com\r\nAccept: */*\r\n";
+        let text = save(file, Some(0), |request| {
+            request.body = "a\nb".to_string();
+        });
+        assert_eq!(
+            text,
+            "GET https://example.com\r\nAccept: */*\r\n\r\na\r\nb\r\n"
+        );
+    }
+
+    #[test]
+    fn saving_a_new_request_appends_it() {
+        let text = save(FILE, None, |_| {});
+        assert_eq!(
+            text,
+            format!("{}\n###\nGET https://example.com/new\n", FILE)
+        );
+    }
+}
@@ -1,9 +1,12 @@
-use reqwest;
+use crate::request_error::RequestError;
+use serde::{Deserialize, Serialize};
 use serde_json::value::Value as JsonValue;
 use std::collections::HashMap;
 use std::fmt::Display;
+use std::time::{Duration, Instant, SystemTime};
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[allow(clippy::upper_case_acronyms)]
 pub enum HttpMethod {
     GET,
     POST,
@@ -24,14 +27,66 @@ impl Display for HttpMethod {
     }
 }
 
+impl std::str::FromStr for HttpMethod {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<HttpMethod, String> {
+        match name.to_uppercase().as_str() {
+            "GET" => Ok(HttpMethod::GET),
+            "POST" => Ok(HttpMethod::POST),
+            "PUT" => Ok(HttpMethod::PUT),
+            "PATCH" => Ok(HttpMethod::PATCH),
+            "DELETE" => Ok(HttpMethod::DELETE),
+            _ => Err(format!("Unsupported HTTP method \"{}\"", name)),
+        }
+    }
+}
+
+pub enum RequestBody {
+    Pairs(HashMap<String, JsonValue>), // Sent as a JSON object
+    Raw(String),
+}
+
+#[derive(Clone)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub http_version: String,
+    pub headers: Vec<(String, String)>, // In the order they were received
+    pub body: String,                   // Decoded with the charset of its Content-Type
+    pub body_bytes: Vec<u8>,            // Exactly as received, for exporting
+    pub request: SentRequest,
+    pub timings: Timings,
+}
+
+/// A request as it went out, after variables were filled in and the body was encoded
+#[derive(Clone)]
+pub struct SentRequest {
+    pub method: String,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Option<String>,
+}
+
+#[derive(Clone)]
+pub struct Timings {
+    pub started: SystemTime,
+    pub wait: Duration, // From sending the request until the response headers arrived
+    pub receive: Duration, // Reading the response body
+}
+
 // TODO: Accept query params
 pub async fn make_http_request(
     endpoint: String,
     method: HttpMethod,
     headers: HashMap<String, JsonValue>,
-    body: HashMap<String, JsonValue>,
-) -> Result<String, reqwest::Error> {
-    let client = reqwest::Client::new();
+    body: RequestBody,
+    timeout: Duration,
+) -> Result<HttpResponse, RequestError> {
+    let url = endpoint.clone();
+    let client = reqwest::Client::builder()
+        .timeout(timeout)
+        .build()
+        .map_err(|err| RequestError::new(&url, &err))?;
     let mut request = match method {
         HttpMethod::GET => client.get(endpoint),
         HttpMethod::POST => client.post(endpoint),
@@ -41,10 +96,100 @@ pub async fn make_http_request(
     };
 
     for (key, value) in headers.iter() {
-        request = request.header(key, value.to_string());
+        match value {
+            JsonValue::String(value) => request = request.header(key, value),
+            _ => request = request.header(key, value.to_string()),
+        }
     }
 
-    request = request.json(&body);
+    request = match body {
+        RequestBody::Pairs(pairs) => request.json(&pairs),
+        RequestBody::Raw(text) => {
+            let has_content_type = headers
+                .keys()
+                .any(|key| key.eq_ignore_ascii_case("content-type"));
+            if !has_content_type && serde_json::from_str::<JsonValue>(&text).is_ok() {
+                request = request.header("content-type", "application/json");
+            }
+            request.body(text)
+        }
+    };
+
+    let request = request
+        .build()
+        .map_err(|err| RequestError::new(&url, &err))?;
+    let sent_request = SentRequest {
+        method: request.method().to_string(),
+        url: request.url().to_string(),
+        headers: header_pairs(request.headers()),
+        body: request
+            .body()
+            .and_then(|body| body.as_bytes())
+            .map(|bytes| String::from_utf8_lossy(bytes).to_string()),
+    };
+
+    let started = SystemTime::now();
+    let start = Instant::now();
+    let response = client
+        .execute(request)
+        .await
+        .map_err(|err| RequestError::new(&url, &err))?;
+    let wait = start.elapsed();
+    let status = response.status().as_u16();
+    let http_version = format!("{:?}", response.version());
+    let headers = header_pairs(response.headers());
+    let body_bytes = response
+        .bytes()
+        .await
+        .map_err(|err| RequestError::new(&url, &err))?
+        .to_vec();
+    let body = decode_body(&headers, &body_bytes);
+
+    Ok(HttpResponse {
+        status,
+        http_version,
+        headers,
+        body,
+        body_bytes,
+        request: sent_request,
+        timings: Timings {
+            started,
+            wait,
+            receive: start.elapsed() - wait,
+        },
+    })
+}
+
+/// Decode a body as text the way reqwest does, with the charset of the Content-Type, or
+/// otherwise as UTF-8, replacing anything which can't be decoded
+fn decode_body(headers: &[(String, String)], bytes: &[u8]) -> String {
+    let charset = headers
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case("content-type"))
+        .and_then(|(_, value)| {
+            value.split(';').skip(1).find_map(|parameter| {
+                let (name, value) = parameter.split_once('=')?;
+                match name.trim().eq_ignore_ascii_case("charset") {
+                    true => Some(value.trim().trim_matches('"')),
+                    false => None,
+                }
+            })
+        })
+        .unwrap_or("utf-8");
+    let encoding =
+        encoding_rs::Encoding::for_label(charset.as_bytes()).unwrap_or(encoding_rs::UTF_8);
+    let (text, _, _) = encoding.decode(bytes);
+    text.into_owned()
+}
 
-    Ok(request.send().await?.text().await?)
+fn header_pairs(headers: &reqwest::header::HeaderMap) -> Vec<(String, String)> {
+    headers
+        .iter()
+        .map(|(key, value)| {
+            (
+                key.to_string(),
+                String::from_utf8_lossy(value.as_bytes()).to_string(),
+            )
+        })
+        .collect()
 }
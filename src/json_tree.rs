@@ -0,0 +1,268 @@
+use serde_json::value::Value as JsonValue;
+use std::collections::HashSet;
+
+pub struct JsonTreeState {
+    pub collapsed: HashSet<String>, // Paths of the collapsed nodes
+}
+
+pub enum TreeLineKind {
+    Scalar,
+    Open,
+    Collapsed,
+    Close,
+}
+
+pub struct TreeLine<'a> {
+    pub path: String,
+    pub depth: usize,
+    pub key: Option<String>,
+    pub value: &'a JsonValue,
+    pub kind: TreeLineKind,
+}
+
+impl JsonTreeState {
+    pub fn new() -> JsonTreeState {
+        JsonTreeState {
+            collapsed: HashSet::new(),
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.collapsed.clear();
+    }
+
+    pub fn toggle(&mut self, path: &str) {
+        if !self.collapsed.remove(path) {
+            self.collapsed.insert(path.to_string());
+        }
+    }
+
+    pub fn expand_all(&mut self) {
+        self.collapsed.clear();
+    }
+
+    pub fn collapse_all(&mut self, root: &JsonValue) {
+        self.collapsed = container_paths(root, "$".to_string());
+        // Keep the root open so that the top level keys remain visible
+        self.collapsed.remove("$");
+    }
+}
+
+/// Flatten a JSON value into the lines which are visible given the collapsed nodes
+pub fn flatten<'a>(root: &'a JsonValue, state: &JsonTreeState) -> Vec<TreeLine<'a>> {
+    let mut lines = Vec::<TreeLine>::new();
+    push_lines(root, "$".to_string(), None, 0, state, &mut lines);
+    lines
+}
+
+fn push_lines<'a>(
+    value: &'a JsonValue,
+    path: String,
+    key: Option<String>,
+    depth: usize,
+    state: &JsonTreeState,
+    lines: &mut Vec<TreeLine<'a>>,
+) {
+    let children: Vec<(String, String, &JsonValue)> = match value {
+        JsonValue::Object(map) => map
+            .iter()
+            .map(|(child_key, child)| (key_path(&path, child_key), child_key.clone(), child))
+            .collect(),
+        JsonValue::Array(items) => items
+            .iter()
+            .enumerate()
+            .map(|(index, child)| (format!("{}[{}]", path, index), index.to_string(), child))
+            .collect(),
+        _ => {
+            lines.push(TreeLine {
+                path,
+                depth,
+                key,
+                value,
+                kind: TreeLineKind::Scalar,
+            });
+            return;
+        }
+    };
+
+    if state.collapsed.contains(&path) || children.is_empty() {
+        lines.push(TreeLine {
+            path,
+            depth,
+            key,
+            value,
+            kind: TreeLineKind::Collapsed,
+        });
+        return;
+    }
+
+    lines.push(TreeLine {
+        path: path.clone(),
+        depth,
+        key,
+        value,
+        kind: TreeLineKind::Open,
+    });
+    for (child_path, child_key, child) in children {
+        push_lines(child, child_path, Some(child_key), depth + 1, state, lines);
+    }
+    lines.push(TreeLine {
+        path,
+        depth,
+        key: None,
+        value,
+        kind: TreeLineKind::Close,
+    });
+}
+
+/// The path of an object's child, with the key quoted so that keys containing dots or
+/// brackets can't be mistaken for nested keys
+pub fn key_path(path: &str, key: &str) -> String {
+    format!("{}[{}]", path, JsonValue::String(key.to_string()))
+}
+
+fn container_paths(value: &JsonValue, path: String) -> HashSet<String> {
+    let mut paths = HashSet::new();
+    match value {
+        JsonValue::Object(map) => {
+            for (child_key, child) in map.iter() {
+                paths.extend(container_paths(child, key_path(&path, child_key)));
+            }
+        }
+        JsonValue::Array(items) => {
+            for (index, child) in items.iter().enumerate() {
+                paths.extend(container_paths(child, format!("{}[{}]", path, index)));
+            }
+        }
+        _ => return paths,
+    }
+    paths.insert(path);
+    paths
+}
+
+/// A short description of a container's size, e.g. `3 keys` or `5 items`
+pub fn describe_size(value: &JsonValue) -> String {
+    match value {
+        JsonValue::Object(map) => match map.len() {
+            1 => "1 key".to_string(),
+            length => format!("{} keys", length),
+        },
+        JsonValue::Array(items) => match items.len() {
+            1 => "1 item".to_string(),
+            length => format!("{} items", length),
+        },
+        _ => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn keys_with_dots_have_their_own_paths() {
+        let root = json!({"a.b": [1], "a": {"b": [2]}});
+        let mut state = JsonTreeState::new();
+        let paths: Vec<String> = flatten(&root, &state)
+            .into_iter()
+            .map(|line| line.path)
+            .collect();
+        assert!(paths.contains(&r#"$["a.b"]"#.to_string()));
+        assert!(paths.contains(&r#"$["a"]["b"]"#.to_string()));
+
+        state.toggle(r#"$["a.b"]"#);
+        let lines = flatten(&root, &state);
+        let collapsed: Vec<&str> = lines
+            .iter()
+            .filter(|line| matches!(line.kind, TreeLineKind::Collapsed))
+            .map(|line| line.path.as_str())
+            .collect();
+        assert_eq!(collapsed, vec![r#"$["a.b"]"#]);
+    }
+
+    fn summary(lines: &[TreeLine]) -> Vec<(String, usize, Option<String>, &'static str)> {
+        lines
+            .iter()
+            .map(|line| {
+                let kind = match line.kind {
+                    TreeLineKind::Scalar => "scalar",
+                    TreeLineKind::Open => "open",
+                    TreeLineKind::Collapsed => "collapsed",
+                    TreeLineKind::Close => "close",
+                };
+                (line.path.clone(), line.depth, line.key.clone(), kind)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn flattens_an_array_of_objects() {
+        let root = json!([{"id": 1}, []]);
+        let state = JsonTreeState::new();
+        let line = |path: &str, depth, key: Option<&str>, kind| {
+            (path.to_string(), depth, key.map(str::to_string), kind)
+        };
+        assert_eq!(
+            summary(&flatten(&root, &state)),
+            vec![
+                line("$", 0, None, "open"),
+                line("$[0]", 1, Some("0"), "open"),
+                line(r#"$[0]["id"]"#, 2, Some("id"), "scalar"),
+                line("$[0]", 1, None, "close"),
+                // Empty containers have nothing to open
+                line("$[1]", 1, Some("1"), "collapsed"),
+                line("$", 0, None, "close"),
+            ]
+        );
+    }
+
+    #[test]
+    fn flattens_a_scalar() {
+        let root = json!("just a string");
+        let lines = flatten(&root, &JsonTreeState::new());
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0].path, "$");
+        assert!(matches!(lines[0].kind, TreeLineKind::Scalar));
+        assert_eq!(lines[0].value, &root);
+    }
+
+    #[test]
+    fn collapses_everything_but_the_root() {
+        let root = json!({"users": [{"name": "a", "tags": ["x"]}], "count": 1});
+        let mut state = JsonTreeState::new();
+        state.collapse_all(&root);
+        let expected: HashSet<String> = [
+            r#"$["users"]"#,
+            r#"$["users"][0]"#,
+            r#"$["users"][0]["tags"]"#,
+        ]
+        .into_iter()
+        .map(str::to_string)
+        .collect();
+        assert_eq!(state.collapsed, expected);
+
+        let lines = flatten(&root, &state);
+        let paths: Vec<&str> = lines.iter().map(|line| line.path.as_str()).collect();
+        assert_eq!(paths, vec!["$", r#"$["count"]"#, r#"$["users"]"#, "$"]);
+
+        state.toggle(r#"$["users"]"#);
+        assert_eq!(flatten(&root, &state).len(), 6);
+        state.expand_all();
+        assert_eq!(flatten(&root, &state).len(), 11);
+    }
+
+    #[test]
+    fn quotes_keys_in_paths() {
+        assert_eq!(key_path("$", "a.b"), r#"$["a.b"]"#);
+        assert_eq!(key_path("$[0]", r#"say "hi""#), r#"$[0]["say \"hi\""]"#);
+        assert_eq!(key_path("$", "[0]"), r#"$["[0]"]"#);
+    }
+
+    #[test]
+    fn describes_sizes() {
+        assert_eq!(describe_size(&json!({"a": 1})), "1 key");
+        assert_eq!(describe_size(&json!([1, 2])), "2 items");
+        assert_eq!(describe_size(&json!(3)), "");
+    }
+}
@@ -0,0 +1,1117 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::Deserialize;
+use serde_json::value::Value as JsonValue;
+
+use crate::app::CurrentScreen;
+use crate::config::{KeyChords, KeybindingsConfig};
+
+#[derive(Clone, Copy, PartialEq, Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeymapContext {
+    Main,
+    EndpointInput,
+    PairInput,
+    FilterInput,
+    NameInput,
+    BodyEditor,
+    Pager,
+    PagerSearch,
+    Diff,
+    AlertLog,
+    Alert, // An error dialog, shown over any screen
+    ExitPrompt,
+    OverwritePrompt,
+    HarEntries,
+    GraphqlEditor,
+    SchemaBrowser,
+    WebSocket,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    // Main screen
+    NextSection,
+    PreviousSection,
+    NextItem,
+    PreviousItem,
+    ScrollLeft,
+    ScrollRight,
+    ToggleWrap,
+    EditEndpoint,
+    AddItem,
+    EditItem,
+    DeleteItem,
+    EditRawBody,
+    EditRequestExternally,
+    ViewExternally,
+    NextMethod,
+    PreviousMethod,
+    FilterResponse,
+    CopyValue,
+    CopySection,
+    CopyEndpoint,
+    CopyAsCurl,
+    ToggleNode,
+    ExpandAll,
+    CollapseAll,
+    ViewResponse,
+    PinBaseline,
+    CompareResponses,
+    NewTab,
+    CloseTab,
+    NextTab,
+    PreviousTab,
+    ToggleLayout,
+    SwitchPane,
+    GrowPane,
+    ShrinkPane,
+    ToggleMouseCapture,
+    ShowAlerts,
+    SaveRequest,
+    ExportHar,
+    ShowHarEntries,
+    ToggleGraphql,
+    BrowseSchema,
+    OpenWebSocket,
+    SendRequest,
+    ToggleHelp,
+    Quit,
+    QuitAndPrint,
+    // Text inputs
+    Submit,
+    Cancel,
+    DeleteCharacter,
+    SwitchField,
+    PreviousFilter,
+    NextFilter,
+    CursorLeft,
+    CursorRight,
+    CursorToStart,
+    CursorToEnd,
+    WordLeft,
+    WordRight,
+    DeleteForward,
+    DeleteWord,
+    Undo,
+    CursorUp,
+    CursorDown,
+    EditExternally,
+    Complete,
+    // Pager and diff
+    ScrollDown,
+    ScrollUp,
+    PageDown,
+    PageUp,
+    ScrollToTop,
+    ScrollToBottom,
+    Search,
+    NextMatch,
+    PreviousMatch,
+    TogglePretty,
+    SwitchContent,
+    OlderBaseline,
+    NewerBaseline,
+    Close,
+    // Exit prompt
+    Confirm,
+    Decline,
+    // HAR entries
+    ReplayEntry,
+    // Schema browser
+    OpenType,
+    GoBack,
+    FetchSchema,
+    // WebSocket
+    SwitchMessageKind,
+    SendPing,
+    CloseConnection,
+    Reconnect,
+}
+
+impl Action {
+    pub fn description(&self) -> &'static str {
+        match self {
+            Action::NextSection => "Next section",
+            Action::PreviousSection => "Previous section",
+            Action::NextItem => "Select the next item",
+            Action::PreviousItem => "Select the previous item",
+            Action::ScrollLeft => "Scroll left",
+            Action::ScrollRight => "Scroll right",
+            Action::ToggleWrap => "Toggle wrapping of long values",
+            Action::EditEndpoint => "Edit the endpoint",
+            Action::AddItem => "Add an item",
+            Action::EditItem => "Edit the selected item",
+            Action::DeleteItem => "Delete the selected item",
+            Action::EditRawBody => "Edit the request body as raw text",
+            Action::EditRequestExternally => "Edit the request in $EDITOR",
+            Action::ViewExternally => "View the response body in $PAGER",
+            Action::NextMethod => "Next HTTP method",
+            Action::PreviousMethod => "Previous HTTP method",
+            Action::FilterResponse => "Filter the response body",
+            Action::CopyValue => "Copy the selected value",
+            Action::CopySection => "Copy the whole body or all headers",
+            Action::CopyEndpoint => "Copy the endpoint",
+            Action::CopyAsCurl => "Copy the request as a curl command",
+            Action::ToggleNode => "Expand or collapse the selected node",
+            Action::ExpandAll => "Expand all nodes",
+            Action::CollapseAll => "Collapse all nodes",
+            Action::ViewResponse => "View the raw response",
+            Action::PinBaseline => "Pin the response as the diff baseline",
+            Action::CompareResponses => "Compare with an earlier response",
+            Action::NewTab => "Open a new tab",
+            Action::CloseTab => "Close the tab",
+            Action::NextTab => "Next tab",
+            Action::PreviousTab => "Previous tab",
+            Action::ToggleLayout => "Toggle the split layout",
+            Action::SwitchPane => "Focus the other pane",
+            Action::GrowPane => "Grow the request pane",
+            Action::ShrinkPane => "Shrink the request pane",
+            Action::ToggleMouseCapture => {
+                "Release or capture the mouse (release it to select text)"
+            }
+            Action::ShowAlerts => "Show past alerts",
+            Action::SaveRequest => "Save the request by name",
+            Action::ExportHar => "Export the history as a HAR file",
+            Action::ShowHarEntries => "Choose an entry of the HAR file to replay",
+            Action::ToggleGraphql => "Switch between a plain body and a GraphQL query",
+            Action::BrowseSchema => "Browse the GraphQL schema",
+            Action::OpenWebSocket => "Connect to the endpoint as a WebSocket",
+            Action::SendRequest => "Send the request",
+            Action::ToggleHelp => "Show or hide this help",
+            Action::Quit => "Quit",
+            Action::QuitAndPrint => "Quit and print the request and response as JSON",
+            Action::Submit => "Submit",
+            Action::Cancel => "Cancel",
+            Action::DeleteCharacter => "Delete the character before the cursor",
+            Action::SwitchField => "Switch to the other input field",
+            Action::PreviousFilter => "Previous filter from history",
+            Action::NextFilter => "Next filter from history",
+            Action::CursorLeft => "Move the cursor left",
+            Action::CursorRight => "Move the cursor right",
+            Action::CursorToStart => "Move the cursor to the start",
+            Action::CursorToEnd => "Move the cursor to the end",
+            Action::WordLeft => "Move the cursor back a word",
+            Action::WordRight => "Move the cursor forward a word",
+            Action::DeleteForward => "Delete the character under the cursor",
+            Action::DeleteWord => "Delete the word before the cursor",
+            Action::Undo => "Undo",
+            Action::CursorUp => "Move the cursor up",
+            Action::CursorDown => "Move the cursor down",
+            Action::EditExternally => "Edit in $EDITOR",
+            Action::Complete => "Complete the field from the GraphQL schema",
+            Action::ScrollDown => "Scroll down",
+            Action::ScrollUp => "Scroll up",
+            Action::PageDown => "Scroll down a page",
+            Action::PageUp => "Scroll up a page",
+            Action::ScrollToTop => "Go to the top",
+            Action::ScrollToBottom => "Go to the bottom",
+            Action::Search => "Search",
+            Action::NextMatch => "Next match",
+            Action::PreviousMatch => "Previous match",
+            Action::TogglePretty => "Toggle pretty printing",
+            Action::SwitchContent => "Switch between body and headers",
+            Action::OlderBaseline => "Compare with an older response",
+            Action::NewerBaseline => "Compare with a newer response",
+            Action::Close => "Close",
+            Action::Confirm => "Yes",
+            Action::Decline => "No",
+            Action::ReplayEntry => "Open the entry in a new tab and send it",
+            Action::OpenType => "Show the fields of the selected type",
+            Action::GoBack => "Go back to the previous type",
+            Action::FetchSchema => "Fetch the schema again",
+            Action::SwitchMessageKind => "Switch between text, JSON and binary messages",
+            Action::SendPing => "Send a ping",
+            Action::CloseConnection => "Close the connection with a code and reason",
+            Action::Reconnect => "Connect again",
+        }
+    }
+}
+
+pub struct KeyBinding {
+    pub context: KeymapContext,
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+    pub action: Action,
+}
+
+/// The single definition of which keys trigger which actions on each screen
+pub struct Keymap {
+    pub bindings: Vec<KeyBinding>,
+}
+
+impl Keymap {
+    pub fn new() -> Keymap {
+        use Action::*;
+        use KeymapContext::*;
+
+        let bindings: Vec<(KeymapContext, KeyCode, Action)> = vec![
+            (Main, KeyCode::Char(']'), NextSection),
+            (Main, KeyCode::Char('['), PreviousSection),
+            (Main, KeyCode::Char('j'), NextItem),
+            (Main, KeyCode::Char('k'), PreviousItem),
+            (Main, KeyCode::Char('h'), ScrollLeft),
+            (Main, KeyCode::Char('l'), ScrollRight),
+            (Main, KeyCode::Char('w'), ToggleWrap),
+            (Main, KeyCode::Tab, EditEndpoint),
+            (Main, KeyCode::Char('a'), AddItem),
+            (Main, KeyCode::Char('e'), EditItem),
+            (Main, KeyCode::Char('d'), DeleteItem),
+            (Main, KeyCode::Char('r'), EditRawBody),
+            (Main, KeyCode::Char('E'), EditRequestExternally),
+            (Main, KeyCode::Char('V'), ViewExternally),
+            (Main, KeyCode::Char('m'), NextMethod),
+            (Main, KeyCode::Char('n'), PreviousMethod),
+            (Main, KeyCode::Char('/'), FilterResponse),
+            (Main, KeyCode::Char('y'), CopyValue),
+            (Main, KeyCode::Char('Y'), CopySection),
+            (Main, KeyCode::Char('u'), CopyEndpoint),
+            (Main, KeyCode::Char('C'), CopyAsCurl),
+            (Main, KeyCode::Char(' '), ToggleNode),
+            (Main, KeyCode::Char('+'), ExpandAll),
+            (Main, KeyCode::Char('-'), CollapseAll),
+            (Main, KeyCode::Char('v'), ViewResponse),
+            (Main, KeyCode::Char('p'), PinBaseline),
+            (Main, KeyCode::Char('c'), CompareResponses),
+            (Main, KeyCode::Char('t'), NewTab),
+            (Main, KeyCode::Char('x'), CloseTab),
+            (Main, KeyCode::Char('}'), NextTab),
+            (Main, KeyCode::Char('{'), PreviousTab),
+            (Main, KeyCode::Char('s'), ToggleLayout),
+            (Main, KeyCode::Char('o'), SwitchPane),
+            (Main, KeyCode::Char('>'), GrowPane),
+            (Main, KeyCode::Char('<'), ShrinkPane),
+            (Main, KeyCode::Char('M'), ToggleMouseCapture),
+            (Main, KeyCode::Char('!'), ShowAlerts),
+            (Main, KeyCode::Char('S'), SaveRequest),
+            (Main, KeyCode::Char('H'), ExportHar),
+            (Main, KeyCode::Char('I'), ShowHarEntries),
+            (Main, KeyCode::Char('G'), ToggleGraphql),
+            (Main, KeyCode::Char('B'), BrowseSchema),
+            (Main, KeyCode::Char('W'), OpenWebSocket),
+            (Main, KeyCode::Enter, SendRequest),
+            (Main, KeyCode::Char('?'), ToggleHelp),
+            (Main, KeyCode::Char('q'), Quit),
+            (Main, KeyCode::Char('Q'), QuitAndPrint),
+            (EndpointInput, KeyCode::Enter, Submit),
+            (EndpointInput, KeyCode::Esc, Cancel),
+            (EndpointInput, KeyCode::Backspace, DeleteCharacter),
+            (EndpointInput, KeyCode::F(1), ToggleHelp),
+            (NameInput, KeyCode::Enter, Submit),
+            (NameInput, KeyCode::Esc, Cancel),
+            (NameInput, KeyCode::Backspace, DeleteCharacter),
+            (NameInput, KeyCode::F(1), ToggleHelp),
+            (PairInput, KeyCode::Enter, Submit),
+            (PairInput, KeyCode::Esc, Cancel),
+            (PairInput, KeyCode::Tab, SwitchField),
+            (PairInput, KeyCode::Backspace, DeleteCharacter),
+            (PairInput, KeyCode::F(1), ToggleHelp),
+            (FilterInput, KeyCode::Enter, Submit),
+            (FilterInput, KeyCode::Esc, Cancel),
+            (FilterInput, KeyCode::Up, PreviousFilter),
+            (FilterInput, KeyCode::Down, NextFilter),
+            (FilterInput, KeyCode::Backspace, DeleteCharacter),
+            (FilterInput, KeyCode::F(1), ToggleHelp),
+            (BodyEditor, KeyCode::Esc, Cancel),
+            (BodyEditor, KeyCode::Backspace, DeleteCharacter),
+            (BodyEditor, KeyCode::Up, CursorUp),
+            (BodyEditor, KeyCode::Down, CursorDown),
+            (BodyEditor, KeyCode::PageUp, PageUp),
+            (BodyEditor, KeyCode::PageDown, PageDown),
+            (BodyEditor, KeyCode::F(1), ToggleHelp),
+            (GraphqlEditor, KeyCode::Esc, Cancel),
+            (GraphqlEditor, KeyCode::Tab, SwitchField),
+            (GraphqlEditor, KeyCode::Backspace, DeleteCharacter),
+            (GraphqlEditor, KeyCode::Up, CursorUp),
+            (GraphqlEditor, KeyCode::Down, CursorDown),
+            (GraphqlEditor, KeyCode::PageUp, PageUp),
+            (GraphqlEditor, KeyCode::PageDown, PageDown),
+            (GraphqlEditor, KeyCode::F(1), ToggleHelp),
+            (Pager, KeyCode::Char('j'), ScrollDown),
+            (Pager, KeyCode::Down, ScrollDown),
+            (Pager, KeyCode::Char('k'), ScrollUp),
+            (Pager, KeyCode::Up, ScrollUp),
+            (Pager, KeyCode::Char(' '), PageDown),
+            (Pager, KeyCode::PageDown, PageDown),
+            (Pager, KeyCode::PageUp, PageUp),
+            (Pager, KeyCode::Char('g'), ScrollToTop),
+            (Pager, KeyCode::Home, ScrollToTop),
+            (Pager, KeyCode::Char('G'), ScrollToBottom),
+            (Pager, KeyCode::End, ScrollToBottom),
+            (Pager, KeyCode::Char('/'), Search),
+            (Pager, KeyCode::Char('n'), NextMatch),
+            (Pager, KeyCode::Char('N'), PreviousMatch),
+            (Pager, KeyCode::Char('w'), ToggleWrap),
+            (Pager, KeyCode::Char('p'), TogglePretty),
+            (Pager, KeyCode::Tab, SwitchContent),
+            (Pager, KeyCode::Char('V'), ViewExternally),
+            (Pager, KeyCode::Char('?'), ToggleHelp),
+            (Pager, KeyCode::Char('q'), Close),
+            (Pager, KeyCode::Esc, Close),
+            (PagerSearch, KeyCode::Enter, Submit),
+            (PagerSearch, KeyCode::Esc, Cancel),
+            (PagerSearch, KeyCode::Backspace, DeleteCharacter),
+            (PagerSearch, KeyCode::F(1), ToggleHelp),
+            (Diff, KeyCode::Char('j'), ScrollDown),
+            (Diff, KeyCode::Down, ScrollDown),
+            (Diff, KeyCode::Char('k'), ScrollUp),
+            (Diff, KeyCode::Up, ScrollUp),
+            (Diff, KeyCode::Char('['), OlderBaseline),
+            (Diff, KeyCode::Char(']'), NewerBaseline),
+            (Diff, KeyCode::Char('p'), PinBaseline),
+            (Diff, KeyCode::Char('?'), ToggleHelp),
+            (Diff, KeyCode::Char('q'), Close),
+            (Diff, KeyCode::Esc, Close),
+            (AlertLog, KeyCode::Char('j'), ScrollDown),
+            (AlertLog, KeyCode::Down, ScrollDown),
+            (AlertLog, KeyCode::Char('k'), ScrollUp),
+            (AlertLog, KeyCode::Up, ScrollUp),
+            (AlertLog, KeyCode::Char('g'), ScrollToTop),
+            (AlertLog, KeyCode::Home, ScrollToTop),
+            (AlertLog, KeyCode::Char('G'), ScrollToBottom),
+            (AlertLog, KeyCode::End, ScrollToBottom),
+            (AlertLog, KeyCode::Char('?'), ToggleHelp),
+            (AlertLog, KeyCode::Char('q'), Close),
+            (AlertLog, KeyCode::Esc, Close),
+            (Alert, KeyCode::Enter, Close),
+            (Alert, KeyCode::Esc, Close),
+            (Alert, KeyCode::Char('q'), Close),
+            (ExitPrompt, KeyCode::Char('y'), Confirm),
+            (ExitPrompt, KeyCode::Char('n'), Decline),
+            (ExitPrompt, KeyCode::Char('q'), Decline),
+            (ExitPrompt, KeyCode::Esc, Cancel),
+            (OverwritePrompt, KeyCode::Char('y'), Confirm),
+            (OverwritePrompt, KeyCode::Char('n'), Decline),
+            (OverwritePrompt, KeyCode::Esc, Cancel),
+            (HarEntries, KeyCode::Char('j'), NextItem),
+            (HarEntries, KeyCode::Down, NextItem),
+            (HarEntries, KeyCode::Char('k'), PreviousItem),
+            (HarEntries, KeyCode::Up, PreviousItem),
+            (HarEntries, KeyCode::Char('g'), ScrollToTop),
+            (HarEntries, KeyCode::Home, ScrollToTop),
+            (HarEntries, KeyCode::Char('G'), ScrollToBottom),
+            (HarEntries, KeyCode::End, ScrollToBottom),
+            (HarEntries, KeyCode::Enter, ReplayEntry),
+            (HarEntries, KeyCode::Char('?'), ToggleHelp),
+            (HarEntries, KeyCode::Char('q'), Close),
+            (HarEntries, KeyCode::Esc, Close),
+            (SchemaBrowser, KeyCode::Char('j'), NextItem),
+            (SchemaBrowser, KeyCode::Down, NextItem),
+            (SchemaBrowser, KeyCode::Char('k'), PreviousItem),
+            (SchemaBrowser, KeyCode::Up, PreviousItem),
+            (SchemaBrowser, KeyCode::Char('g'), ScrollToTop),
+            (SchemaBrowser, KeyCode::Home, ScrollToTop),
+            (SchemaBrowser, KeyCode::Char('G'), ScrollToBottom),
+            (SchemaBrowser, KeyCode::End, ScrollToBottom),
+            (SchemaBrowser, KeyCode::Enter, OpenType),
+            (SchemaBrowser, KeyCode::Char('l'), OpenType),
+            (SchemaBrowser, KeyCode::Right, OpenType),
+            (SchemaBrowser, KeyCode::Char('h'), GoBack),
+            (SchemaBrowser, KeyCode::Left, GoBack),
+            (SchemaBrowser, KeyCode::Backspace, GoBack),
+            (SchemaBrowser, KeyCode::Char('r'), FetchSchema),
+            (SchemaBrowser, KeyCode::Char('?'), ToggleHelp),
+            (SchemaBrowser, KeyCode::Char('q'), Close),
+            (SchemaBrowser, KeyCode::Esc, Close),
+            (WebSocket, KeyCode::Enter, Submit),
+            (WebSocket, KeyCode::Esc, Cancel),
+            (WebSocket, KeyCode::Tab, SwitchMessageKind),
+            (WebSocket, KeyCode::Backspace, DeleteCharacter),
+            (WebSocket, KeyCode::Up, ScrollUp),
+            (WebSocket, KeyCode::Down, ScrollDown),
+            (WebSocket, KeyCode::PageUp, PageUp),
+            (WebSocket, KeyCode::PageDown, PageDown),
+            (WebSocket, KeyCode::F(1), ToggleHelp),
+        ];
+
+        // Line editing keys are shared by every text input
+        let line_editing_bindings: Vec<(KeyCode, KeyModifiers, Action)> = vec![
+            (KeyCode::Left, KeyModifiers::NONE, CursorLeft),
+            (KeyCode::Right, KeyModifiers::NONE, CursorRight),
+            (KeyCode::Home, KeyModifiers::NONE, CursorToStart),
+            (KeyCode::Char('a'), KeyModifiers::CONTROL, CursorToStart),
+            (KeyCode::End, KeyModifiers::NONE, CursorToEnd),
+            (KeyCode::Char('e'), KeyModifiers::CONTROL, CursorToEnd),
+            (KeyCode::Left, KeyModifiers::CONTROL, WordLeft),
+            (KeyCode::Char('b'), KeyModifiers::ALT, WordLeft),
+            (KeyCode::Right, KeyModifiers::CONTROL, WordRight),
+            (KeyCode::Char('f'), KeyModifiers::ALT, WordRight),
+            (KeyCode::Delete, KeyModifiers::NONE, DeleteForward),
+            (KeyCode::Char('w'), KeyModifiers::CONTROL, DeleteWord),
+            (KeyCode::Backspace, KeyModifiers::ALT, DeleteWord),
+            (KeyCode::Char('z'), KeyModifiers::CONTROL, Undo),
+        ];
+
+        let mut keymap = Keymap {
+            bindings: bindings
+                .into_iter()
+                .map(|(context, code, action)| KeyBinding {
+                    context,
+                    code,
+                    modifiers: KeyModifiers::NONE,
+                    action,
+                })
+                .collect(),
+        };
+        for context in [
+            EndpointInput,
+            PairInput,
+            FilterInput,
+            NameInput,
+            BodyEditor,
+            GraphqlEditor,
+            PagerSearch,
+            WebSocket,
+        ] {
+            for (code, modifiers, action) in line_editing_bindings.iter() {
+                keymap.bindings.push(KeyBinding {
+                    context,
+                    code: *code,
+                    modifiers: *modifiers,
+                    action: *action,
+                });
+            }
+        }
+        for (context, code, action) in [
+            (BodyEditor, KeyCode::Char('s'), Submit),
+            (BodyEditor, KeyCode::Char('o'), EditExternally),
+            (GraphqlEditor, KeyCode::Char('s'), Submit),
+            (GraphqlEditor, KeyCode::Char('o'), EditExternally),
+            (GraphqlEditor, KeyCode::Char(' '), Complete),
+            (WebSocket, KeyCode::Char('p'), SendPing),
+            (WebSocket, KeyCode::Char('d'), CloseConnection),
+            (WebSocket, KeyCode::Char('r'), Reconnect),
+        ] {
+            keymap.bindings.push(KeyBinding {
+                context,
+                code,
+                modifiers: KeyModifiers::CONTROL,
+                action,
+            });
+        }
+        keymap
+    }
+
+    /// Build the keymap from a preset and the user's bindings, along with any problems found
+    pub fn from_config(config: &KeybindingsConfig) -> (Keymap, Vec<String>) {
+        let mut keymap = Keymap::new();
+        let mut problems = Vec::<String>::new();
+
+        match config.preset.as_deref() {
+            None | Some("vim") => {}
+            Some("arrows") => keymap.apply_arrows_preset(),
+            Some(preset) => problems.push(format!(
+                "Unknown keybinding preset \"{}\", expected \"vim\" or \"arrows\"",
+                preset
+            )),
+        }
+
+        for (context_name, actions) in config.bindings.iter() {
+            let context: KeymapContext =
+                match serde_json::from_value(JsonValue::String(context_name.clone())) {
+                    Ok(context) => context,
+                    Err(_) => {
+                        problems.push(format!("Unknown keybinding screen \"{}\"", context_name));
+                        continue;
+                    }
+                };
+            for (action_name, chords) in actions.iter() {
+                let action: Action =
+                    match serde_json::from_value(JsonValue::String(action_name.clone())) {
+                        Ok(action) => action,
+                        Err(_) => {
+                            problems.push(format!(
+                                "Unknown action \"{}\" for screen \"{}\"",
+                                action_name, context_name
+                            ));
+                            continue;
+                        }
+                    };
+                let chords = match chords {
+                    KeyChords::One(chord) => vec![chord.clone()],
+                    KeyChords::Many(chords) => chords.clone(),
+                };
+                let mut keys = Vec::<(KeyCode, KeyModifiers)>::new();
+                for chord in chords.iter() {
+                    match parse_key_chord(chord) {
+                        Ok(key) => keys.push(key),
+                        Err(err) => problems.push(format!(
+                            "Invalid key for {}.{}: {}",
+                            context_name, action_name, err
+                        )),
+                    }
+                }
+                // Leave the defaults in place rather than leaving the action without any keys
+                if keys.is_empty() {
+                    problems.push(format!(
+                        "No valid keys for {}.{}, keeping its default keys",
+                        context_name, action_name
+                    ));
+                    continue;
+                }
+                keymap.rebind(context, action, keys);
+            }
+        }
+
+        problems.extend(keymap.conflicts());
+        (keymap, problems)
+    }
+
+    /// Replace the keys bound to an action
+    fn rebind(
+        &mut self,
+        context: KeymapContext,
+        action: Action,
+        keys: Vec<(KeyCode, KeyModifiers)>,
+    ) {
+        self.bindings
+            .retain(|binding| binding.context != context || binding.action != action);
+        for (code, modifiers) in keys {
+            self.bindings.push(KeyBinding {
+                context,
+                code,
+                modifiers,
+                action,
+            });
+        }
+    }
+
+    /// Use the arrow keys for navigation rather than h, j, k and l
+    fn apply_arrows_preset(&mut self) {
+        use Action::*;
+        use KeymapContext::*;
+
+        let arrow_bindings = [
+            (Main, NextItem, KeyCode::Down, KeyModifiers::NONE),
+            (Main, PreviousItem, KeyCode::Up, KeyModifiers::NONE),
+            (Main, ScrollLeft, KeyCode::Left, KeyModifiers::NONE),
+            (Main, ScrollRight, KeyCode::Right, KeyModifiers::NONE),
+            (Main, NextSection, KeyCode::PageDown, KeyModifiers::NONE),
+            (Main, PreviousSection, KeyCode::PageUp, KeyModifiers::NONE),
+            (Main, NextTab, KeyCode::Right, KeyModifiers::ALT),
+            (Main, PreviousTab, KeyCode::Left, KeyModifiers::ALT),
+            (Pager, ScrollDown, KeyCode::Down, KeyModifiers::NONE),
+            (Pager, ScrollUp, KeyCode::Up, KeyModifiers::NONE),
+            (Pager, ScrollToTop, KeyCode::Home, KeyModifiers::NONE),
+            (Pager, ScrollToBottom, KeyCode::End, KeyModifiers::NONE),
+            (Diff, ScrollDown, KeyCode::Down, KeyModifiers::NONE),
+            (Diff, ScrollUp, KeyCode::Up, KeyModifiers::NONE),
+            (Diff, OlderBaseline, KeyCode::Left, KeyModifiers::NONE),
+            (Diff, NewerBaseline, KeyCode::Right, KeyModifiers::NONE),
+        ];
+        for (context, action, code, modifiers) in arrow_bindings {
+            self.rebind(context, action, vec![(code, modifiers)]);
+        }
+    }
+
+    /// Describe every key which is bound to more than one action on the same screen
+    fn conflicts(&self) -> Vec<String> {
+        let mut conflicts = Vec::<String>::new();
+        for (index, binding) in self.bindings.iter().enumerate() {
+            for other in self.bindings[index + 1..].iter() {
+                if binding.context == other.context
+                    && binding.code == other.code
+                    && binding.modifiers == other.modifiers
+                    && binding.action != other.action
+                {
+                    conflicts.push(format!(
+                        "{} is bound to both {:?} and {:?} on the {:?} screen",
+                        describe_key(binding.code, binding.modifiers),
+                        binding.action,
+                        other.action,
+                        binding.context
+                    ));
+                }
+            }
+        }
+        conflicts
+    }
+
+    pub fn action_for(&self, context: KeymapContext, key: &KeyEvent) -> Option<Action> {
+        // Shift is implied by upper case characters, so only compare the other modifiers
+        let modifiers = match key.code {
+            KeyCode::Char(_) => key.modifiers - KeyModifiers::SHIFT,
+            _ => key.modifiers,
+        };
+        self.bindings
+            .iter()
+            .find(|binding| {
+                binding.context == context
+                    && binding.code == key.code
+                    && binding.modifiers == modifiers
+            })
+            .map(|binding| binding.action)
+    }
+
+    pub fn bindings_for(&self, context: KeymapContext) -> Vec<&KeyBinding> {
+        self.bindings
+            .iter()
+            .filter(|binding| binding.context == context)
+            .collect()
+    }
+
+    /// The first key bound to an action, formatted for display
+    pub fn key_for(&self, context: KeymapContext, action: Action) -> Option<String> {
+        self.bindings
+            .iter()
+            .find(|binding| binding.context == context && binding.action == action)
+            .map(|binding| describe_key(binding.code, binding.modifiers))
+    }
+
+    /// A one line summary of the most important keys in a context, for the footer
+    pub fn hint(&self, context: KeymapContext) -> String {
+        footer_actions(context)
+            .iter()
+            .filter_map(|action| {
+                self.key_for(context, *action)
+                    .map(|key| format!("[{}] {}", key, short_description(*action)))
+            })
+            .collect::<Vec<String>>()
+            .join(" / ")
+    }
+}
+
+pub fn context_for(screen: &CurrentScreen) -> KeymapContext {
+    match screen {
+        CurrentScreen::Main => KeymapContext::Main,
+        CurrentScreen::EndpointInput(_) => KeymapContext::EndpointInput,
+        CurrentScreen::PairInput(_) => KeymapContext::PairInput,
+        CurrentScreen::FilterInput(_) => KeymapContext::FilterInput,
+        CurrentScreen::BodyEditor(_) => KeymapContext::BodyEditor,
+        CurrentScreen::GraphqlEditor(_) => KeymapContext::GraphqlEditor,
+        CurrentScreen::SchemaBrowser(_) => KeymapContext::SchemaBrowser,
+        CurrentScreen::Pager(pager_state) if pager_state.is_searching => KeymapContext::PagerSearch,
+        CurrentScreen::Pager(_) => KeymapContext::Pager,
+        CurrentScreen::Diff(_) => KeymapContext::Diff,
+        CurrentScreen::AlertLog(_) => KeymapContext::AlertLog,
+        CurrentScreen::SaveInput(_) => KeymapContext::NameInput,
+        CurrentScreen::ExitPrompt => KeymapContext::ExitPrompt,
+        CurrentScreen::OverwritePrompt(_) | CurrentScreen::HarOverwritePrompt(_) => {
+            KeymapContext::OverwritePrompt
+        }
+        CurrentScreen::HarEntries(_) => KeymapContext::HarEntries,
+        CurrentScreen::HarExportInput(_) => KeymapContext::NameInput,
+        CurrentScreen::WebSocket(_) => KeymapContext::WebSocket,
+        CurrentScreen::Loading => KeymapContext::Main,
+    }
+}
+
+fn footer_actions(context: KeymapContext) -> &'static [Action] {
+    match context {
+        KeymapContext::Main => &[
+            Action::ToggleHelp,
+            Action::AddItem,
+            Action::EditItem,
+            Action::DeleteItem,
+            Action::SendRequest,
+            Action::Quit,
+        ],
+        KeymapContext::EndpointInput | KeymapContext::NameInput | KeymapContext::PagerSearch => {
+            &[Action::Cancel, Action::Submit]
+        }
+        KeymapContext::PairInput => &[Action::Cancel, Action::SwitchField, Action::Submit],
+        KeymapContext::FilterInput => &[
+            Action::Cancel,
+            Action::PreviousFilter,
+            Action::NextFilter,
+            Action::Submit,
+        ],
+        KeymapContext::BodyEditor => &[
+            Action::ToggleHelp,
+            Action::Cancel,
+            Action::EditExternally,
+            Action::Submit,
+        ],
+        KeymapContext::Pager => &[
+            Action::ToggleHelp,
+            Action::Search,
+            Action::NextMatch,
+            Action::PreviousMatch,
+            Action::ToggleWrap,
+            Action::TogglePretty,
+            Action::SwitchContent,
+            Action::Close,
+        ],
+        KeymapContext::Diff => &[
+            Action::ToggleHelp,
+            Action::OlderBaseline,
+            Action::NewerBaseline,
+            Action::PinBaseline,
+            Action::Close,
+        ],
+        KeymapContext::AlertLog => &[Action::ToggleHelp, Action::Close],
+        KeymapContext::Alert => &[Action::Close],
+        KeymapContext::ExitPrompt | KeymapContext::OverwritePrompt => {
+            &[Action::Confirm, Action::Decline, Action::Cancel]
+        }
+        KeymapContext::HarEntries => &[Action::ToggleHelp, Action::ReplayEntry, Action::Close],
+        KeymapContext::GraphqlEditor => &[
+            Action::ToggleHelp,
+            Action::Cancel,
+            Action::SwitchField,
+            Action::Complete,
+            Action::Submit,
+        ],
+        KeymapContext::WebSocket => &[
+            Action::ToggleHelp,
+            Action::Cancel,
+            Action::SwitchMessageKind,
+            Action::SendPing,
+            Action::CloseConnection,
+            Action::Submit,
+        ],
+        KeymapContext::SchemaBrowser => &[
+            Action::ToggleHelp,
+            Action::OpenType,
+            Action::GoBack,
+            Action::FetchSchema,
+            Action::Close,
+        ],
+    }
+}
+
+fn short_description(action: Action) -> &'static str {
+    match action {
+        Action::ToggleHelp => "help",
+        Action::AddItem => "add",
+        Action::EditItem => "edit",
+        Action::DeleteItem => "delete",
+        Action::SendRequest => "send",
+        Action::Quit => "quit",
+        Action::Cancel => "cancel",
+        Action::Submit => "submit",
+        Action::EditExternally => "$EDITOR",
+        Action::SwitchField => "switch boxes",
+        Action::PreviousFilter => "previous",
+        Action::NextFilter => "next",
+        Action::Search => "search",
+        Action::NextMatch => "next match",
+        Action::PreviousMatch => "previous match",
+        Action::ToggleWrap => "wrap",
+        Action::TogglePretty => "pretty",
+        Action::SwitchContent => "body/headers",
+        Action::OlderBaseline => "older",
+        Action::NewerBaseline => "newer",
+        Action::PinBaseline => "pin",
+        Action::Close => "close",
+        Action::Confirm => "yes",
+        Action::Decline => "no",
+        Action::ReplayEntry => "replay",
+        Action::Complete => "complete",
+        Action::OpenType => "open",
+        Action::GoBack => "back",
+        Action::FetchSchema => "refetch",
+        Action::SwitchMessageKind => "text/JSON/binary",
+        Action::SendPing => "ping",
+        Action::CloseConnection => "close",
+        _ => action.description(),
+    }
+}
+
+/// Parse a key chord such as `ctrl+s`, `alt+enter`, `G` or `f1`
+pub fn parse_key_chord(chord: &str) -> Result<(KeyCode, KeyModifiers), String> {
+    // A trailing "+" is the plus key itself rather than a separator
+    let (modifier_part, key_part) = match chord.strip_suffix("++") {
+        Some(modifiers) => (Some(modifiers), "+"),
+        None if chord == "+" => (None, "+"),
+        None => match chord.rsplit_once('+') {
+            Some((modifiers, key)) => (Some(modifiers), key),
+            None => (None, chord),
+        },
+    };
+
+    let mut modifiers = KeyModifiers::NONE;
+    for modifier in modifier_part.into_iter().flat_map(|part| part.split('+')) {
+        modifiers |= match modifier.to_lowercase().as_str() {
+            "ctrl" | "control" => KeyModifiers::CONTROL,
+            "alt" | "meta" => KeyModifiers::ALT,
+            "shift" => KeyModifiers::SHIFT,
+            _ => {
+                return Err(format!(
+                    "unknown modifier \"{}\" in \"{}\"",
+                    modifier, chord
+                ))
+            }
+        };
+    }
+
+    let code = match key_part.to_lowercase().as_str() {
+        "enter" | "return" => KeyCode::Enter,
+        "esc" | "escape" => KeyCode::Esc,
+        "tab" if modifiers.contains(KeyModifiers::SHIFT) => {
+            modifiers -= KeyModifiers::SHIFT;
+            KeyCode::BackTab
+        }
+        "tab" => KeyCode::Tab,
+        "backtab" => KeyCode::BackTab,
+        "backspace" => KeyCode::Backspace,
+        "delete" | "del" => KeyCode::Delete,
+        "insert" => KeyCode::Insert,
+        "space" => KeyCode::Char(' '),
+        "up" => KeyCode::Up,
+        "down" => KeyCode::Down,
+        "left" => KeyCode::Left,
+        "right" => KeyCode::Right,
+        "home" => KeyCode::Home,
+        "end" => KeyCode::End,
+        "pageup" | "pgup" => KeyCode::PageUp,
+        "pagedown" | "pgdn" => KeyCode::PageDown,
+        name if name.len() > 1 && name.starts_with('f') => match name[1..].parse::<u8>() {
+            Ok(number) if (1..=12).contains(&number) => KeyCode::F(number),
+            _ => return Err(format!("unknown key \"{}\"", key_part)),
+        },
+        _ => {
+            let mut chars = key_part.chars();
+            match (chars.next(), chars.next()) {
+                // Shift is implied by upper case characters
+                (Some(c), None) if modifiers.contains(KeyModifiers::SHIFT) => {
+                    modifiers -= KeyModifiers::SHIFT;
+                    KeyCode::Char(c.to_ascii_uppercase())
+                }
+                (Some(c), None) => KeyCode::Char(c),
+                _ => return Err(format!("unknown key \"{}\"", key_part)),
+            }
+        }
+    };
+    Ok((code, modifiers))
+}
+
+pub fn describe_key(code: KeyCode, modifiers: KeyModifiers) -> String {
+    let key = match code {
+        KeyCode::Char(' ') => "Space".to_string(),
+        KeyCode::Char(c) => c.to_string(),
+        KeyCode::F(number) => format!("F{}", number),
+        KeyCode::PageUp => "PgUp".to_string(),
+        KeyCode::PageDown => "PgDn".to_string(),
+        other => format!("{:?}", other),
+    };
+    let mut prefix = String::new();
+    if modifiers.contains(KeyModifiers::CONTROL) {
+        prefix.push_str("Ctrl+");
+    }
+    if modifiers.contains(KeyModifiers::ALT) {
+        prefix.push_str("Alt+");
+    }
+    if modifiers.contains(KeyModifiers::SHIFT) {
+        prefix.push_str("Shift+");
+    }
+    format!("{}{}", prefix, key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn config(bindings: &[(&str, &str, &[&str])]) -> KeybindingsConfig {
+        let mut config = KeybindingsConfig::default();
+        for (context, action, chords) in bindings {
+            let chords = chords.iter().map(|chord| chord.to_string()).collect();
+            config
+                .bindings
+                .entry(context.to_string())
+                .or_insert_with(HashMap::new)
+                .insert(action.to_string(), KeyChords::Many(chords));
+        }
+        config
+    }
+
+    #[test]
+    fn modifiers_and_keys() {
+        assert_eq!(
+            parse_key_chord("ctrl+s"),
+            Ok((KeyCode::Char('s'), KeyModifiers::CONTROL))
+        );
+        assert_eq!(
+            parse_key_chord("Ctrl+Alt+Enter"),
+            Ok((KeyCode::Enter, KeyModifiers::CONTROL | KeyModifiers::ALT))
+        );
+        assert_eq!(
+            parse_key_chord("pgdn"),
+            Ok((KeyCode::PageDown, KeyModifiers::NONE))
+        );
+        assert_eq!(
+            parse_key_chord("space"),
+            Ok((KeyCode::Char(' '), KeyModifiers::NONE))
+        );
+    }
+
+    #[test]
+    fn plus_key() {
+        assert_eq!(
+            parse_key_chord("+"),
+            Ok((KeyCode::Char('+'), KeyModifiers::NONE))
+        );
+        assert_eq!(
+            parse_key_chord("ctrl++"),
+            Ok((KeyCode::Char('+'), KeyModifiers::CONTROL))
+        );
+    }
+
+    #[test]
+    fn shift() {
+        assert_eq!(
+            parse_key_chord("shift+tab"),
+            Ok((KeyCode::BackTab, KeyModifiers::NONE))
+        );
+        assert_eq!(
+            parse_key_chord("shift+g"),
+            Ok((KeyCode::Char('G'), KeyModifiers::NONE))
+        );
+        assert_eq!(
+            parse_key_chord("G"),
+            Ok((KeyCode::Char('G'), KeyModifiers::NONE))
+        );
+        assert_eq!(
+            parse_key_chord("shift+up"),
+            Ok((KeyCode::Up, KeyModifiers::SHIFT))
+        );
+    }
+
+    #[test]
+    fn function_keys() {
+        assert_eq!(
+            parse_key_chord("f1"),
+            Ok((KeyCode::F(1), KeyModifiers::NONE))
+        );
+        assert_eq!(
+            parse_key_chord("F12"),
+            Ok((KeyCode::F(12), KeyModifiers::NONE))
+        );
+        assert_eq!(
+            parse_key_chord("f"),
+            Ok((KeyCode::Char('f'), KeyModifiers::NONE))
+        );
+        assert_eq!(
+            parse_key_chord("f13"),
+            Err("unknown key \"f13\"".to_string())
+        );
+        assert_eq!(parse_key_chord("f0"), Err("unknown key \"f0\"".to_string()));
+    }
+
+    #[test]
+    fn invalid_chords() {
+        assert_eq!(
+            parse_key_chord("hyper+s"),
+            Err("unknown modifier \"hyper\" in \"hyper+s\"".to_string())
+        );
+        assert_eq!(
+            parse_key_chord("banana"),
+            Err("unknown key \"banana\"".to_string())
+        );
+        assert_eq!(parse_key_chord(""), Err("unknown key \"\"".to_string()));
+        assert!(parse_key_chord("ctrl+").is_err());
+    }
+
+    #[test]
+    fn describing_keys() {
+        assert_eq!(
+            describe_key(KeyCode::Char('s'), KeyModifiers::CONTROL),
+            "Ctrl+s"
+        );
+        assert_eq!(describe_key(KeyCode::F(1), KeyModifiers::NONE), "F1");
+        assert_eq!(
+            describe_key(KeyCode::Char(' '), KeyModifiers::NONE),
+            "Space"
+        );
+        assert_eq!(
+            describe_key(KeyCode::PageDown, KeyModifiers::ALT),
+            "Alt+PgDn"
+        );
+    }
+
+    #[test]
+    fn shift_is_implied_for_characters() {
+        let keymap = Keymap::new();
+        let key = KeyEvent::new(KeyCode::Char('G'), KeyModifiers::SHIFT);
+        assert_eq!(
+            keymap.action_for(KeymapContext::HarEntries, &key),
+            Some(Action::ScrollToBottom)
+        );
+    }
+
+    #[test]
+    fn presets_have_no_conflicts() {
+        assert_eq!(Keymap::new().conflicts(), Vec::<String>::new());
+        let (_, problems) = Keymap::from_config(&KeybindingsConfig {
+            preset: Some("arrows".to_string()),
+            ..Default::default()
+        });
+        assert_eq!(problems, Vec::<String>::new());
+    }
+
+    #[test]
+    fn rebinding() {
+        let (keymap, problems) =
+            Keymap::from_config(&config(&[("main", "send_request", &["ctrl+r", "f5"])]));
+        assert_eq!(problems, Vec::<String>::new());
+        let key = KeyEvent::new(KeyCode::F(5), KeyModifiers::NONE);
+        assert_eq!(
+            keymap.action_for(KeymapContext::Main, &key),
+            Some(Action::SendRequest)
+        );
+        // The old key is replaced
+        let key = KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE);
+        assert_ne!(
+            keymap.action_for(KeymapContext::Main, &key),
+            Some(Action::SendRequest)
+        );
+    }
+
+    #[test]
+    fn duplicate_chords() {
+        let (_, problems) = Keymap::from_config(&config(&[("main", "next_item", &["k"])]));
+        assert_eq!(
+            problems,
+            vec!["k is bound to both PreviousItem and NextItem on the Main screen"]
+        );
+    }
+
+    #[test]
+    fn unknown_names() {
+        let (_, problems) = Keymap::from_config(&config(&[("nowhere", "next_item", &["j"])]));
+        assert_eq!(problems, vec!["Unknown keybinding screen \"nowhere\""]);
+
+        let (_, problems) = Keymap::from_config(&config(&[("main", "fly", &["j"])]));
+        assert_eq!(problems, vec!["Unknown action \"fly\" for screen \"main\""]);
+
+        let (_, problems) = Keymap::from_config(&KeybindingsConfig {
+            preset: Some("emacs".to_string()),
+            ..Default::default()
+        });
+        assert_eq!(
+            problems,
+            vec!["Unknown keybinding preset \"emacs\", expected \"vim\" or \"arrows\""]
+        );
+    }
+
+    #[test]
+    fn invalid_keys() {
+        // Valid keys are still bound
+        let (keymap, problems) =
+            Keymap::from_config(&config(&[("main", "send_request", &["ctrl+r", "hyper+r"])]));
+        assert_eq!(
+            problems,
+            vec!["Invalid key for main.send_request: unknown modifier \"hyper\" in \"hyper+r\""]
+        );
+        assert_eq!(
+            keymap.key_for(KeymapContext::Main, Action::SendRequest),
+            Some("Ctrl+r".to_string())
+        );
+
+        // Without any valid keys the defaults are kept
+        let (keymap, problems) =
+            Keymap::from_config(&config(&[("main", "send_request", &["banana"])]));
+        assert_eq!(
+            problems,
+            vec![
+                "Invalid key for main.send_request: unknown key \"banana\"",
+                "No valid keys for main.send_request, keeping its default keys",
+            ]
+        );
+        assert_eq!(
+            keymap.key_for(KeymapContext::Main, Action::SendRequest),
+            Some("Enter".to_string())
+        );
+    }
+}
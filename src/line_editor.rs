@@ -0,0 +1,279 @@
+use unicode_width::UnicodeWidthChar;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum EditKind {
+    Insert,
+    Delete,
+    Other,
+}
+
+/// A single line of editable text with a cursor and undo history
+#[derive(Clone)]
+pub struct LineEditor {
+    text: String,
+    cursor: usize, // Position in characters, not bytes
+    undo_stack: Vec<(String, usize)>,
+    last_edit: Option<EditKind>, // Consecutive inserts or deletes are undone together
+}
+
+impl LineEditor {
+    pub fn new(text: &str) -> LineEditor {
+        LineEditor {
+            text: text.to_string(),
+            cursor: text.chars().count(),
+            undo_stack: Vec::new(),
+            last_edit: None,
+        }
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty()
+    }
+
+    /// Replace the whole text, leaving the cursor at the end
+    pub fn set_text(&mut self, text: &str) {
+        self.save_undo(EditKind::Other);
+        self.text = text.to_string();
+        self.cursor = self.char_count();
+    }
+
+    pub fn clear(&mut self) {
+        self.set_text("");
+    }
+
+    pub fn insert_char(&mut self, c: char) {
+        if c.is_control() {
+            return;
+        }
+        self.save_undo(EditKind::Insert);
+        let index = self.byte_index(self.cursor);
+        self.text.insert(index, c);
+        self.cursor += 1;
+    }
+
+    /// Insert pasted text at the cursor, dropping line breaks and other control characters
+    pub fn insert_str(&mut self, text: &str) {
+        let text: String = text.chars().filter(|c| !c.is_control()).collect();
+        if text.is_empty() {
+            return;
+        }
+        self.save_undo(EditKind::Other);
+        let index = self.byte_index(self.cursor);
+        self.text.insert_str(index, &text);
+        self.cursor += text.chars().count();
+    }
+
+    pub fn delete_backward(&mut self) {
+        if self.cursor == 0 {
+            return;
+        }
+        self.save_undo(EditKind::Delete);
+        self.cursor -= 1;
+        self.text.remove(self.byte_index(self.cursor));
+    }
+
+    pub fn delete_forward(&mut self) {
+        if self.cursor == self.char_count() {
+            return;
+        }
+        self.save_undo(EditKind::Delete);
+        self.text.remove(self.byte_index(self.cursor));
+    }
+
+    /// Delete from the start of the word before the cursor up to the cursor
+    pub fn delete_word_backward(&mut self) {
+        let start = self.word_start_before(self.cursor);
+        if start == self.cursor {
+            return;
+        }
+        self.save_undo(EditKind::Other);
+        let range = self.byte_index(start)..self.byte_index(self.cursor);
+        self.text.replace_range(range, "");
+        self.cursor = start;
+    }
+
+    pub fn move_left(&mut self) {
+        self.move_to(self.cursor.saturating_sub(1));
+    }
+
+    pub fn move_right(&mut self) {
+        self.move_to(self.cursor + 1);
+    }
+
+    pub fn move_to_start(&mut self) {
+        self.move_to(0);
+    }
+
+    pub fn move_to_end(&mut self) {
+        self.move_to(self.char_count());
+    }
+
+    pub fn move_word_left(&mut self) {
+        self.move_to(self.word_start_before(self.cursor));
+    }
+
+    pub fn move_word_right(&mut self) {
+        let chars: Vec<char> = self.text.chars().collect();
+        let mut position = self.cursor;
+        while position < chars.len() && !is_word_char(chars[position]) {
+            position += 1;
+        }
+        while position < chars.len() && is_word_char(chars[position]) {
+            position += 1;
+        }
+        self.move_to(position);
+    }
+
+    pub fn undo(&mut self) {
+        if let Some((text, cursor)) = self.undo_stack.pop() {
+            self.text = text;
+            self.cursor = cursor;
+            self.last_edit = None;
+        }
+    }
+
+    /// The display column of the cursor, accounting for wide characters
+    pub fn cursor_column(&self) -> usize {
+        self.text
+            .chars()
+            .take(self.cursor)
+            .map(|c| c.width().unwrap_or(0))
+            .sum()
+    }
+
+    fn move_to(&mut self, position: usize) {
+        self.cursor = position.min(self.char_count());
+        self.last_edit = None;
+    }
+
+    fn word_start_before(&self, position: usize) -> usize {
+        let chars: Vec<char> = self.text.chars().collect();
+        let mut position = position;
+        while position > 0 && !is_word_char(chars[position - 1]) {
+            position -= 1;
+        }
+        while position > 0 && is_word_char(chars[position - 1]) {
+            position -= 1;
+        }
+        position
+    }
+
+    fn save_undo(&mut self, kind: EditKind) {
+        if kind == EditKind::Other || self.last_edit != Some(kind) {
+            self.undo_stack.push((self.text.clone(), self.cursor));
+        }
+        self.last_edit = Some(kind);
+    }
+
+    fn char_count(&self) -> usize {
+        self.text.chars().count()
+    }
+
+    fn byte_index(&self, char_index: usize) -> usize {
+        self.text
+            .char_indices()
+            .nth(char_index)
+            .map(|(index, _)| index)
+            .unwrap_or(self.text.len())
+    }
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn multi_byte_characters() {
+        let mut editor = LineEditor::new("héllo");
+        editor.move_to_start();
+        editor.move_right();
+        editor.move_right();
+        editor.insert_char('ü');
+        assert_eq!(editor.text(), "héüllo");
+        editor.delete_backward();
+        editor.delete_backward();
+        assert_eq!(editor.text(), "hllo");
+        editor.insert_str("日本");
+        assert_eq!(editor.text(), "h日本llo");
+        assert_eq!(editor.cursor_column(), 5);
+        editor.delete_forward();
+        assert_eq!(editor.text(), "h日本lo");
+    }
+
+    #[test]
+    fn pasting_drops_control_characters() {
+        let mut editor = LineEditor::new("");
+        editor.insert_str("a\r\nb\tc");
+        assert_eq!(editor.text(), "abc");
+        editor.insert_char('\n');
+        assert_eq!(editor.text(), "abc");
+    }
+
+    #[test]
+    fn word_jumps() {
+        let mut editor = LineEditor::new("Bearer some_token.über");
+        editor.move_word_left();
+        editor.insert_char('|');
+        assert_eq!(editor.text(), "Bearer some_token.|über");
+        // Undo puts the cursor back where it was
+        editor.undo();
+        editor.move_word_left();
+        editor.insert_char('|');
+        assert_eq!(editor.text(), "Bearer |some_token.über");
+        editor.undo();
+        editor.move_to_start();
+        editor.move_word_right();
+        editor.insert_char('|');
+        assert_eq!(editor.text(), "Bearer| some_token.über");
+    }
+
+    #[test]
+    fn deleting_words() {
+        let mut editor = LineEditor::new("one two  ");
+        editor.delete_word_backward();
+        assert_eq!(editor.text(), "one ");
+        editor.delete_word_backward();
+        assert_eq!(editor.text(), "");
+        editor.delete_word_backward();
+        assert_eq!(editor.text(), "");
+    }
+
+    #[test]
+    fn undo_groups_consecutive_edits() {
+        let mut editor = LineEditor::new("");
+        for c in "abc".chars() {
+            editor.insert_char(c);
+        }
+        editor.delete_backward();
+        editor.delete_backward();
+        assert_eq!(editor.text(), "a");
+        editor.undo();
+        assert_eq!(editor.text(), "abc");
+        editor.undo();
+        assert_eq!(editor.text(), "");
+        editor.undo();
+        assert_eq!(editor.text(), "");
+    }
+
+    #[test]
+    fn moving_ends_an_undo_group() {
+        let mut editor = LineEditor::new("");
+        editor.insert_char('a');
+        editor.move_left();
+        editor.insert_char('b');
+        assert_eq!(editor.text(), "ba");
+        editor.undo();
+        assert_eq!(editor.text(), "a");
+        editor.set_text("xyz");
+        editor.undo();
+        assert_eq!(editor.text(), "a");
+    }
+}
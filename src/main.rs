@@ -1,9 +1,13 @@
-use std::{error::Error, io};
+use std::{
+    env,
+    error::Error,
+    io::{self, IsTerminal},
+    time::Duration,
+};
 
-use crossterm::{
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind},
-    execute,
-    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+use crossterm::event::{
+    self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseButton, MouseEvent,
+    MouseEventKind,
 };
 use ratatui::{
     backend::{Backend, CrosstermBackend},
@@ -11,37 +15,164 @@ use ratatui::{
 };
 
 mod app;
+mod cli;
+mod clipboard;
+mod config;
+mod diff;
+mod filter;
+mod graphql;
+mod har;
 mod helpers;
+mod http_file;
 mod http_request;
+mod json_tree;
+mod keymap;
+mod line_editor;
+mod notification;
+mod pager;
+mod request_error;
+mod request_file;
+mod storage;
+mod terminal;
+mod text_editor;
+mod theme;
 mod ui;
+mod variables;
+mod websocket;
 
 use crate::{
-    app::{App, CurrentScreen, KeyValuePair},
+    app::{
+        App, CurrentScreen, GraphqlEditorState, GraphqlField, KeyValuePair, MouseTarget,
+        SaveInputState,
+    },
+    cli::{initial_request, parse_args, Args, InitialRequest, USAGE},
+    config::load_config,
+    keymap::{context_for, Action, Keymap, KeymapContext},
+    line_editor::LineEditor,
+    notification::Severity,
+    pager::{pager_lines, PagerContent},
+    request_file::parse_request,
+    storage::{load_tabs, requests_directory, save_tabs, set_aside_tabs, set_requests_directory},
+    terminal::{
+        edit_externally, restore_terminal, set_mouse_capture, setup_terminal, view_externally,
+    },
+    text_editor::TextEditor,
+    theme::Theme,
     ui::ui,
 };
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
+    let args = match parse_args(env::args().skip(1)) {
+        Ok(args) => args,
+        Err(err) => exit_with_usage(&err),
+    };
+    if args.show_help {
+        println!("{}", USAGE);
+        return Ok(());
+    }
+    if let Some(ref collection) = args.collection {
+        set_requests_directory(collection.clone());
+    }
+    let initial_request = initial_request(&args, requests_directory().as_deref())
+        .unwrap_or_else(|err| exit_with_usage(&err));
+
+    let mut startup_problems = Vec::<String>::new();
+    let (config, config_loaded) = match load_config() {
+        Ok(config) => (config, true),
+        Err(err) => {
+            startup_problems.push(format!("{}, using the default config", err));
+            (Default::default(), false)
+        }
+    };
+    if let Some(ref environment) = args.environment {
+        if !config.environments.contains_key(environment) {
+            let mut names: Vec<&String> = config.environments.keys().collect();
+            names.sort();
+            exit_with_usage(&format!(
+                "Unknown environment \"{}\", the config has {}",
+                environment,
+                match names.is_empty() {
+                    true => "none".to_string(),
+                    false => names
+                        .iter()
+                        .map(|name| name.as_str())
+                        .collect::<Vec<&str>>()
+                        .join(", "),
+                }
+            ));
+        }
+    }
+    let (keymap, keymap_problems) = Keymap::from_config(&config.keybindings);
+    startup_problems.extend(keymap_problems);
+    let (theme, theme_problems) = Theme::from_config(&config.theme);
+    startup_problems.extend(theme_problems);
+    report_startup_problems(&startup_problems)?;
+
+    let mut app = App::new();
+    app.config = config;
+    app.config_loaded = config_loaded;
+    app.keymap = keymap;
+    app.theme = theme;
+    app.environment = args.environment.clone();
+    if args.send {
+        let Some(initial_request) = initial_request else {
+            exit_with_usage("--send needs a request to send");
+        };
+        open_initial_request(&mut app, initial_request, args);
+        // Every entry of a HAR file is replayed, other than those tapir can't send
+        for entry in app.har_entries.clone() {
+            match entry.request_file() {
+                Ok(request) => app.open_request_file(request),
+                Err(err) => eprintln!(
+                    "tapir: Skipped {} {}: {}",
+                    entry.request.method, entry.request.url, err
+                ),
+            }
+        }
+        return send_headlessly(&mut app).await;
+    }
+
     // setup terminal
-    enable_raw_mode()?;
-    let mut stderr = io::stderr(); // This is a special case. Normally using stdout is fine
-    execute!(stderr, EnterAlternateScreen, EnableMouseCapture)?;
-    let backend = CrosstermBackend::new(stderr);
+    setup_terminal()?;
+    let backend = CrosstermBackend::new(io::stderr());
     let mut terminal = Terminal::new(backend)?;
 
-    // create app and run it
-    let mut app = App::new();
+    // restore the tabs from last time, then open any request from the command line
+    let mut should_save_tabs = true;
+    match load_tabs() {
+        Ok(Some(saved_tabs)) => app.restore_tabs(saved_tabs),
+        Ok(None) => {}
+        Err(err) => {
+            // Keep the tabs which couldn't be loaded rather than saving over them on exit
+            let details = match set_aside_tabs() {
+                Ok(backup_path) => {
+                    format!("{}\n\nThe file was moved to {}", err, backup_path.display())
+                }
+                Err(_) => {
+                    should_save_tabs = false;
+                    err.to_string()
+                }
+            };
+            app.notifications
+                .error("Unable to restore the open tabs", Some(details));
+        }
+    }
+    if let Some(initial_request) = initial_request {
+        open_initial_request(&mut app, initial_request, args);
+    }
     let output_value = run_app(&mut terminal, &mut app).await;
 
     // restore terminal
-    disable_raw_mode()?;
-    execute!(
-        terminal.backend_mut(),
-        LeaveAlternateScreen,
-        DisableMouseCapture
-    )?;
+    restore_terminal()?;
     terminal.show_cursor()?;
 
+    if should_save_tabs {
+        if let Err(err) = save_tabs(&app.saved_tabs()) {
+            eprintln!("Unable to save open tabs: {}", err);
+        }
+    }
+
     if let Some(output) = output_value? {
         println!("{}", output);
     }
@@ -49,108 +180,895 @@ async fn main() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+fn open_initial_request(app: &mut App, initial_request: InitialRequest, args: Args) {
+    match initial_request {
+        InitialRequest::New(request) => {
+            app.open_request_file(request);
+            return;
+        }
+        InitialRequest::Saved(name, request) => app.open_saved_request(&name, request),
+        InitialRequest::Loaded(request) => app.open_request(request),
+        InitialRequest::HttpFile(path, http_file) => {
+            app.open_http_file(path, http_file);
+            return;
+        }
+        InitialRequest::Har(entries) => {
+            app.open_har(entries);
+            return;
+        }
+    }
+    // Headers and a body given alongside a saved or loaded request change it
+    if !args.headers.is_empty() || args.body.is_some() {
+        let mut request = app.request_file();
+        request.headers.extend(args.headers);
+        if let Some(body) = args.body {
+            request.body = body;
+        }
+        app.load_request_file(request);
+    }
+}
+
+/// Send each open request in turn without the TUI, printing the requests and responses as JSON.
+/// A request which fails is included with its error, and the exit status is non-zero.
+async fn send_headlessly(app: &mut App) -> Result<(), Box<dyn Error>> {
+    let mut sessions = Vec::<serde_json::Value>::new();
+    let mut failed = false;
+    // Such as requests in a .http file which were skipped
+    for notification in app.notifications.log.iter() {
+        if notification.severity == Severity::Warning {
+            eprintln!("tapir: warning: {}", notification.message);
+        }
+    }
+    for index in 0..app.tabs.len() {
+        app.select_tab(index);
+        let notification_count = app.notifications.count();
+        app.send_api_request().await;
+        let mut session = app.session_json();
+        let mut errors = Vec::<String>::new();
+        for notification in app.notifications.since(notification_count) {
+            match notification.severity {
+                Severity::Warning => eprintln!("tapir: warning: {}", notification.message),
+                Severity::Error => {
+                    eprintln!("tapir: {}", notification.message);
+                    if let Some(ref details) = notification.details {
+                        eprintln!("\n{}\n", details);
+                    }
+                    errors.push(notification.message.clone());
+                }
+                _ => {}
+            }
+        }
+        if !errors.is_empty() {
+            failed = true;
+            session["errors"] = serde_json::json!(errors);
+        }
+        sessions.push(session);
+    }
+    let output = match sessions.len() {
+        1 => sessions.remove(0),
+        _ => serde_json::Value::Array(sessions),
+    };
+    println!("{}", serde_json::to_string_pretty(&output)?);
+    if failed {
+        std::process::exit(1)
+    }
+    Ok(())
+}
+
+fn exit_with_usage(message: &str) -> ! {
+    eprintln!("tapir: {}\n\n{}", message, USAGE);
+    std::process::exit(2)
+}
+
+/// Print any problems with the config before the TUI hides them, and wait for acknowledgement
+fn report_startup_problems(problems: &[String]) -> io::Result<()> {
+    if problems.is_empty() {
+        return Ok(());
+    }
+    eprintln!("There were problems with the tapir config:");
+    for problem in problems {
+        eprintln!("  - {}", problem);
+    }
+    if io::stdin().is_terminal() {
+        eprintln!("Press Enter to continue");
+        io::stdin().read_line(&mut String::new())?;
+    }
+    Ok(())
+}
+
+/// Edit the active request in `$EDITOR`, reopening it with the problem noted until it parses.
+/// Deleting everything in the file cancels the edit.
+fn edit_request_externally<B: Backend>(
+    terminal: &mut Terminal<B>,
+    app: &mut App,
+) -> io::Result<()> {
+    const NOTE_PREFIX: &str = "# tapir: ";
+    let mut text = app.request_file().to_string();
+    loop {
+        let result = edit_externally(&text, "http");
+        terminal.clear()?;
+        let edited_text = result?;
+        if edited_text
+            .lines()
+            .all(|line| line.trim().is_empty() || line.starts_with('#'))
+        {
+            return Ok(());
+        }
+        match parse_request(&edited_text) {
+            Ok(request) => {
+                app.load_request_file(request);
+                return Ok(());
+            }
+            Err(err) => {
+                let request_lines: Vec<&str> = edited_text
+                    .lines()
+                    .filter(|line| !line.starts_with(NOTE_PREFIX))
+                    .collect();
+                text = format!(
+                    "{}{}\n{}Fix the request, or delete everything to cancel\n{}\n",
+                    NOTE_PREFIX,
+                    err,
+                    NOTE_PREFIX,
+                    request_lines.join("\n")
+                );
+            }
+        }
+    }
+}
+
+/// What to print after quitting, if the user asked for it
+fn exit_output(app: &App) -> Option<String> {
+    match app.print_on_exit {
+        true => serde_json::to_string_pretty(&app.session_json()).ok(),
+        false => None,
+    }
+}
+
+/// Click on whatever was drawn under the mouse, or scroll with the wheel
+async fn handle_mouse_event<B: Backend>(
+    terminal: &mut Terminal<B>,
+    app: &mut App,
+    mouse: MouseEvent,
+) -> io::Result<()> {
+    let is_click = mouse.kind == MouseEventKind::Down(MouseButton::Left);
+    if app.notifications.current_error().is_some() {
+        // A click anywhere dismisses the error
+        if is_click {
+            app.notifications.dismiss_error();
+        }
+        return Ok(());
+    }
+    if app.show_help {
+        // Any click closes the help overlay
+        app.show_help = !is_click;
+        return Ok(());
+    }
+    let scroll_amount: isize = match mouse.kind {
+        MouseEventKind::ScrollDown => 1,
+        MouseEventKind::ScrollUp => -1,
+        _ => 0,
+    };
+    let target = app.mouse_target_at(mouse.column, mouse.row);
+
+    match app.current_screen {
+        CurrentScreen::Main if is_click => match target {
+            Some(MouseTarget::Tab(index)) => app.select_tab(index),
+            Some(MouseTarget::Method) => app.increment_method(false),
+            Some(MouseTarget::Endpoint) => {
+                app.current_screen = CurrentScreen::EndpointInput(LineEditor::new(&app.endpoint));
+            }
+            Some(MouseTarget::SendButton) => app.send_api_request().await,
+            Some(MouseTarget::SectionName) => app.increment_section(false),
+            Some(MouseTarget::Pane(is_request_pane)) => app.focus_pane(is_request_pane),
+            Some(MouseTarget::ListItem(is_request_pane, index)) => {
+                app.focus_pane(is_request_pane);
+                app.select_item(index);
+            }
+            None => {}
+        },
+        CurrentScreen::Main if scroll_amount != 0 => {
+            if let Some(MouseTarget::Pane(is_request_pane))
+            | Some(MouseTarget::ListItem(is_request_pane, _)) = target
+            {
+                app.focus_pane(is_request_pane);
+                app.increment_selection(scroll_amount < 0);
+            }
+        }
+        CurrentScreen::Pager(ref mut pager_state) if scroll_amount != 0 => {
+            let line_count = match app.last_response {
+                Some(ref response) => pager_lines(response, pager_state).len(),
+                None => 0,
+            };
+            pager_state.scroll_by(scroll_amount * 3, line_count);
+        }
+        CurrentScreen::Diff(_) => app.scroll_diff(scroll_amount * 3),
+        CurrentScreen::AlertLog(ref mut scroll) => {
+            let last = app.notifications.log.len().saturating_sub(1);
+            *scroll = scroll.saturating_add_signed(scroll_amount).min(last);
+        }
+        CurrentScreen::HarEntries(ref mut selected) => {
+            let last = app.har_entries.len().saturating_sub(1);
+            *selected = selected.saturating_add_signed(scroll_amount).min(last);
+        }
+        CurrentScreen::WebSocket(ref mut websocket_state) => {
+            websocket_state.scroll_by(-scroll_amount * 3);
+        }
+        CurrentScreen::SchemaBrowser(ref browser_state) => {
+            app.select_schema_item(browser_state.selected.saturating_add_signed(scroll_amount));
+        }
+        CurrentScreen::BodyEditor(ref mut editor_state) if scroll_amount != 0 => {
+            match scroll_amount < 0 {
+                true => editor_state.editor.move_up(3),
+                false => editor_state.editor.move_down(3),
+            }
+            let visible_rows = terminal.size()?.height.saturating_sub(10) as usize;
+            editor_state.editor.scroll_to_cursor(visible_rows);
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Apply a line editing action, or type the key if it isn't bound to anything
+fn edit_line(editor: &mut LineEditor, action: Option<Action>, key: &KeyEvent) {
+    match (action, key.code) {
+        (Some(Action::DeleteCharacter), _) => editor.delete_backward(),
+        (Some(Action::DeleteForward), _) => editor.delete_forward(),
+        (Some(Action::DeleteWord), _) => editor.delete_word_backward(),
+        (Some(Action::CursorLeft), _) => editor.move_left(),
+        (Some(Action::CursorRight), _) => editor.move_right(),
+        (Some(Action::CursorToStart), _) => editor.move_to_start(),
+        (Some(Action::CursorToEnd), _) => editor.move_to_end(),
+        (Some(Action::WordLeft), _) => editor.move_word_left(),
+        (Some(Action::WordRight), _) => editor.move_word_right(),
+        (Some(Action::Undo), _) => editor.undo(),
+        (None, KeyCode::Char(value))
+            if !key
+                .modifiers
+                .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) =>
+        {
+            editor.insert_char(value)
+        }
+        _ => {}
+    }
+}
+
+/// Apply a multi-line editing action, or type the key if it isn't bound to anything
+fn edit_text(editor: &mut TextEditor, action: Option<Action>, key: &KeyEvent, page_height: usize) {
+    match (action, key.code) {
+        (Some(Action::DeleteCharacter), _) => editor.delete_backward(),
+        (Some(Action::DeleteForward), _) => editor.delete_forward(),
+        (Some(Action::DeleteWord), _) => editor.delete_word_backward(),
+        (Some(Action::CursorLeft), _) => editor.move_left(),
+        (Some(Action::CursorRight), _) => editor.move_right(),
+        (Some(Action::CursorUp), _) => editor.move_up(1),
+        (Some(Action::CursorDown), _) => editor.move_down(1),
+        (Some(Action::PageUp), _) => editor.move_up(page_height),
+        (Some(Action::PageDown), _) => editor.move_down(page_height),
+        (Some(Action::CursorToStart), _) => editor.move_to_line_start(),
+        (Some(Action::CursorToEnd), _) => editor.move_to_line_end(),
+        (Some(Action::WordLeft), _) => editor.move_word_left(),
+        (Some(Action::WordRight), _) => editor.move_word_right(),
+        (Some(Action::Undo), _) => editor.undo(),
+        (None, KeyCode::Enter) => editor.insert_newline(),
+        (None, KeyCode::Tab) => editor.insert_tab(),
+        (None, KeyCode::Char(value))
+            if !key
+                .modifiers
+                .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) =>
+        {
+            editor.insert_char(value)
+        }
+        _ => {}
+    }
+}
+
 async fn run_app<B: Backend>(
     terminal: &mut Terminal<B>,
     app: &mut App,
 ) -> io::Result<Option<String>> {
     loop {
-        terminal.draw(|frame| ui::<B>(frame, app))?;
+        app.receive_websocket_frames();
+        terminal.draw(|frame| ui(frame, app))?;
 
-        if let Event::Key(key) = event::read()? {
+        // Redraw regularly even without any input, so that notifications expire
+        if !event::poll(Duration::from_millis(250))? {
+            continue;
+        }
+        let event = event::read()?;
+        if let Event::Paste(ref text) = event {
+            if let CurrentScreen::BodyEditor(ref mut editor_state) = app.current_screen {
+                editor_state.editor.insert_str(text);
+            } else if let CurrentScreen::WebSocket(ref mut websocket_state) = app.current_screen {
+                websocket_state.composer.insert_str(text);
+            } else if let CurrentScreen::GraphqlEditor(GraphqlEditorState {
+                focus: focus @ (GraphqlField::Query | GraphqlField::Variables),
+                ref mut query,
+                ref mut variables,
+                ..
+            }) = app.current_screen
+            {
+                match focus {
+                    GraphqlField::Query => query.insert_str(text),
+                    _ => variables.insert_str(text),
+                }
+            } else if let Some(editor) = app.active_line_editor() {
+                editor.insert_str(text);
+            }
+        }
+        if let Event::Mouse(mouse) = event {
+            handle_mouse_event(terminal, app, mouse).await?;
+        }
+        if let Event::Key(key) = event {
             if key.kind == event::KeyEventKind::Release {
                 // Skip events that are not KeyEventKind::Press
                 continue;
             }
+            if app.notifications.current_error().is_some() {
+                // Errors have to be dismissed before anything else
+                if let Some(Action::Close) = app.keymap.action_for(KeymapContext::Alert, &key) {
+                    app.notifications.dismiss_error();
+                }
+                continue;
+            }
+            if app.show_help {
+                // Any key closes the help overlay
+                app.show_help = false;
+                continue;
+            }
+            let action = app
+                .keymap
+                .action_for(context_for(&app.current_screen), &key);
+            if let Some(Action::ToggleHelp) = action {
+                app.show_help = true;
+                continue;
+            }
             match app.current_screen {
-                CurrentScreen::Main => match key.code {
+                CurrentScreen::Main => match action {
                     // Navigation
-                    KeyCode::Char(']') => app.increment_section(false),
-                    KeyCode::Char('[') => app.increment_section(true),
-                    KeyCode::Char('j') => app.increment_selection(false),
-                    KeyCode::Char('k') => app.increment_selection(true),
+                    Some(Action::NextSection) => app.increment_section(false),
+                    Some(Action::PreviousSection) => app.increment_section(true),
+                    Some(Action::NextItem) => app.increment_selection(false),
+                    Some(Action::PreviousItem) => app.increment_selection(true),
+                    Some(Action::ScrollLeft) => app.scroll_horizontal(true),
+                    Some(Action::ScrollRight) => app.scroll_horizontal(false),
+                    Some(Action::ToggleWrap) => app.toggle_wrap(),
+                    Some(Action::NextTab) => app.switch_tab(false),
+                    Some(Action::PreviousTab) => app.switch_tab(true),
+                    Some(Action::SwitchPane) => app.switch_pane(),
 
                     // Edit values
-                    KeyCode::Tab => {
-                        app.current_screen = CurrentScreen::EndpointInput(app.endpoint.clone());
+                    Some(Action::EditEndpoint) => {
+                        app.current_screen =
+                            CurrentScreen::EndpointInput(LineEditor::new(&app.endpoint));
                     }
-                    KeyCode::Char('a') => {
+                    Some(Action::AddItem) => {
                         app.add_item();
                     }
-                    KeyCode::Char('e') => {
+                    Some(Action::EditItem) => {
                         app.edit_item();
                     }
-                    KeyCode::Char('d') => {
+                    Some(Action::DeleteItem) => {
                         app.delete_item();
                     }
-                    KeyCode::Char('m') => {
+                    Some(Action::EditRawBody) => {
+                        app.open_body_editor();
+                    }
+                    Some(Action::EditRequestExternally) => {
+                        if let Err(err) = edit_request_externally(terminal, app) {
+                            app.notifications
+                                .error("Unable to run the editor", Some(err.to_string()));
+                        }
+                    }
+                    Some(Action::ViewExternally) => {
+                        if let Some(ref response) = app.last_response {
+                            let (text, extension) =
+                                match serde_json::from_str::<serde_json::Value>(&response.body) {
+                                    Ok(json) => (
+                                        serde_json::to_string_pretty(&json)
+                                            .unwrap_or(response.body.clone()),
+                                        "json",
+                                    ),
+                                    Err(_) => (response.body.clone(), "txt"),
+                                };
+                            let result = view_externally(&text, extension);
+                            terminal.clear()?;
+                            if let Err(err) = result {
+                                app.notifications
+                                    .error("Unable to run the pager", Some(err.to_string()));
+                            }
+                        }
+                    }
+                    Some(Action::NextMethod) => {
                         app.increment_method(false);
                     }
-                    KeyCode::Char('n') => {
+                    Some(Action::PreviousMethod) => {
                         app.increment_method(true);
                     }
 
+                    // Response
+                    Some(Action::FilterResponse) => {
+                        app.open_filter_input();
+                    }
+                    Some(Action::CopyValue) => {
+                        app.copy_selected_value();
+                    }
+                    Some(Action::CopySection) => {
+                        app.copy_selected_section();
+                    }
+                    Some(Action::CopyEndpoint) => {
+                        app.copy_endpoint();
+                    }
+                    Some(Action::CopyAsCurl) => {
+                        app.copy_as_curl();
+                    }
+                    Some(Action::ToggleNode) => {
+                        app.toggle_tree_node();
+                    }
+                    Some(Action::ExpandAll) => {
+                        app.expand_all_tree_nodes();
+                    }
+                    Some(Action::CollapseAll) => {
+                        app.collapse_all_tree_nodes();
+                    }
+                    Some(Action::ViewResponse) => {
+                        app.open_pager();
+                    }
+                    Some(Action::PinBaseline) => {
+                        app.pin_baseline();
+                    }
+                    Some(Action::CompareResponses) => {
+                        app.open_diff();
+                    }
+
+                    // Layout
+                    Some(Action::NewTab) => {
+                        app.new_tab();
+                    }
+                    Some(Action::CloseTab) => {
+                        app.close_tab();
+                    }
+                    Some(Action::ToggleLayout) => app.toggle_layout(),
+                    Some(Action::GrowPane) => app.resize_panes(false),
+                    Some(Action::ShrinkPane) => app.resize_panes(true),
+                    Some(Action::ToggleMouseCapture) => {
+                        app.mouse_captured = !app.mouse_captured;
+                        set_mouse_capture(app.mouse_captured)?;
+                    }
+                    Some(Action::ShowAlerts) => {
+                        app.open_alert_log();
+                    }
+
                     // Functions
-                    KeyCode::Enter => app.send_api_request().await,
-                    KeyCode::Char('q') => {
-                        return Ok(None);
+                    Some(Action::SendRequest) => app.send_api_request().await,
+                    Some(Action::SaveRequest) => {
+                        app.open_save_input(false);
+                    }
+                    Some(Action::ExportHar) => app.open_har_export_input(),
+                    Some(Action::ShowHarEntries) => app.show_har_entries(),
+                    Some(Action::ToggleGraphql) => app.toggle_graphql(),
+                    Some(Action::OpenWebSocket) => app.open_websocket(),
+                    Some(Action::BrowseSchema) => {
+                        if app.section_values.graphql.is_none() {
+                            app.notifications.warning(format!(
+                                "Switch to GraphQL with {} to browse the schema",
+                                app.keymap
+                                    .key_for(KeymapContext::Main, Action::ToggleGraphql)
+                                    .unwrap_or_default()
+                            ));
+                        } else if app.schema().is_some() || app.fetch_schema().await {
+                            app.open_schema_browser();
+                        }
+                    }
+                    Some(Action::Quit) | Some(Action::QuitAndPrint) => {
+                        app.print_on_exit = action == Some(Action::QuitAndPrint);
+                        match app.dirty_tabs().is_empty() {
+                            true => return Ok(exit_output(app)),
+                            false => app.current_screen = CurrentScreen::ExitPrompt,
+                        }
                     }
 
                     _ => {}
                 },
-                CurrentScreen::Loading => match key.code {
-                    KeyCode::Char('y') => return Ok(Some(":)".to_string())),
-                    KeyCode::Char('n') | KeyCode::Char('q') => {
-                        return Ok(None);
+                CurrentScreen::ExitPrompt => match action {
+                    Some(Action::Confirm) => {
+                        // Unnamed requests are named first, and quitting carries on afterwards
+                        let is_saved = app.save_dirty_tabs();
+                        if is_saved {
+                            return Ok(exit_output(app));
+                        }
+                    }
+                    Some(Action::Decline) => return Ok(exit_output(app)),
+                    Some(Action::Cancel) => {
+                        app.current_screen = CurrentScreen::Main;
+                    }
+                    _ => {}
+                },
+                CurrentScreen::SaveInput(ref mut input_state) => match action {
+                    Some(Action::Submit) => {
+                        let name = input_state.name.text().trim().to_string();
+                        let then_quit = input_state.then_quit;
+                        if app.would_overwrite(&name) {
+                            app.current_screen = CurrentScreen::OverwritePrompt(SaveInputState {
+                                name: LineEditor::new(&name),
+                                then_quit,
+                            });
+                        } else if app.save_request_as(&name) {
+                            app.current_screen = CurrentScreen::Main;
+                            if then_quit && app.save_dirty_tabs() {
+                                return Ok(exit_output(app));
+                            }
+                        }
+                    }
+                    Some(Action::Cancel) => {
+                        app.current_screen = CurrentScreen::Main;
+                    }
+                    _ => edit_line(&mut input_state.name, action, &key),
+                },
+                CurrentScreen::OverwritePrompt(ref input_state) => match action {
+                    Some(Action::Confirm) => {
+                        let name = input_state.name.text().to_string();
+                        let then_quit = input_state.then_quit;
+                        match app.save_request_as(&name) {
+                            true => {
+                                app.current_screen = CurrentScreen::Main;
+                                if then_quit && app.save_dirty_tabs() {
+                                    return Ok(exit_output(app));
+                                }
+                            }
+                            false => app.open_save_input(then_quit),
+                        }
+                    }
+                    // Choose another name instead
+                    Some(Action::Decline) => {
+                        let name = input_state.name.text().to_string();
+                        app.current_screen = CurrentScreen::SaveInput(SaveInputState {
+                            name: LineEditor::new(&name),
+                            then_quit: input_state.then_quit,
+                        });
+                    }
+                    Some(Action::Cancel) => {
+                        app.current_screen = CurrentScreen::Main;
                     }
                     _ => {}
                 },
-                CurrentScreen::EndpointInput(ref previous_endpoint) => match key.code {
-                    KeyCode::Enter => {
+                CurrentScreen::EndpointInput(ref mut editor) => match action {
+                    Some(Action::Submit) => {
+                        app.endpoint = editor.text().to_string();
+                        app.current_screen = CurrentScreen::Main;
+                    }
+                    Some(Action::Cancel) => {
+                        app.current_screen = CurrentScreen::Main;
+                    }
+                    _ => edit_line(editor, action, &key),
+                },
+                CurrentScreen::FilterInput(ref mut input_state) => match action {
+                    Some(Action::Submit) => {
+                        app.apply_filter_input();
+                    }
+                    Some(Action::Cancel) => {
+                        app.current_screen = CurrentScreen::Main;
+                    }
+                    Some(Action::PreviousFilter) => {
+                        app.cycle_filter_history(true);
+                    }
+                    Some(Action::NextFilter) => {
+                        app.cycle_filter_history(false);
+                    }
+                    _ => edit_line(&mut input_state.expression, action, &key),
+                },
+                CurrentScreen::BodyEditor(ref mut editor_state) => {
+                    // Everything but the tab bar, endpoint, footer, borders and status line
+                    let visible_rows = terminal.size()?.height.saturating_sub(10) as usize;
+                    match action {
+                        Some(Action::Submit) => app.save_body_editor(),
+                        Some(Action::Cancel) => {
+                            app.current_screen = CurrentScreen::Main;
+                        }
+                        Some(Action::EditExternally) => {
+                            let editor = &mut editor_state.editor;
+                            let extension = match editor.json_error() {
+                                None if editor.text().trim_start().starts_with(['{', '[']) => {
+                                    "json"
+                                }
+                                _ => "txt",
+                            };
+                            let result = edit_externally(&editor.text(), extension);
+                            terminal.clear()?;
+                            match result {
+                                Ok(text) => {
+                                    editor.set_text(text.strip_suffix('\n').unwrap_or(&text));
+                                    editor_state.message = None;
+                                }
+                                Err(err) => {
+                                    editor_state.message =
+                                        Some(format!("Unable to run the editor: {}", err));
+                                }
+                            }
+                        }
+                        _ => edit_text(&mut editor_state.editor, action, &key, visible_rows),
+                    }
+                    if let CurrentScreen::BodyEditor(ref mut editor_state) = app.current_screen {
+                        editor_state.editor.scroll_to_cursor(visible_rows);
+                    }
+                }
+                CurrentScreen::GraphqlEditor(ref mut editor_state) => {
+                    // The variables and operation name take 9 rows below the query
+                    let query_rows = terminal.size()?.height.saturating_sub(19) as usize;
+                    let variables_rows = 4;
+                    match action {
+                        Some(Action::Submit) => app.save_graphql_editor(),
+                        Some(Action::Cancel) => {
+                            app.current_screen = CurrentScreen::Main;
+                        }
+                        Some(Action::SwitchField) => app.switch_graphql_field(),
+                        Some(Action::Complete) => {
+                            if app.schema().is_some() || app.fetch_schema().await {
+                                app.complete_graphql_field();
+                            }
+                        }
+                        Some(Action::EditExternally) => {
+                            let (editor, extension) = match editor_state.focus {
+                                GraphqlField::Query => (&mut editor_state.query, "graphql"),
+                                _ => (&mut editor_state.variables, "json"),
+                            };
+                            let result = edit_externally(&editor.text(), extension);
+                            terminal.clear()?;
+                            match result {
+                                Ok(text) => {
+                                    editor.set_text(text.strip_suffix('\n').unwrap_or(&text));
+                                    editor_state.message = None;
+                                }
+                                Err(err) => {
+                                    editor_state.message =
+                                        Some(format!("Unable to run the editor: {}", err));
+                                }
+                            }
+                        }
+                        _ => {
+                            editor_state.message = None;
+                            match editor_state.focus {
+                                GraphqlField::Query => {
+                                    edit_text(&mut editor_state.query, action, &key, query_rows)
+                                }
+                                GraphqlField::Variables => edit_text(
+                                    &mut editor_state.variables,
+                                    action,
+                                    &key,
+                                    variables_rows,
+                                ),
+                                GraphqlField::OperationName => {
+                                    edit_line(&mut editor_state.operation_name, action, &key)
+                                }
+                            }
+                        }
+                    }
+                    if let CurrentScreen::GraphqlEditor(ref mut editor_state) = app.current_screen {
+                        editor_state.query.scroll_to_cursor(query_rows);
+                        editor_state.variables.scroll_to_cursor(variables_rows);
+                    }
+                }
+                CurrentScreen::WebSocket(ref mut websocket_state) => {
+                    let page_height = terminal.size()?.height.saturating_sub(6) as isize;
+                    match action {
+                        Some(Action::Submit) => app.submit_websocket_message(),
+                        Some(Action::Cancel) if websocket_state.is_closing => {
+                            websocket_state.is_closing = false;
+                            websocket_state.composer.clear();
+                        }
+                        // Leaving the screen drops the connection, which closes it
+                        Some(Action::Cancel) => {
+                            app.current_screen = CurrentScreen::Main;
+                        }
+                        Some(Action::SwitchMessageKind) => {
+                            websocket_state.kind = websocket_state.kind.next();
+                            websocket_state.is_closing = false;
+                        }
+                        Some(Action::SendPing) => {
+                            websocket_state.ping();
+                        }
+                        Some(Action::CloseConnection) => {
+                            websocket_state.is_closing = true;
+                            websocket_state.composer.clear();
+                        }
+                        Some(Action::Reconnect) => websocket_state.connect(),
+                        Some(Action::ScrollUp) => websocket_state.scroll_by(1),
+                        Some(Action::ScrollDown) => websocket_state.scroll_by(-1),
+                        Some(Action::PageUp) => websocket_state.scroll_by(page_height),
+                        Some(Action::PageDown) => websocket_state.scroll_by(-page_height),
+                        _ => {
+                            websocket_state.message = None;
+                            edit_line(&mut websocket_state.composer, action, &key)
+                        }
+                    }
+                }
+                CurrentScreen::SchemaBrowser(ref browser_state) => {
+                    let selected = browser_state.selected;
+                    match action {
+                        Some(Action::NextItem) => app.select_schema_item(selected + 1),
+                        Some(Action::PreviousItem) => {
+                            app.select_schema_item(selected.saturating_sub(1))
+                        }
+                        Some(Action::ScrollToTop) => app.select_schema_item(0),
+                        Some(Action::ScrollToBottom) => app.select_schema_item(usize::MAX),
+                        Some(Action::OpenType) => app.open_schema_item(),
+                        Some(Action::GoBack) => app.close_schema_item(),
+                        Some(Action::FetchSchema) => {
+                            app.fetch_schema().await;
+                            app.select_schema_item(selected);
+                        }
+                        Some(Action::Close) => {
+                            app.current_screen = CurrentScreen::Main;
+                        }
+                        _ => {}
+                    }
+                }
+                CurrentScreen::Pager(ref mut pager_state) if pager_state.is_searching => {
+                    let lines = match app.last_response {
+                        Some(ref response) => pager_lines(response, pager_state),
+                        None => Vec::new(),
+                    };
+                    match action {
+                        Some(Action::Submit) => {
+                            pager_state.is_searching = false;
+                            pager_state.update_matches(&lines);
+                        }
+                        Some(Action::Cancel) => {
+                            pager_state.is_searching = false;
+                            pager_state.search.clear();
+                            pager_state.update_matches(&lines);
+                        }
+                        _ => edit_line(&mut pager_state.search, action, &key),
+                    }
+                }
+                CurrentScreen::Pager(ref mut pager_state) => {
+                    let lines = match app.last_response {
+                        Some(ref response) => pager_lines(response, pager_state),
+                        None => Vec::new(),
+                    };
+                    let page_height = terminal.size()?.height.saturating_sub(4) as isize;
+                    match action {
+                        Some(Action::ScrollDown) => pager_state.scroll_by(1, lines.len()),
+                        Some(Action::ScrollUp) => pager_state.scroll_by(-1, lines.len()),
+                        Some(Action::PageDown) => pager_state.scroll_by(page_height, lines.len()),
+                        Some(Action::PageUp) => pager_state.scroll_by(-page_height, lines.len()),
+                        Some(Action::ScrollToTop) => pager_state.scroll = 0,
+                        Some(Action::ScrollToBottom) => {
+                            pager_state.scroll = lines.len().saturating_sub(1)
+                        }
+                        Some(Action::Search) => {
+                            pager_state.is_searching = true;
+                            pager_state.search.clear();
+                        }
+                        Some(Action::NextMatch) => pager_state.jump_to_match(false),
+                        Some(Action::PreviousMatch) => pager_state.jump_to_match(true),
+                        Some(Action::ToggleWrap) => pager_state.wrap = !pager_state.wrap,
+                        Some(Action::TogglePretty) => {
+                            pager_state.pretty = !pager_state.pretty;
+                            pager_state.scroll = 0;
+                            if let Some(ref response) = app.last_response {
+                                pager_state.update_matches(&pager_lines(response, pager_state));
+                            }
+                        }
+                        Some(Action::ViewExternally) => {
+                            let result = view_externally(&lines.join("\n"), "txt");
+                            terminal.clear()?;
+                            if let Err(err) = result {
+                                app.notifications
+                                    .error("Unable to run the pager", Some(err.to_string()));
+                            }
+                        }
+                        Some(Action::SwitchContent) => {
+                            pager_state.content = match pager_state.content {
+                                PagerContent::Body => PagerContent::Headers,
+                                PagerContent::Headers => PagerContent::Body,
+                            };
+                            pager_state.scroll = 0;
+                            if let Some(ref response) = app.last_response {
+                                pager_state.update_matches(&pager_lines(response, pager_state));
+                            }
+                        }
+                        Some(Action::Close) => {
+                            app.current_screen = CurrentScreen::Main;
+                        }
+                        _ => {}
+                    }
+                }
+                CurrentScreen::Diff(_) => match action {
+                    Some(Action::ScrollDown) => app.scroll_diff(1),
+                    Some(Action::ScrollUp) => app.scroll_diff(-1),
+                    Some(Action::OlderBaseline) => app.increment_diff_baseline(true),
+                    Some(Action::NewerBaseline) => app.increment_diff_baseline(false),
+                    Some(Action::PinBaseline) => app.pin_baseline(),
+                    Some(Action::Close) => {
                         app.current_screen = CurrentScreen::Main;
                     }
-                    KeyCode::Backspace => {
-                        app.endpoint.pop();
+                    _ => {}
+                },
+                CurrentScreen::HarEntries(ref mut selected) => {
+                    let last = app.har_entries.len().saturating_sub(1);
+                    match action {
+                        Some(Action::NextItem) => *selected = (*selected + 1).min(last),
+                        Some(Action::PreviousItem) => *selected = selected.saturating_sub(1),
+                        Some(Action::ScrollToTop) => *selected = 0,
+                        Some(Action::ScrollToBottom) => *selected = last,
+                        Some(Action::ReplayEntry) => {
+                            let index = *selected;
+                            app.current_screen = CurrentScreen::Main;
+                            if app.open_har_entry(index) {
+                                app.send_api_request().await;
+                            }
+                        }
+                        Some(Action::Close) => {
+                            app.current_screen = CurrentScreen::Main;
+                        }
+                        _ => {}
+                    }
+                }
+                CurrentScreen::HarExportInput(ref mut editor) => match action {
+                    Some(Action::Submit) => {
+                        let path = editor.text().trim().to_string();
+                        if std::path::Path::new(&path).exists() {
+                            app.current_screen =
+                                CurrentScreen::HarOverwritePrompt(LineEditor::new(&path));
+                        } else if app.export_har(&path) {
+                            app.current_screen = CurrentScreen::Main;
+                        }
                     }
-                    KeyCode::Esc => {
-                        app.endpoint = previous_endpoint.clone();
+                    Some(Action::Cancel) => {
                         app.current_screen = CurrentScreen::Main;
                     }
-                    KeyCode::Char(value) => {
-                        app.endpoint.push(value);
+                    _ => edit_line(editor, action, &key),
+                },
+                CurrentScreen::HarOverwritePrompt(ref editor) => match action {
+                    Some(Action::Confirm) => {
+                        let path = editor.text().to_string();
+                        app.current_screen = match app.export_har(&path) {
+                            true => CurrentScreen::Main,
+                            false => CurrentScreen::HarExportInput(LineEditor::new(&path)),
+                        };
+                    }
+                    // Choose another file instead
+                    Some(Action::Decline) => {
+                        let path = editor.text().to_string();
+                        app.current_screen = CurrentScreen::HarExportInput(LineEditor::new(&path));
+                    }
+                    Some(Action::Cancel) => {
+                        app.current_screen = CurrentScreen::Main;
                     }
                     _ => {}
                 },
+                CurrentScreen::AlertLog(ref mut scroll) => {
+                    let last = app.notifications.log.len().saturating_sub(1);
+                    match action {
+                        Some(Action::ScrollDown) => *scroll = (*scroll + 1).min(last),
+                        Some(Action::ScrollUp) => *scroll = scroll.saturating_sub(1),
+                        Some(Action::ScrollToTop) => *scroll = 0,
+                        Some(Action::ScrollToBottom) => *scroll = last,
+                        Some(Action::Close) => {
+                            app.current_screen = CurrentScreen::Main;
+                        }
+                        _ => {}
+                    }
+                }
                 CurrentScreen::PairInput(ref mut input_state)
                     if key.kind == KeyEventKind::Press =>
                 {
-                    match key.code {
-                        KeyCode::Enter => {
+                    match action {
+                        Some(Action::Submit) => {
                             app.write_item();
                             app.current_screen = CurrentScreen::Main;
                         }
-                        KeyCode::Backspace => match input_state.selected_item {
-                            KeyValuePair::Key => {
-                                input_state.key.pop();
-                            }
-                            KeyValuePair::Value => {
-                                input_state.value.pop();
-                            }
-                        },
-                        KeyCode::Esc => {
+                        Some(Action::Cancel) => {
                             app.current_screen = CurrentScreen::Main;
                         }
-                        KeyCode::Tab => {
+                        Some(Action::SwitchField) => {
                             app.toggle_input_field();
                         }
-                        KeyCode::Char(value) => match input_state.selected_item {
-                            KeyValuePair::Key => {
-                                input_state.key.push(value);
-                            }
-                            KeyValuePair::Value => {
-                                input_state.value.push(value);
-                            }
+                        _ => match input_state.selected_item {
+                            KeyValuePair::Key => edit_line(&mut input_state.key, action, &key),
+                            KeyValuePair::Value => edit_line(&mut input_state.value, action, &key),
                         },
-                        _ => {}
                     }
                 }
                 _ => {}
@@ -0,0 +1,214 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+const TOAST_DURATION: Duration = Duration::from_secs(4);
+const MAX_TOASTS: usize = 3;
+const MAX_LOG_ENTRIES: usize = 500;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Severity {
+    Info,
+    Success,
+    Warning,
+    Error, // Shown in a dialog which has to be dismissed, rather than as a toast
+}
+
+pub struct Notification {
+    pub severity: Severity,
+    pub message: String,
+    pub details: Option<String>,
+    pub time: Instant,
+}
+
+pub struct Notifications {
+    pub log: Vec<Notification>,
+    pending_errors: VecDeque<usize>, // Indices into the log of errors not yet dismissed
+    dropped: usize,                  // How many of the oldest notifications have been dropped
+}
+
+impl Notifications {
+    pub fn new() -> Notifications {
+        Notifications {
+            log: Vec::new(),
+            pending_errors: VecDeque::new(),
+            dropped: 0,
+        }
+    }
+
+    pub fn info(&mut self, message: impl Into<String>) {
+        self.push(Severity::Info, message.into(), None);
+    }
+
+    pub fn success(&mut self, message: impl Into<String>) {
+        self.push(Severity::Success, message.into(), None);
+    }
+
+    pub fn warning(&mut self, message: impl Into<String>) {
+        self.push(Severity::Warning, message.into(), None);
+    }
+
+    pub fn error(&mut self, message: impl Into<String>, details: Option<String>) {
+        self.push(Severity::Error, message.into(), details);
+    }
+
+    fn push(&mut self, severity: Severity, message: String, details: Option<String>) {
+        if severity == Severity::Error {
+            self.pending_errors.push_back(self.log.len());
+        }
+        self.log.push(Notification {
+            severity,
+            message,
+            details,
+            time: Instant::now(),
+        });
+
+        // Drop the oldest notifications, so a long session doesn't keep every one of them
+        let excess = self.log.len().saturating_sub(MAX_LOG_ENTRIES);
+        if excess > 0 {
+            self.log.drain(..excess);
+            self.dropped += excess;
+            self.pending_errors = self
+                .pending_errors
+                .iter()
+                .filter_map(|index| index.checked_sub(excess))
+                .collect();
+        }
+    }
+
+    /// How many notifications there have been, including any which have been dropped
+    pub fn count(&self) -> usize {
+        self.dropped + self.log.len()
+    }
+
+    /// The notifications since there were `count` of them, e.g. those from sending a request
+    pub fn since(&self, count: usize) -> &[Notification] {
+        let start = count.saturating_sub(self.dropped).min(self.log.len());
+        &self.log[start..]
+    }
+
+    /// The oldest error which hasn't been dismissed
+    pub fn current_error(&self) -> Option<&Notification> {
+        self.pending_errors.front().map(|index| &self.log[*index])
+    }
+
+    pub fn pending_error_count(&self) -> usize {
+        self.pending_errors.len()
+    }
+
+    pub fn dismiss_error(&mut self) {
+        self.pending_errors.pop_front();
+    }
+
+    /// The most recent toasts which haven't expired yet, oldest first
+    pub fn visible_toasts(&self) -> Vec<&Notification> {
+        let mut toasts: Vec<&Notification> = self
+            .log
+            .iter()
+            .rev()
+            .take_while(|notification| notification.time.elapsed() < TOAST_DURATION)
+            .filter(|notification| notification.severity != Severity::Error)
+            .take(MAX_TOASTS)
+            .collect();
+        toasts.reverse();
+        toasts
+    }
+}
+
+impl Severity {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Severity::Info => "Info",
+            Severity::Success => "Success",
+            Severity::Warning => "Warning",
+            Severity::Error => "Error",
+        }
+    }
+}
+
+/// How long ago something happened, e.g. "5s ago" or "3m ago"
+pub fn describe_age(time: Instant) -> String {
+    let seconds = time.elapsed().as_secs();
+    match seconds {
+        0..=59 => format!("{}s ago", seconds),
+        60..=3599 => format!("{}m ago", seconds / 60),
+        _ => format!("{}h ago", seconds / 3600),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn messages(notifications: &[&Notification]) -> Vec<String> {
+        notifications
+            .iter()
+            .map(|notification| notification.message.clone())
+            .collect()
+    }
+
+    #[test]
+    fn toasts_are_the_latest_non_errors_oldest_first() {
+        let mut notifications = Notifications::new();
+        notifications.info("one");
+        notifications.success("two");
+        notifications.error("three", None);
+        notifications.warning("four");
+        notifications.info("five");
+        assert_eq!(
+            messages(&notifications.visible_toasts()),
+            vec!["two", "four", "five"]
+        );
+        let severities: Vec<&str> = notifications
+            .log
+            .iter()
+            .map(|notification| notification.severity.label())
+            .collect();
+        assert_eq!(
+            severities,
+            vec!["Info", "Success", "Error", "Warning", "Info"]
+        );
+    }
+
+    #[test]
+    fn errors_are_dismissed_oldest_first() {
+        let mut notifications = Notifications::new();
+        assert!(notifications.current_error().is_none());
+        notifications.error("first", Some("details".to_string()));
+        notifications.warning("not an error");
+        notifications.error("second", None);
+        assert_eq!(notifications.pending_error_count(), 2);
+        let error = notifications.current_error().unwrap();
+        assert_eq!(error.message, "first");
+        assert_eq!(error.details.as_deref(), Some("details"));
+
+        notifications.dismiss_error();
+        assert_eq!(notifications.current_error().unwrap().message, "second");
+        notifications.dismiss_error();
+        assert!(notifications.current_error().is_none());
+        assert_eq!(notifications.pending_error_count(), 0);
+        // Dismissed errors stay in the log
+        assert_eq!(notifications.log.len(), 3);
+    }
+
+    #[test]
+    fn the_log_is_capped() {
+        let mut notifications = Notifications::new();
+        notifications.error("dropped", None);
+        notifications.error("kept", None);
+        for index in 0..MAX_LOG_ENTRIES - 1 {
+            notifications.info(format!("info {}", index));
+        }
+        assert_eq!(notifications.log.len(), MAX_LOG_ENTRIES);
+        assert_eq!(notifications.count(), MAX_LOG_ENTRIES + 1);
+        assert_eq!(notifications.log[0].message, "kept");
+        // The pending errors still point at the right notifications
+        assert_eq!(notifications.pending_error_count(), 1);
+        assert_eq!(notifications.current_error().unwrap().message, "kept");
+
+        let count = notifications.count();
+        notifications.warning("latest");
+        let latest: Vec<&Notification> = notifications.since(count).iter().collect();
+        assert_eq!(messages(&latest), vec!["latest"]);
+        assert_eq!(notifications.since(0).len(), MAX_LOG_ENTRIES);
+    }
+}
@@ -0,0 +1,217 @@
+use crate::http_request::HttpResponse;
+use crate::line_editor::LineEditor;
+use serde_json::value::Value as JsonValue;
+
+pub enum PagerContent {
+    Body,
+    Headers,
+}
+
+pub struct PagerState {
+    pub content: PagerContent,
+    pub scroll: usize,
+    pub wrap: bool,
+    pub pretty: bool,
+    pub search: LineEditor,
+    pub is_searching: bool,  // Whether the search query is being typed
+    pub matches: Vec<usize>, // Line numbers which contain the search query
+    pub current_match: Option<usize>,
+}
+
+impl PagerState {
+    pub fn new() -> PagerState {
+        PagerState {
+            content: PagerContent::Body,
+            scroll: 0,
+            wrap: false,
+            pretty: false,
+            search: LineEditor::new(""),
+            is_searching: false,
+            matches: Vec::new(),
+            current_match: None,
+        }
+    }
+
+    pub fn scroll_by(&mut self, amount: isize, line_count: usize) {
+        let max_scroll = line_count.saturating_sub(1);
+        self.scroll = self.scroll.saturating_add_signed(amount).min(max_scroll);
+    }
+
+    /// Recalculate the matching lines and jump to the first match at or after the current line
+    pub fn update_matches(&mut self, lines: &[String]) {
+        self.matches = find_matches(lines, self.search.text());
+        self.current_match = self
+            .matches
+            .iter()
+            .position(|line_number| *line_number >= self.scroll)
+            .or(match self.matches.is_empty() {
+                true => None,
+                false => Some(0),
+            });
+        if let Some(current_match) = self.current_match {
+            self.scroll = self.matches[current_match];
+        }
+    }
+
+    pub fn jump_to_match(&mut self, reverse: bool) {
+        if self.matches.is_empty() {
+            return;
+        }
+        let last_match = self.matches.len() - 1;
+        let new_match = match (self.current_match, reverse) {
+            (Some(0), true) | (None, true) => last_match,
+            (Some(index), true) => index - 1,
+            (Some(index), false) if index < last_match => index + 1,
+            (Some(_), false) | (None, false) => 0,
+        };
+        self.current_match = Some(new_match);
+        self.scroll = self.matches[new_match];
+    }
+}
+
+impl std::fmt::Display for PagerContent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PagerContent::Body => write!(f, "Response Body"),
+            PagerContent::Headers => write!(f, "Response Headers"),
+        }
+    }
+}
+
+/// The lines of the response exactly as received, or pretty printed if requested and possible.
+/// Lines ending in `\r\n` keep their `\r`.
+pub fn pager_lines(response: &HttpResponse, state: &PagerState) -> Vec<String> {
+    let text = match state.content {
+        PagerContent::Body => match state.pretty {
+            true => match serde_json::from_str::<JsonValue>(&response.body) {
+                Ok(json) => serde_json::to_string_pretty(&json).unwrap_or(response.body.clone()),
+                Err(_) => response.body.clone(),
+            },
+            false => response.body.clone(),
+        },
+        PagerContent::Headers => {
+            let mut text = format!("HTTP {}\n", response.status);
+            for (key, value) in response.headers.iter() {
+                text.push_str(&format!("{}: {}\n", key, value));
+            }
+            text
+        }
+    };
+    text.split('\n').map(str::to_string).collect()
+}
+
+pub fn find_matches(lines: &[String], query: &str) -> Vec<usize> {
+    if query.is_empty() {
+        return Vec::new();
+    }
+    let query = query.to_lowercase();
+    lines
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| line.to_lowercase().contains(&query))
+        .map(|(line_number, _)| line_number)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http_request::{SentRequest, Timings};
+    use std::time::{Duration, SystemTime};
+
+    fn lines(text: &str) -> Vec<String> {
+        text.lines().map(str::to_string).collect()
+    }
+
+    fn searched(query: &str, scroll: usize) -> PagerState {
+        let mut state = PagerState::new();
+        state.search = LineEditor::new(query);
+        state.scroll = scroll;
+        state.update_matches(&lines("Alpha\nbeta\nALPHA beta\ngamma\nalphabet"));
+        state
+    }
+
+    #[test]
+    fn finds_matches_ignoring_case() {
+        let lines = lines("Alpha\nbeta\nALPHA beta\ngamma\nalphabet");
+        assert_eq!(find_matches(&lines, "alpha"), vec![0, 2, 4]);
+        assert_eq!(find_matches(&lines, "BETA"), vec![1, 2]);
+        assert_eq!(find_matches(&lines, "delta"), Vec::<usize>::new());
+        assert_eq!(find_matches(&lines, ""), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn starts_at_the_first_match_from_the_current_line() {
+        let state = searched("alpha", 1);
+        assert_eq!(state.current_match, Some(1));
+        assert_eq!(state.scroll, 2);
+
+        // Wraps around to the first match when there are none further down
+        let state = searched("beta", 3);
+        assert_eq!(state.current_match, Some(0));
+        assert_eq!(state.scroll, 1);
+    }
+
+    #[test]
+    fn no_matches() {
+        let mut state = searched("delta", 2);
+        assert!(state.matches.is_empty());
+        assert_eq!(state.current_match, None);
+        assert_eq!(state.scroll, 2);
+        state.jump_to_match(false);
+        state.jump_to_match(true);
+        assert_eq!(state.current_match, None);
+        assert_eq!(state.scroll, 2);
+    }
+
+    #[test]
+    fn jumps_between_matches_with_wrap_around() {
+        let mut state = searched("alpha", 0);
+        assert_eq!(state.matches, vec![0, 2, 4]);
+        assert_eq!(state.current_match, Some(0));
+        state.jump_to_match(false);
+        assert_eq!(state.scroll, 2);
+        state.jump_to_match(false);
+        assert_eq!(state.scroll, 4);
+        state.jump_to_match(false);
+        assert_eq!(state.scroll, 0);
+        state.jump_to_match(true);
+        assert_eq!(state.scroll, 4);
+        assert_eq!(state.current_match, Some(2));
+        state.jump_to_match(true);
+        assert_eq!(state.scroll, 2);
+    }
+
+    #[test]
+    fn keeps_carriage_returns() {
+        let body = "line one\r\nline two\r\n";
+        let response = HttpResponse {
+            status: 200,
+            http_version: "HTTP/1.1".to_string(),
+            headers: vec![("Content-Type".to_string(), "text/plain".to_string())],
+            body: body.to_string(),
+            body_bytes: body.as_bytes().to_vec(),
+            request: SentRequest {
+                method: "GET".to_string(),
+                url: "https://example.com".to_string(),
+                headers: Vec::new(),
+                body: None,
+            },
+            timings: Timings {
+                started: SystemTime::now(),
+                wait: Duration::ZERO,
+                receive: Duration::ZERO,
+            },
+        };
+        let mut state = PagerState::new();
+        assert_eq!(
+            pager_lines(&response, &state),
+            vec!["line one\r", "line two\r", ""]
+        );
+        state.content = PagerContent::Headers;
+        assert_eq!(
+            pager_lines(&response, &state),
+            vec!["HTTP 200", "Content-Type: text/plain", ""]
+        );
+    }
+}
@@ -0,0 +1,337 @@
+use reqwest::Url;
+use std::error::Error;
+use std::fmt::Display;
+use std::io;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum RequestErrorKind {
+    InvalidUrl,
+    InvalidRequest, // Anything else which stopped the request being built, e.g. a bad header
+    Dns,
+    ConnectionRefused,
+    Timeout,
+    Tls,
+    RedirectLoop,
+    BodyDecode,
+    Other,
+}
+
+/// Why a request failed, with enough context to explain it and suggest a fix
+pub struct RequestError {
+    pub kind: RequestErrorKind,
+    pub endpoint: String,
+    pub chain: Vec<String>, // The error followed by each of its causes
+}
+
+impl RequestError {
+    pub fn new(endpoint: &str, err: &reqwest::Error) -> RequestError {
+        let mut chain = Vec::<String>::new();
+        let mut io_kind = None;
+        let mut source: Option<&dyn Error> = Some(err);
+        while let Some(cause) = source {
+            let description = cause.to_string();
+            if chain.last() != Some(&description) {
+                chain.push(description);
+            }
+            if let Some(io_error) = cause.downcast_ref::<io::Error>() {
+                io_kind = Some(io_error.kind());
+            }
+            source = cause.source();
+        }
+        // Most errors repeat their cause at the end of their own description
+        let chain: Vec<String> = chain
+            .iter()
+            .zip(chain.iter().skip(1).map(Some).chain([None]))
+            .map(|(description, cause)| {
+                match cause.and_then(|cause| description.strip_suffix(cause.as_str())) {
+                    Some(own_part) if !own_part.trim().is_empty() => {
+                        own_part.trim_end_matches([' ', ':']).to_string()
+                    }
+                    _ => description.clone(),
+                }
+            })
+            .collect();
+
+        let mentions = |words: &[&str]| {
+            chain.iter().any(|description| {
+                let description = description.to_lowercase();
+                words.iter().any(|word| description.contains(word))
+            })
+        };
+        let kind = if Url::parse(endpoint).is_err() || mentions(&["relative url", "url scheme"]) {
+            RequestErrorKind::InvalidUrl
+        } else if err.is_builder() {
+            RequestErrorKind::InvalidRequest
+        } else if err.is_timeout() || io_kind == Some(io::ErrorKind::TimedOut) {
+            RequestErrorKind::Timeout
+        } else if err.is_redirect() {
+            RequestErrorKind::RedirectLoop
+        } else if err.is_decode() || err.is_body() {
+            RequestErrorKind::BodyDecode
+        } else if mentions(&["dns error", "failed to lookup address"]) {
+            RequestErrorKind::Dns
+        } else if io_kind == Some(io::ErrorKind::ConnectionRefused) {
+            RequestErrorKind::ConnectionRefused
+        } else if mentions(&["certificate", "handshake", "tls", "ssl"]) {
+            RequestErrorKind::Tls
+        } else {
+            RequestErrorKind::Other
+        };
+
+        RequestError {
+            kind,
+            endpoint: endpoint.to_string(),
+            chain,
+        }
+    }
+
+    pub fn explanation(&self) -> &'static str {
+        match self.kind {
+            RequestErrorKind::InvalidUrl => "The endpoint isn't a valid URL.",
+            RequestErrorKind::InvalidRequest => {
+                "The request couldn't be built, so it was never sent."
+            }
+            RequestErrorKind::Dns => "The host name couldn't be resolved to an address.",
+            RequestErrorKind::ConnectionRefused => {
+                "The host was reached, but nothing accepted the connection on that port."
+            }
+            RequestErrorKind::Timeout => "The server didn't respond in time.",
+            RequestErrorKind::Tls => "A secure connection couldn't be established.",
+            RequestErrorKind::RedirectLoop => {
+                "The server redirected too many times, probably in a loop."
+            }
+            RequestErrorKind::BodyDecode => "The response body couldn't be read or decoded.",
+            RequestErrorKind::Other => "The request failed.",
+        }
+    }
+
+    /// Things worth trying, most likely first
+    pub fn suggestions(&self) -> Vec<String> {
+        let url = Url::parse(&self.endpoint).ok();
+        let scheme = url.as_ref().map(|url| url.scheme()).unwrap_or_default();
+        let with_scheme = |new_scheme: &str| match self.endpoint.split_once("://") {
+            Some((_, rest)) => format!("{}://{}", new_scheme, rest),
+            None => self.endpoint.clone(),
+        };
+
+        let mut suggestions = Vec::<String>::new();
+        match self.kind {
+            RequestErrorKind::InvalidUrl => {
+                if !self.endpoint.contains("://") {
+                    let is_local = ["localhost", "127.0.0.1", "[::1]"]
+                        .iter()
+                        .any(|host| self.endpoint.starts_with(host));
+                    let scheme = match is_local {
+                        true => "http",
+                        false => "https",
+                    };
+                    suggestions.push(format!(
+                        "Did you mean {}://{}?",
+                        scheme,
+                        self.endpoint.trim()
+                    ));
+                }
+                if self.endpoint.trim().contains(' ') {
+                    suggestions.push("Remove the spaces, or encode them as %20".to_string());
+                }
+                suggestions.push("Check the endpoint for typos".to_string());
+            }
+            RequestErrorKind::InvalidRequest => {
+                suggestions.push(
+                    "Check the header names and values for spaces or special characters"
+                        .to_string(),
+                );
+            }
+            RequestErrorKind::Dns => {
+                suggestions.push("Check the host name for typos".to_string());
+                suggestions.push("Check your network connection, VPN and DNS settings".to_string());
+            }
+            RequestErrorKind::ConnectionRefused => {
+                let port = url.as_ref().and_then(|url| url.port_or_known_default());
+                match port {
+                    Some(port) => suggestions.push(format!(
+                        "Check that the server is running and listening on port {}",
+                        port
+                    )),
+                    None => suggestions.push("Check that the server is running".to_string()),
+                }
+                match (scheme, port) {
+                    ("https", Some(port)) if port != 443 => {
+                        suggestions.push(format!("Did you mean {}?", with_scheme("http")))
+                    }
+                    ("http", Some(443)) => {
+                        suggestions.push(format!("Did you mean {}?", with_scheme("https")))
+                    }
+                    _ => {}
+                }
+            }
+            RequestErrorKind::Timeout => {
+                suggestions.push("Check that the host and port are correct".to_string());
+                suggestions.push(
+                    "If the server is just slow, raise request.timeout_seconds in the config"
+                        .to_string(),
+                );
+                suggestions.push("A firewall or VPN may be dropping the connection".to_string());
+            }
+            RequestErrorKind::Tls => {
+                if scheme == "https" {
+                    suggestions.push(format!(
+                        "If the server only speaks plain HTTP, did you mean {}?",
+                        with_scheme("http")
+                    ));
+                }
+                suggestions.push(
+                    "Check that the certificate is valid for this host name and hasn't expired"
+                        .to_string(),
+                );
+            }
+            RequestErrorKind::RedirectLoop => {
+                suggestions.push(
+                    "Check where the server redirects to with its Location header".to_string(),
+                );
+            }
+            RequestErrorKind::BodyDecode => {
+                suggestions.push(
+                    "Check the Content-Encoding and charset the server responds with".to_string(),
+                );
+            }
+            RequestErrorKind::Other => {}
+        }
+        suggestions
+    }
+
+    /// The explanation, the chain of causes and the suggestions, for an error dialog
+    pub fn details(&self) -> String {
+        let mut details = vec![self.explanation().to_string()];
+        if !self.chain.is_empty() {
+            details.push(String::new());
+            details.extend(
+                self.chain
+                    .iter()
+                    .enumerate()
+                    .map(|(index, description)| match index {
+                        0 => description.clone(),
+                        _ => format!("  caused by: {}", description),
+                    }),
+            );
+        }
+        let suggestions = self.suggestions();
+        if !suggestions.is_empty() {
+            details.push(String::new());
+            details.extend(
+                suggestions
+                    .iter()
+                    .map(|suggestion| format!("• {}", suggestion)),
+            );
+        }
+        details.join("\n")
+    }
+}
+
+impl Display for RequestError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.kind {
+            RequestErrorKind::InvalidUrl => write!(f, "Invalid URL"),
+            RequestErrorKind::InvalidRequest => write!(f, "Invalid request"),
+            RequestErrorKind::Dns => write!(f, "DNS lookup failed"),
+            RequestErrorKind::ConnectionRefused => write!(f, "Connection refused"),
+            RequestErrorKind::Timeout => write!(f, "Request timed out"),
+            RequestErrorKind::Tls => write!(f, "TLS handshake failed"),
+            RequestErrorKind::RedirectLoop => write!(f, "Too many redirects"),
+            RequestErrorKind::BodyDecode => write!(f, "Unable to read the response body"),
+            RequestErrorKind::Other => write!(f, "Unable to send the request"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http_request::{make_http_request, HttpMethod, RequestBody};
+    use serde_json::value::Value as JsonValue;
+    use std::collections::HashMap;
+    use std::time::Duration;
+
+    async fn send(endpoint: &str, headers: &[(&str, &str)], timeout: Duration) -> RequestError {
+        let headers: HashMap<String, JsonValue> = headers
+            .iter()
+            .map(|(key, value)| (key.to_string(), JsonValue::String(value.to_string())))
+            .collect();
+        let body = RequestBody::Raw(String::new());
+        match make_http_request(
+            endpoint.to_string(),
+            HttpMethod::GET,
+            headers,
+            body,
+            timeout,
+        )
+        .await
+        {
+            Ok(_) => panic!("{} should have failed", endpoint),
+            Err(err) => err,
+        }
+    }
+
+    #[tokio::test]
+    async fn connection_refused() {
+        let err = send("http://127.0.0.1:1/", &[], Duration::from_secs(5)).await;
+        assert_eq!(err.kind, RequestErrorKind::ConnectionRefused);
+        assert_eq!(
+            err.suggestions(),
+            vec!["Check that the server is running and listening on port 1"]
+        );
+    }
+
+    #[tokio::test]
+    async fn invalid_url() {
+        let err = send("localhost:8080/users", &[], Duration::from_secs(5)).await;
+        assert_eq!(err.kind, RequestErrorKind::InvalidUrl);
+        assert_eq!(
+            err.suggestions(),
+            vec![
+                "Did you mean http://localhost:8080/users?",
+                "Check the endpoint for typos"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn bad_header_name() {
+        let err = send(
+            "http://127.0.0.1:1/",
+            &[("bad header", "value")],
+            Duration::from_secs(5),
+        )
+        .await;
+        assert_eq!(err.kind, RequestErrorKind::InvalidRequest);
+        assert_eq!(
+            err.suggestions(),
+            vec!["Check the header names and values for spaces or special characters"]
+        );
+    }
+
+    #[tokio::test]
+    async fn timeout() {
+        // Accept the connection but never respond
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}/", listener.local_addr().unwrap());
+        let server = tokio::spawn(async move {
+            let (_stream, _) = listener.accept().await.unwrap();
+            tokio::time::sleep(Duration::from_secs(5)).await;
+        });
+        let err = send(&endpoint, &[], Duration::from_millis(200)).await;
+        server.abort();
+        assert_eq!(err.kind, RequestErrorKind::Timeout);
+        assert_eq!(err.to_string(), "Request timed out");
+    }
+
+    #[test]
+    fn suggests_the_other_scheme() {
+        let err = RequestError {
+            kind: RequestErrorKind::ConnectionRefused,
+            endpoint: "https://localhost:8080/".to_string(),
+            chain: Vec::new(),
+        };
+        assert_eq!(err.suggestions()[1], "Did you mean http://localhost:8080/?");
+    }
+}
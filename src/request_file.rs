@@ -0,0 +1,201 @@
+use crate::http_request::HttpMethod;
+use serde_json::value::Value as JsonValue;
+
+/// A request as plain text, laid out like an HTTP message:
+///
+/// ```text
+/// POST https://example.com/items
+/// Content-Type: application/json
+///
+/// {"name": "tapir"}
+/// ```
+pub struct RequestFile {
+    pub method: HttpMethod,
+    pub endpoint: String,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl std::fmt::Display for RequestFile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{} {}", self.method, self.endpoint)?;
+        for (key, value) in self.headers.iter() {
+            writeln!(f, "{}: {}", key, value)?;
+        }
+        if !self.body.is_empty() {
+            writeln!(f)?;
+            writeln!(f, "{}", self.body)?;
+        }
+        Ok(())
+    }
+}
+
+/// Parse a request, ignoring `#` comment lines before the request line
+pub fn parse_request(text: &str) -> Result<RequestFile, String> {
+    let mut lines = text
+        .lines()
+        .skip_while(|line| line.trim().is_empty() || line.starts_with('#'));
+
+    let request_line = lines.next().ok_or("The request is empty")?;
+    let (method, endpoint) = match request_line.trim().split_once(char::is_whitespace) {
+        Some((method, endpoint)) => (method.parse::<HttpMethod>()?, endpoint.trim()),
+        None => (request_line.trim().parse::<HttpMethod>()?, ""),
+    };
+
+    let mut headers = Vec::<(String, String)>::new();
+    for line in lines.by_ref() {
+        if line.trim().is_empty() {
+            break;
+        }
+        match line.split_once(':') {
+            Some((key, value)) if !key.trim().is_empty() => {
+                headers.push((key.trim().to_string(), value.trim().to_string()))
+            }
+            _ => {
+                return Err(format!(
+                    "Invalid header \"{}\", expected \"Name: value\"",
+                    line
+                ))
+            }
+        }
+    }
+
+    let body = lines
+        .collect::<Vec<&str>>()
+        .join("\n")
+        .trim_end()
+        .to_string();
+    Ok(RequestFile {
+        method,
+        endpoint: endpoint.to_string(),
+        headers,
+        body,
+    })
+}
+
+/// The request as a curl command which can be pasted into a shell
+pub fn curl_command(request: &RequestFile) -> String {
+    let mut command = format!(
+        "curl -X {} {}",
+        request.method,
+        shell_quote(&request.endpoint)
+    );
+    for (key, value) in request.headers.iter() {
+        command.push_str(&format!(
+            " -H {}",
+            shell_quote(&format!("{}: {}", key, value))
+        ));
+    }
+    if !request.body.is_empty() {
+        let has_content_type = request
+            .headers
+            .iter()
+            .any(|(key, _)| key.eq_ignore_ascii_case("content-type"));
+        // Send JSON compactly, with the content type tapir itself would send
+        let body = match serde_json::from_str::<JsonValue>(&request.body) {
+            Ok(json) => {
+                if !has_content_type {
+                    command.push_str(" -H 'content-type: application/json'");
+                }
+                json.to_string()
+            }
+            Err(_) => request.body.clone(),
+        };
+        command.push_str(&format!(" --data-raw {}", shell_quote(&body)));
+    }
+    command
+}
+
+fn shell_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', "'\\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(method: HttpMethod, headers: &[(&str, &str)], body: &str) -> RequestFile {
+        RequestFile {
+            method,
+            endpoint: "https://example.com/items?q=a b".to_string(),
+            headers: headers
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect(),
+            body: body.to_string(),
+        }
+    }
+
+    #[test]
+    fn parses_a_request() {
+        let text = "# Edit the request\n\nPOST https://example.com/items\nContent-Type: application/json\nX-Time: 12:00\n\n{\n  \"name\": \"tapir\"\n}\n\n";
+        let parsed = parse_request(text).unwrap();
+        assert_eq!(parsed.method, HttpMethod::POST);
+        assert_eq!(parsed.endpoint, "https://example.com/items");
+        assert_eq!(
+            parsed.headers,
+            vec![
+                ("Content-Type".to_string(), "application/json".to_string()),
+                ("X-Time".to_string(), "12:00".to_string()),
+            ]
+        );
+        assert_eq!(parsed.body, "{\n  \"name\": \"tapir\"\n}");
+    }
+
+    #[test]
+    fn round_trips_through_display() {
+        let original = request(HttpMethod::PUT, &[("Accept", "*/*")], "a\nb");
+        let parsed = parse_request(&original.to_string()).unwrap();
+        assert_eq!(parsed.method, original.method);
+        assert_eq!(parsed.endpoint, original.endpoint);
+        assert_eq!(parsed.headers, original.headers);
+        assert_eq!(parsed.body, original.body);
+    }
+
+    #[test]
+    fn invalid_requests() {
+        assert_eq!(
+            parse_request("# only a comment\n").err(),
+            Some("The request is empty".to_string())
+        );
+        assert!(parse_request("FETCH https://example.com").is_err());
+        assert_eq!(
+            parse_request("GET https://example.com\nno colon").err(),
+            Some("Invalid header \"no colon\", expected \"Name: value\"".to_string())
+        );
+    }
+
+    #[test]
+    fn curl_without_a_body() {
+        assert_eq!(
+            curl_command(&request(HttpMethod::GET, &[("Accept", "*/*")], "")),
+            "curl -X GET 'https://example.com/items?q=a b' -H 'Accept: */*'"
+        );
+    }
+
+    #[test]
+    fn curl_sends_json_compactly() {
+        assert_eq!(
+            curl_command(&request(HttpMethod::POST, &[], "{\n  \"a\": [1, 2]\n}")),
+            "curl -X POST 'https://example.com/items?q=a b' -H 'content-type: application/json' --data-raw '{\"a\":[1,2]}'"
+        );
+        // The request's own content type is kept
+        assert_eq!(
+            curl_command(&request(
+                HttpMethod::POST,
+                &[("Content-Type", "application/vnd.api+json")],
+                "{}"
+            )),
+            "curl -X POST 'https://example.com/items?q=a b' -H 'Content-Type: application/vnd.api+json' --data-raw '{}'"
+        );
+    }
+
+    #[test]
+    fn curl_quotes_for_the_shell() {
+        assert_eq!(shell_quote("it's $HOME"), r"'it'\''s $HOME'");
+        assert_eq!(
+            curl_command(&request(HttpMethod::PATCH, &[], "don't `run` $(this)")),
+            r"curl -X PATCH 'https://example.com/items?q=a b' --data-raw 'don'\''t `run` $(this)'"
+        );
+    }
+}
@@ -0,0 +1,151 @@
+use crate::graphql::GraphqlRequest;
+use crate::http_file::HttpFileSource;
+use crate::http_request::HttpMethod;
+use serde::{Deserialize, Serialize};
+use serde_json::value::Value as JsonValue;
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Everything needed to send a request again
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedRequest {
+    pub method: HttpMethod,
+    pub endpoint: String,
+    #[serde(default)]
+    pub request_headers: HashMap<String, JsonValue>,
+    #[serde(default)]
+    pub request_body: HashMap<String, JsonValue>,
+    #[serde(default)]
+    pub raw_request_body: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub graphql: Option<GraphqlRequest>,
+}
+
+impl SavedRequest {
+    /// Whether there's nothing worth keeping, as in a new tab
+    pub fn is_empty(&self) -> bool {
+        self.endpoint.is_empty()
+            && self.request_headers.is_empty()
+            && self.request_body.is_empty()
+            && self.raw_request_body.is_none()
+            && self.graphql.is_none()
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SavedTab {
+    #[serde(flatten)]
+    pub request: SavedRequest,
+    #[serde(default)]
+    pub saved_name: Option<String>, // The saved request the tab was last saved as or opened from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub http_file: Option<HttpFileSource>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SavedTabs {
+    pub tabs: Vec<SavedTab>,
+    pub active_tab: usize,
+}
+
+/// The directory in which tapir keeps its state between sessions
+pub fn data_directory() -> Option<PathBuf> {
+    dirs::data_dir().map(|directory| directory.join("tapir"))
+}
+
+/// The tabs left open last time, if there are any
+pub fn load_tabs() -> io::Result<Option<SavedTabs>> {
+    let Some(directory) = data_directory() else {
+        return Ok(None);
+    };
+    let contents = match fs::read_to_string(directory.join("tabs.json")) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err),
+    };
+    Ok(Some(serde_json::from_str(&contents)?))
+}
+
+/// Move a tabs file which couldn't be loaded out of the way, returning where it went
+pub fn set_aside_tabs() -> io::Result<PathBuf> {
+    let directory = data_directory().ok_or(io::Error::new(
+        io::ErrorKind::NotFound,
+        "Unable to locate a data directory",
+    ))?;
+    let backup_path = directory.join("tabs.json.bak");
+    fs::rename(directory.join("tabs.json"), &backup_path)?;
+    Ok(backup_path)
+}
+
+pub fn save_tabs(saved_tabs: &SavedTabs) -> io::Result<()> {
+    let directory = data_directory().ok_or(io::Error::new(
+        io::ErrorKind::NotFound,
+        "Unable to locate a data directory",
+    ))?;
+    fs::create_dir_all(&directory)?;
+    fs::write(
+        directory.join("tabs.json"),
+        serde_json::to_string_pretty(saved_tabs)?,
+    )
+}
+
+// A collection of saved requests chosen on the command line, used instead of the default one
+static REQUESTS_DIRECTORY: OnceLock<PathBuf> = OnceLock::new();
+
+pub fn set_requests_directory(directory: PathBuf) {
+    let _ = REQUESTS_DIRECTORY.set(directory);
+}
+
+/// The directory of requests saved by name
+pub fn requests_directory() -> Option<PathBuf> {
+    match REQUESTS_DIRECTORY.get() {
+        Some(directory) => Some(directory.clone()),
+        None => data_directory().map(|directory| directory.join("requests")),
+    }
+}
+
+/// Check a name can be used as a file name in the requests directory
+pub fn validate_request_name(name: &str) -> Result<(), String> {
+    if name.trim().is_empty() {
+        return Err("Enter a name for the request".to_string());
+    }
+    if name.starts_with('.') || name.contains(['/', '\\']) {
+        return Err(format!(
+            "\"{}\" can't start with a dot or contain slashes",
+            name
+        ));
+    }
+    Ok(())
+}
+
+pub fn request_exists(name: &str) -> bool {
+    requests_directory().is_some_and(|directory| directory.join(format!("{}.json", name)).exists())
+}
+
+pub fn load_request(name: &str) -> io::Result<SavedRequest> {
+    let directory = requests_directory().ok_or(io::Error::new(
+        io::ErrorKind::NotFound,
+        "Unable to locate a data directory",
+    ))?;
+    load_request_from(&directory, name)
+}
+
+pub fn load_request_from(directory: &Path, name: &str) -> io::Result<SavedRequest> {
+    let contents = fs::read_to_string(directory.join(format!("{}.json", name)))?;
+    Ok(serde_json::from_str(&contents)?)
+}
+
+pub fn save_request(name: &str, request: &SavedRequest) -> io::Result<()> {
+    let directory = requests_directory().ok_or(io::Error::new(
+        io::ErrorKind::NotFound,
+        "Unable to locate a data directory",
+    ))?;
+    fs::create_dir_all(&directory)?;
+    fs::write(
+        directory.join(format!("{}.json", name)),
+        serde_json::to_string_pretty(request)?,
+    )
+}
@@ -0,0 +1,111 @@
+use crossterm::{
+    event::{DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture},
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use std::env;
+use std::fs;
+use std::io::{self, IsTerminal, Write};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use tempfile::NamedTempFile;
+
+// Whether the mouse should be captured when the TUI is (re)entered
+static MOUSE_CAPTURE: AtomicBool = AtomicBool::new(true);
+
+pub fn setup_terminal() -> io::Result<()> {
+    enable_raw_mode()?;
+    // This is a special case. Normally using stdout is fine
+    execute!(io::stderr(), EnterAlternateScreen, EnableBracketedPaste)?;
+    set_mouse_capture(MOUSE_CAPTURE.load(Ordering::Relaxed))
+}
+
+/// Capture the mouse, or release it so that the terminal's own text selection works
+pub fn set_mouse_capture(enabled: bool) -> io::Result<()> {
+    MOUSE_CAPTURE.store(enabled, Ordering::Relaxed);
+    match enabled {
+        true => execute!(io::stderr(), EnableMouseCapture),
+        false => execute!(io::stderr(), DisableMouseCapture),
+    }
+}
+
+pub fn restore_terminal() -> io::Result<()> {
+    disable_raw_mode()?;
+    execute!(
+        io::stderr(),
+        LeaveAlternateScreen,
+        DisableMouseCapture,
+        DisableBracketedPaste
+    )
+}
+
+/// Edit text in `$VISUAL` or `$EDITOR`, handing the terminal over until it exits
+pub fn edit_externally(text: &str, extension: &str) -> io::Result<String> {
+    let editor = env::var("VISUAL")
+        .or_else(|_| env::var("EDITOR"))
+        .unwrap_or("vi".to_string());
+    let file = temporary_file(extension, text)?;
+
+    let status = run_suspended(&editor, &file.path().to_string_lossy());
+    let edited_text = fs::read_to_string(file.path());
+    match status? {
+        true => edited_text,
+        false => Err(io::Error::other(format!("{} exited with an error", editor))),
+    }
+}
+
+/// Show text in `$PAGER`, handing the terminal over until it exits
+pub fn view_externally(text: &str, extension: &str) -> io::Result<()> {
+    let pager = env::var("PAGER").unwrap_or("less".to_string());
+    let file = temporary_file(extension, text)?;
+
+    let status = run_suspended(&pager, &file.path().to_string_lossy());
+    match status? {
+        true => Ok(()),
+        false => Err(io::Error::other(format!("{} exited with an error", pager))),
+    }
+}
+
+/// A file holding the text, which only the user can read and which is removed when dropped
+fn temporary_file(extension: &str, text: &str) -> io::Result<NamedTempFile> {
+    let mut file = tempfile::Builder::new()
+        .prefix("tapir-")
+        .suffix(&format!(".{}", extension))
+        .tempfile()?;
+    file.write_all(text.as_bytes())?;
+    file.flush()?;
+    Ok(file)
+}
+
+/// Run a command (which may include arguments, e.g. `code --wait`) with the TUI suspended
+fn run_suspended(command: &str, argument: &str) -> io::Result<bool> {
+    let mut words = command.split_whitespace();
+    let program = words.next().ok_or(io::Error::new(
+        io::ErrorKind::InvalidInput,
+        "No program given",
+    ))?;
+
+    let mut command = Command::new(program);
+    command.args(words).arg(argument);
+    if !io::stdout().is_terminal() {
+        // Keep the program on the terminal when stdout is piped, e.g. `tapir | jq`
+        command.stdout(stderr_as_stdio()?);
+    }
+
+    restore_terminal()?;
+    let status = command.status();
+    setup_terminal()?;
+    Ok(status?.success())
+}
+
+#[cfg(unix)]
+fn stderr_as_stdio() -> io::Result<Stdio> {
+    use std::os::fd::AsFd;
+    Ok(Stdio::from(io::stderr().as_fd().try_clone_to_owned()?))
+}
+
+#[cfg(windows)]
+fn stderr_as_stdio() -> io::Result<Stdio> {
+    use std::os::windows::io::AsHandle;
+    Ok(Stdio::from(io::stderr().as_handle().try_clone_to_owned()?))
+}
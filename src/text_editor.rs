@@ -0,0 +1,513 @@
+use serde_json::value::Value as JsonValue;
+use unicode_width::UnicodeWidthChar;
+
+use crate::line_editor::EditKind;
+
+/// Multi-line editable text with a cursor, auto-indent and undo history
+pub struct TextEditor {
+    lines: Vec<String>,
+    row: usize,
+    column: usize, // Position in characters, not bytes
+    pub scroll: usize,
+    undo_stack: Vec<(Vec<String>, usize, usize)>,
+    last_edit: Option<EditKind>,
+}
+
+pub struct JsonError {
+    pub line: usize, // Zero based
+    pub message: String,
+}
+
+const INDENT: &str = "  ";
+
+impl TextEditor {
+    pub fn new(text: &str) -> TextEditor {
+        TextEditor {
+            lines: split_lines(text),
+            row: 0,
+            column: 0,
+            scroll: 0,
+            undo_stack: Vec::new(),
+            last_edit: None,
+        }
+    }
+
+    pub fn text(&self) -> String {
+        self.lines.join("\n")
+    }
+
+    pub fn lines(&self) -> &[String] {
+        &self.lines
+    }
+
+    /// Everything before the cursor, e.g. to complete the word being typed
+    pub fn text_before_cursor(&self) -> String {
+        let mut lines = self.lines[..self.row].to_vec();
+        lines.push(self.before_cursor());
+        lines.join("\n")
+    }
+
+    /// The row and column of the cursor, in characters
+    pub fn cursor(&self) -> (usize, usize) {
+        (self.row, self.column)
+    }
+
+    /// The display column of the cursor, accounting for wide characters
+    pub fn cursor_column(&self) -> usize {
+        self.lines[self.row]
+            .chars()
+            .take(self.column)
+            .map(|c| c.width().unwrap_or(0))
+            .sum()
+    }
+
+    /// Replace the whole text, keeping the cursor as close to where it was as possible
+    pub fn set_text(&mut self, text: &str) {
+        self.save_undo(EditKind::Other);
+        self.lines = split_lines(text);
+        self.row = self.row.min(self.lines.len() - 1);
+        self.column = self.column.min(self.line_length(self.row));
+    }
+
+    pub fn insert_char(&mut self, c: char) {
+        if c.is_control() {
+            return;
+        }
+        self.save_undo(EditKind::Insert);
+        // Closing a block on an otherwise empty line removes one level of indentation
+        if matches!(c, '}' | ']' | ')') {
+            let before_cursor = self.before_cursor();
+            if !before_cursor.is_empty()
+                && before_cursor.trim().is_empty()
+                && before_cursor.ends_with(INDENT)
+            {
+                let index = self.byte_index(self.row, self.column - INDENT.len());
+                self.lines[self.row].replace_range(index..index + INDENT.len(), "");
+                self.column -= INDENT.len();
+            }
+        }
+        let index = self.byte_index(self.row, self.column);
+        self.lines[self.row].insert(index, c);
+        self.column += 1;
+    }
+
+    pub fn insert_tab(&mut self) {
+        for c in INDENT.chars() {
+            self.insert_char(c);
+        }
+    }
+
+    /// Insert pasted text at the cursor, keeping its line breaks
+    pub fn insert_str(&mut self, text: &str) {
+        self.save_undo(EditKind::Other);
+        let index = self.byte_index(self.row, self.column);
+        let after_cursor = self.lines[self.row].split_off(index);
+        let mut pasted_lines = split_lines(text).into_iter();
+        if let Some(first_line) = pasted_lines.next() {
+            self.lines[self.row].push_str(&first_line);
+        }
+        for line in pasted_lines {
+            self.row += 1;
+            self.lines.insert(self.row, line);
+        }
+        self.column = self.line_length(self.row);
+        self.lines[self.row].push_str(&after_cursor);
+    }
+
+    /// Split the line at the cursor, indenting the new line to match the block it is in
+    pub fn insert_newline(&mut self) {
+        self.save_undo(EditKind::Other);
+        let line = &self.lines[self.row];
+        let indent: String = line.chars().take_while(|c| *c == ' ').collect();
+        let index = self.byte_index(self.row, self.column);
+        let before_cursor = line[..index].trim_end().to_string();
+        let after_cursor = line[index..].trim_start().to_string();
+
+        let opens_block = before_cursor.ends_with(['{', '[', '(']);
+        let closes_block = after_cursor.starts_with(['}', ']', ')']);
+        self.lines[self.row] = before_cursor;
+        match (opens_block, closes_block) {
+            (true, true) => {
+                self.lines
+                    .insert(self.row + 1, format!("{}{}", indent, INDENT));
+                self.lines
+                    .insert(self.row + 2, format!("{}{}", indent, after_cursor));
+                self.column = indent.len() + INDENT.len();
+            }
+            (true, false) => {
+                self.lines.insert(
+                    self.row + 1,
+                    format!("{}{}{}", indent, INDENT, after_cursor),
+                );
+                self.column = indent.len() + INDENT.len();
+            }
+            (false, _) => {
+                self.lines
+                    .insert(self.row + 1, format!("{}{}", indent, after_cursor));
+                self.column = indent.len();
+            }
+        }
+        self.row += 1;
+    }
+
+    pub fn delete_backward(&mut self) {
+        match (self.row, self.column) {
+            (0, 0) => {}
+            (_, 0) => {
+                self.save_undo(EditKind::Delete);
+                let line = self.lines.remove(self.row);
+                self.row -= 1;
+                self.column = self.line_length(self.row);
+                self.lines[self.row].push_str(&line);
+            }
+            _ => {
+                self.save_undo(EditKind::Delete);
+                self.column -= 1;
+                let index = self.byte_index(self.row, self.column);
+                self.lines[self.row].remove(index);
+            }
+        }
+    }
+
+    pub fn delete_forward(&mut self) {
+        if self.column < self.line_length(self.row) {
+            self.save_undo(EditKind::Delete);
+            let index = self.byte_index(self.row, self.column);
+            self.lines[self.row].remove(index);
+        } else if self.row + 1 < self.lines.len() {
+            self.save_undo(EditKind::Delete);
+            let line = self.lines.remove(self.row + 1);
+            self.lines[self.row].push_str(&line);
+        }
+    }
+
+    /// Delete from the start of the word before the cursor up to the cursor
+    pub fn delete_word_backward(&mut self) {
+        let start = self.word_start_before();
+        if start == self.column {
+            self.delete_backward();
+            return;
+        }
+        self.save_undo(EditKind::Other);
+        let range = self.byte_index(self.row, start)..self.byte_index(self.row, self.column);
+        self.lines[self.row].replace_range(range, "");
+        self.column = start;
+    }
+
+    pub fn move_left(&mut self) {
+        match (self.row, self.column) {
+            (0, 0) => {}
+            (_, 0) => self.move_to(self.row - 1, usize::MAX),
+            _ => self.move_to(self.row, self.column - 1),
+        }
+    }
+
+    pub fn move_right(&mut self) {
+        if self.column < self.line_length(self.row) {
+            self.move_to(self.row, self.column + 1);
+        } else if self.row + 1 < self.lines.len() {
+            self.move_to(self.row + 1, 0);
+        }
+    }
+
+    pub fn move_up(&mut self, rows: usize) {
+        self.move_to(self.row.saturating_sub(rows), self.column);
+    }
+
+    pub fn move_down(&mut self, rows: usize) {
+        self.move_to(self.row + rows, self.column);
+    }
+
+    pub fn move_to_line_start(&mut self) {
+        self.move_to(self.row, 0);
+    }
+
+    pub fn move_to_line_end(&mut self) {
+        self.move_to(self.row, usize::MAX);
+    }
+
+    pub fn move_word_left(&mut self) {
+        match self.column {
+            0 => self.move_left(),
+            _ => self.move_to(self.row, self.word_start_before()),
+        }
+    }
+
+    pub fn move_word_right(&mut self) {
+        let chars: Vec<char> = self.lines[self.row].chars().collect();
+        if self.column == chars.len() {
+            self.move_right();
+            return;
+        }
+        let mut position = self.column;
+        while position < chars.len() && !is_word_char(chars[position]) {
+            position += 1;
+        }
+        while position < chars.len() && is_word_char(chars[position]) {
+            position += 1;
+        }
+        self.move_to(self.row, position);
+    }
+
+    pub fn undo(&mut self) {
+        if let Some((lines, row, column)) = self.undo_stack.pop() {
+            self.lines = lines;
+            self.row = row;
+            self.column = column;
+            self.last_edit = None;
+        }
+    }
+
+    /// Scroll so that the cursor is within the visible rows
+    pub fn scroll_to_cursor(&mut self, visible_rows: usize) {
+        let visible_rows = visible_rows.max(1);
+        if self.row < self.scroll {
+            self.scroll = self.row;
+        } else if self.row >= self.scroll + visible_rows {
+            self.scroll = self.row + 1 - visible_rows;
+        }
+    }
+
+    /// The position of the bracket matching the one at (or just before) the cursor
+    pub fn matching_bracket(&self) -> Option<((usize, usize), (usize, usize))> {
+        let chars: Vec<char> = self.lines[self.row].chars().collect();
+        let bracket_column = [Some(self.column), self.column.checked_sub(1)]
+            .into_iter()
+            .flatten()
+            .find(|column| chars.get(*column).is_some_and(|c| "{}[]()".contains(*c)))?;
+        let bracket = chars[bracket_column];
+        let (open, close, forward) = match bracket {
+            '{' => ('{', '}', true),
+            '[' => ('[', ']', true),
+            '(' => ('(', ')', true),
+            '}' => ('{', '}', false),
+            ']' => ('[', ']', false),
+            _ => ('(', ')', false),
+        };
+
+        // Walk through every character after (or before) the bracket, tracking the nesting depth
+        let positions: Vec<((usize, usize), char)> = self
+            .lines
+            .iter()
+            .enumerate()
+            .flat_map(|(row, line)| {
+                line.chars()
+                    .enumerate()
+                    .map(move |(column, c)| ((row, column), c))
+            })
+            .collect();
+        let start = positions
+            .iter()
+            .position(|(position, _)| *position == (self.row, bracket_column))?;
+        let candidates: Box<dyn Iterator<Item = &((usize, usize), char)>> = match forward {
+            true => Box::new(positions[start + 1..].iter()),
+            false => Box::new(positions[..start].iter().rev()),
+        };
+        let mut depth = 0;
+        for (position, c) in candidates {
+            match (*c == bracket, *c == open || *c == close) {
+                (true, _) => depth += 1,
+                (false, true) if depth == 0 => {
+                    return Some(((self.row, bracket_column), *position))
+                }
+                (false, true) => depth -= 1,
+                _ => {}
+            }
+        }
+        None
+    }
+
+    /// The first syntax error in the text, if it looks like JSON
+    pub fn json_error(&self) -> Option<JsonError> {
+        let text = self.text();
+        if !text.trim_start().starts_with(['{', '[']) {
+            return None;
+        }
+        match serde_json::from_str::<JsonValue>(&text) {
+            Ok(_) => None,
+            Err(err) => Some(JsonError {
+                line: err.line().saturating_sub(1).min(self.lines.len() - 1),
+                message: err.to_string(),
+            }),
+        }
+    }
+
+    fn move_to(&mut self, row: usize, column: usize) {
+        self.row = row.min(self.lines.len() - 1);
+        self.column = column.min(self.line_length(self.row));
+        self.last_edit = None;
+    }
+
+    fn before_cursor(&self) -> String {
+        self.lines[self.row].chars().take(self.column).collect()
+    }
+
+    fn word_start_before(&self) -> usize {
+        let chars: Vec<char> = self.lines[self.row].chars().collect();
+        let mut position = self.column;
+        while position > 0 && !is_word_char(chars[position - 1]) {
+            position -= 1;
+        }
+        while position > 0 && is_word_char(chars[position - 1]) {
+            position -= 1;
+        }
+        position
+    }
+
+    fn save_undo(&mut self, kind: EditKind) {
+        if kind == EditKind::Other || self.last_edit != Some(kind) {
+            self.undo_stack
+                .push((self.lines.clone(), self.row, self.column));
+        }
+        self.last_edit = Some(kind);
+    }
+
+    fn line_length(&self, row: usize) -> usize {
+        self.lines[row].chars().count()
+    }
+
+    fn byte_index(&self, row: usize, column: usize) -> usize {
+        let line = &self.lines[row];
+        line.char_indices()
+            .nth(column)
+            .map(|(index, _)| index)
+            .unwrap_or(line.len())
+    }
+}
+
+fn split_lines(text: &str) -> Vec<String> {
+    text.split('\n')
+        .map(|line| {
+            line.strip_suffix('\r')
+                .unwrap_or(line)
+                .replace('\t', INDENT)
+        })
+        .collect()
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn multi_byte_characters() {
+        let mut editor = TextEditor::new("naïve\n日本");
+        editor.move_to_line_end();
+        editor.insert_char('s');
+        assert_eq!(editor.text(), "naïves\n日本");
+        editor.move_down(1);
+        editor.insert_char('語');
+        assert_eq!(editor.text(), "naïves\n日本語");
+        assert_eq!(editor.cursor(), (1, 3));
+        assert_eq!(editor.cursor_column(), 6);
+        editor.move_to_line_start();
+        editor.delete_backward();
+        assert_eq!(editor.text(), "naïves日本語");
+        assert_eq!(editor.cursor(), (0, 6));
+    }
+
+    #[test]
+    fn word_jumps() {
+        let mut editor = TextEditor::new("{\"user_id\": 1}\nnext");
+        editor.move_word_right();
+        assert_eq!(editor.cursor(), (0, 9));
+        editor.move_word_right();
+        assert_eq!(editor.cursor(), (0, 13));
+        editor.move_to_line_end();
+        // At the end of a line, jumping moves on to the next line
+        editor.move_word_right();
+        assert_eq!(editor.cursor(), (1, 0));
+        editor.move_word_left();
+        assert_eq!(editor.cursor(), (0, 14));
+        editor.move_word_left();
+        assert_eq!(editor.cursor(), (0, 12));
+    }
+
+    #[test]
+    fn deleting_a_word_at_the_start_of_a_line_joins_lines() {
+        let mut editor = TextEditor::new("one two\nthree");
+        editor.move_down(1);
+        editor.delete_word_backward();
+        assert_eq!(editor.text(), "one twothree");
+        editor.delete_word_backward();
+        assert_eq!(editor.text(), "one three");
+    }
+
+    #[test]
+    fn indentation() {
+        let mut editor = TextEditor::new("");
+        editor.insert_char('{');
+        editor.insert_char('}');
+        editor.move_left();
+        editor.insert_newline();
+        assert_eq!(editor.text(), "{\n  \n}");
+        assert_eq!(editor.cursor(), (1, 2));
+        editor.insert_char('[');
+        editor.insert_newline();
+        editor.insert_char(']');
+        assert_eq!(editor.text(), "{\n  [\n  ]\n}");
+    }
+
+    #[test]
+    fn pasting_keeps_line_breaks() {
+        let mut editor = TextEditor::new("ab");
+        editor.move_right();
+        editor.insert_str("1\r\n2\t3");
+        assert_eq!(editor.text(), "a1\n2  3b");
+        assert_eq!(editor.cursor(), (1, 4));
+        editor.undo();
+        assert_eq!(editor.text(), "ab");
+    }
+
+    #[test]
+    fn undo_groups_consecutive_edits() {
+        let mut editor = TextEditor::new("");
+        for c in "abc".chars() {
+            editor.insert_char(c);
+        }
+        editor.insert_newline();
+        editor.insert_char('d');
+        editor.delete_backward();
+        editor.delete_backward();
+        assert_eq!(editor.text(), "abc");
+        editor.undo();
+        assert_eq!(editor.text(), "abc\nd");
+        editor.undo();
+        assert_eq!(editor.text(), "abc\n");
+        editor.undo();
+        assert_eq!(editor.text(), "abc");
+        editor.undo();
+        assert_eq!(editor.text(), "");
+    }
+
+    #[test]
+    fn matching_brackets() {
+        let editor = TextEditor::new("{\"a\": [1, (2)]}");
+        assert_eq!(editor.matching_bracket(), Some(((0, 0), (0, 14))));
+        let mut editor = TextEditor::new("{\n  \"a\": {}\n}");
+        editor.move_down(2);
+        editor.move_to_line_end();
+        assert_eq!(editor.matching_bracket(), Some(((2, 0), (0, 0))));
+        let editor = TextEditor::new("plain");
+        assert!(editor.matching_bracket().is_none());
+    }
+
+    #[test]
+    fn json_error_positions() {
+        let editor = TextEditor::new("{\n  \"a\": 1,\n  \"b\" 2\n}");
+        let error = editor.json_error().unwrap();
+        assert_eq!(error.line, 2);
+        assert!(error.message.contains("line 3"));
+
+        // Errors at the end of the text stay on the last line
+        let editor = TextEditor::new("{\n  \"a\": 1,\n");
+        assert_eq!(editor.json_error().unwrap().line, 2);
+
+        assert!(TextEditor::new("{\"a\": [1, 2]}").json_error().is_none());
+        assert!(TextEditor::new("name=value").json_error().is_none());
+    }
+}
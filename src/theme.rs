@@ -0,0 +1,365 @@
+use ratatui::style::{Color, Modifier, Style};
+use std::env;
+use std::str::FromStr;
+
+use crate::config::ThemeConfig;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum ColorDepth {
+    TrueColor,
+    Ansi256,
+    Ansi16,
+}
+
+#[derive(Clone)]
+pub struct Theme {
+    pub selected: Style,     // Active tab and focused pane border
+    pub highlighted: Style,  // Selected list item
+    pub muted: Style,        // Unfocused borders, line numbers and sizes
+    pub text: Style,         // Headings
+    pub endpoint: Style,     // The method and endpoint line
+    pub normal_mode: Style,  // Footer mode text while navigating
+    pub input_mode: Style,   // Footer mode text while typing or viewing
+    pub edit_mode: Style,    // Footer mode text while adding or editing an item
+    pub exit_mode: Style,    // Footer mode text while exiting
+    pub hint: Style,         // Key hints
+    pub error: Style,        // Error messages
+    pub warning: Style,      // Warning notifications
+    pub success: Style,      // Success notifications
+    pub info: Style,         // Informational notifications
+    pub popup: Style,        // Popup backgrounds
+    pub input: Style,        // The focused text input
+    pub help_key: Style,     // Keys in the help overlay
+    pub search_match: Style, // Search matches in the pager
+    pub added: Style,
+    pub removed: Style,
+    pub changed: Style,
+    pub json_key: Style,
+    pub json_string: Style,
+    pub json_number: Style,
+    pub json_bool: Style,
+    pub json_null: Style,
+    pub json_punctuation: Style,
+}
+
+impl Theme {
+    pub fn dark() -> Theme {
+        Theme {
+            selected: Style::default().fg(Color::Magenta),
+            highlighted: Style::default().bg(Color::DarkGray).fg(Color::Magenta),
+            muted: Style::default().fg(Color::DarkGray),
+            text: Style::default().fg(Color::White),
+            endpoint: Style::default().fg(Color::Green),
+            normal_mode: Style::default().fg(Color::Green),
+            input_mode: Style::default().fg(Color::Blue),
+            edit_mode: Style::default().fg(Color::Red),
+            exit_mode: Style::default().fg(Color::LightRed),
+            hint: Style::default().fg(Color::Red),
+            error: Style::default().fg(Color::Red),
+            warning: Style::default().fg(Color::Yellow),
+            success: Style::default().fg(Color::Green),
+            info: Style::default().fg(Color::Cyan),
+            popup: Style::default().bg(Color::DarkGray),
+            input: Style::default().bg(Color::LightYellow).fg(Color::Black),
+            help_key: Style::default().fg(Color::Yellow),
+            search_match: Style::default().bg(Color::Yellow).fg(Color::Black),
+            added: Style::default().fg(Color::Green),
+            removed: Style::default().fg(Color::Red),
+            changed: Style::default().fg(Color::Yellow),
+            json_key: Style::default().fg(Color::Cyan),
+            json_string: Style::default().fg(Color::Green),
+            json_number: Style::default().fg(Color::Yellow),
+            json_bool: Style::default().fg(Color::Magenta),
+            json_null: Style::default().fg(Color::DarkGray),
+            json_punctuation: Style::default().fg(Color::Gray),
+        }
+    }
+
+    pub fn light() -> Theme {
+        Theme {
+            selected: Style::default().fg(Color::Blue),
+            highlighted: Style::default().bg(Color::Gray).fg(Color::Blue),
+            muted: Style::default().fg(Color::DarkGray),
+            text: Style::default().fg(Color::Black),
+            endpoint: Style::default().fg(Color::Green),
+            normal_mode: Style::default().fg(Color::Green),
+            input_mode: Style::default().fg(Color::Blue),
+            edit_mode: Style::default().fg(Color::Red),
+            exit_mode: Style::default().fg(Color::Red),
+            hint: Style::default().fg(Color::Red),
+            error: Style::default().fg(Color::Red),
+            warning: Style::default().fg(Color::Magenta),
+            success: Style::default().fg(Color::Green),
+            info: Style::default().fg(Color::Blue),
+            popup: Style::default().bg(Color::Gray).fg(Color::Black),
+            input: Style::default().bg(Color::White).fg(Color::Black),
+            help_key: Style::default().fg(Color::Blue),
+            search_match: Style::default().bg(Color::LightYellow).fg(Color::Black),
+            added: Style::default().fg(Color::Green),
+            removed: Style::default().fg(Color::Red),
+            changed: Style::default().fg(Color::Magenta),
+            json_key: Style::default().fg(Color::Blue),
+            json_string: Style::default().fg(Color::Green),
+            json_number: Style::default().fg(Color::Magenta),
+            json_bool: Style::default().fg(Color::Red),
+            json_null: Style::default().fg(Color::DarkGray),
+            json_punctuation: Style::default().fg(Color::DarkGray),
+        }
+    }
+
+    pub fn high_contrast() -> Theme {
+        let bold = Style::default().add_modifier(Modifier::BOLD);
+        Theme {
+            selected: bold.fg(Color::LightYellow),
+            highlighted: bold.bg(Color::White).fg(Color::Black),
+            muted: Style::default().fg(Color::Gray),
+            text: bold.fg(Color::White),
+            endpoint: bold.fg(Color::LightGreen),
+            normal_mode: bold.fg(Color::LightGreen),
+            input_mode: bold.fg(Color::LightCyan),
+            edit_mode: bold.fg(Color::LightYellow),
+            exit_mode: bold.fg(Color::LightRed),
+            hint: Style::default().fg(Color::White),
+            error: bold.fg(Color::LightRed),
+            warning: bold.fg(Color::LightYellow),
+            success: bold.fg(Color::LightGreen),
+            info: bold.fg(Color::LightCyan),
+            popup: Style::default().bg(Color::Black).fg(Color::White),
+            input: Style::default().bg(Color::White).fg(Color::Black),
+            help_key: bold.fg(Color::LightYellow),
+            search_match: bold.bg(Color::LightYellow).fg(Color::Black),
+            added: bold.fg(Color::LightGreen),
+            removed: bold.fg(Color::LightRed),
+            changed: bold.fg(Color::LightYellow),
+            json_key: bold.fg(Color::LightCyan),
+            json_string: Style::default().fg(Color::LightGreen),
+            json_number: Style::default().fg(Color::LightYellow),
+            json_bool: Style::default().fg(Color::LightMagenta),
+            json_null: Style::default().fg(Color::Gray),
+            json_punctuation: Style::default().fg(Color::White),
+        }
+    }
+
+    /// No colours at all, only emphasis, as requested by `NO_COLOR`
+    pub fn no_color() -> Theme {
+        let plain = Style::default();
+        let reversed = Style::default().add_modifier(Modifier::REVERSED);
+        let bold = Style::default().add_modifier(Modifier::BOLD);
+        Theme {
+            selected: bold,
+            highlighted: reversed,
+            muted: plain.add_modifier(Modifier::DIM),
+            text: bold,
+            endpoint: plain,
+            normal_mode: plain,
+            input_mode: bold,
+            edit_mode: bold,
+            exit_mode: bold,
+            hint: plain,
+            error: bold,
+            warning: bold,
+            success: plain,
+            info: plain,
+            popup: plain,
+            input: reversed,
+            help_key: bold,
+            search_match: reversed,
+            added: plain,
+            removed: plain,
+            changed: plain,
+            json_key: bold,
+            json_string: plain,
+            json_number: plain,
+            json_bool: plain,
+            json_null: plain,
+            json_punctuation: plain,
+        }
+    }
+
+    /// Build the theme from the config and the environment, along with any problems found
+    pub fn from_config(config: &ThemeConfig) -> (Theme, Vec<String>) {
+        let mut problems = Vec::<String>::new();
+        let no_color = env::var("NO_COLOR").is_ok_and(|value| !value.is_empty());
+
+        let mut theme = match (no_color, config.name.as_deref()) {
+            (true, _) => Theme::no_color(),
+            (false, None | Some("dark")) => Theme::dark(),
+            (false, Some("light")) => Theme::light(),
+            (false, Some("high-contrast")) => Theme::high_contrast(),
+            (false, Some(name)) => {
+                problems.push(format!(
+                    "Unknown theme \"{}\", expected \"dark\", \"light\" or \"high-contrast\"",
+                    name
+                ));
+                Theme::dark()
+            }
+        };
+
+        // Styles set explicitly by the user still apply with NO_COLOR
+        let color_depth = color_depth();
+        for (name, spec) in config.styles.iter() {
+            match (theme.style_mut(name), parse_style(spec)) {
+                (Some(style), Ok(new_style)) => *style = downgrade_style(new_style, color_depth),
+                (Some(_), Err(err)) => {
+                    problems.push(format!("Invalid style for {}: {}", name, err))
+                }
+                (None, _) => problems.push(format!("Unknown theme style \"{}\"", name)),
+            }
+        }
+
+        (theme, problems)
+    }
+
+    fn style_mut(&mut self, name: &str) -> Option<&mut Style> {
+        match name {
+            "selected" => Some(&mut self.selected),
+            "highlighted" => Some(&mut self.highlighted),
+            "muted" => Some(&mut self.muted),
+            "text" => Some(&mut self.text),
+            "endpoint" => Some(&mut self.endpoint),
+            "normal_mode" => Some(&mut self.normal_mode),
+            "input_mode" => Some(&mut self.input_mode),
+            "edit_mode" => Some(&mut self.edit_mode),
+            "exit_mode" => Some(&mut self.exit_mode),
+            "hint" => Some(&mut self.hint),
+            "error" => Some(&mut self.error),
+            "warning" => Some(&mut self.warning),
+            "success" => Some(&mut self.success),
+            "info" => Some(&mut self.info),
+            "popup" => Some(&mut self.popup),
+            "input" => Some(&mut self.input),
+            "help_key" => Some(&mut self.help_key),
+            "search_match" => Some(&mut self.search_match),
+            "added" => Some(&mut self.added),
+            "removed" => Some(&mut self.removed),
+            "changed" => Some(&mut self.changed),
+            "json_key" => Some(&mut self.json_key),
+            "json_string" => Some(&mut self.json_string),
+            "json_number" => Some(&mut self.json_number),
+            "json_bool" => Some(&mut self.json_bool),
+            "json_null" => Some(&mut self.json_null),
+            "json_punctuation" => Some(&mut self.json_punctuation),
+            _ => None,
+        }
+    }
+}
+
+/// Parse a style such as `yellow`, `black on #ffd700` or `bold white on red`
+pub fn parse_style(spec: &str) -> Result<Style, String> {
+    let mut style = Style::default();
+    let mut words = spec.split_whitespace();
+    while let Some(word) = words.next() {
+        style = match word.to_lowercase().as_str() {
+            "bold" => style.add_modifier(Modifier::BOLD),
+            "dim" => style.add_modifier(Modifier::DIM),
+            "italic" => style.add_modifier(Modifier::ITALIC),
+            "underlined" => style.add_modifier(Modifier::UNDERLINED),
+            "reversed" => style.add_modifier(Modifier::REVERSED),
+            "on" => match words.next() {
+                Some(background) => style.bg(parse_color(background)?),
+                None => return Err(format!("missing a background colour in \"{}\"", spec)),
+            },
+            _ => style.fg(parse_color(word)?),
+        };
+    }
+    Ok(style)
+}
+
+fn parse_color(name: &str) -> Result<Color, String> {
+    Color::from_str(name).map_err(|_| format!("unknown colour \"{}\"", name))
+}
+
+/// Guess how many colours the terminal supports from the environment
+pub fn color_depth() -> ColorDepth {
+    let color_term = env::var("COLORTERM").unwrap_or_default();
+    let term = env::var("TERM").unwrap_or_default();
+    match (color_term.as_str(), term.contains("256color")) {
+        ("truecolor" | "24bit", _) => ColorDepth::TrueColor,
+        (_, true) => ColorDepth::Ansi256,
+        _ => ColorDepth::Ansi16,
+    }
+}
+
+fn downgrade_style(style: Style, depth: ColorDepth) -> Style {
+    Style {
+        fg: style.fg.map(|color| downgrade_color(color, depth)),
+        bg: style.bg.map(|color| downgrade_color(color, depth)),
+        ..style
+    }
+}
+
+/// Replace a colour the terminal can't display with the closest one it can
+pub fn downgrade_color(color: Color, depth: ColorDepth) -> Color {
+    match (color, depth) {
+        (Color::Rgb(r, g, b), ColorDepth::Ansi256) => {
+            let level = |value: u8| match value {
+                0..=47 => 0,
+                48..=114 => 1,
+                _ => (value - 35) / 40,
+            };
+            Color::Indexed(16 + 36 * level(r) + 6 * level(g) + level(b))
+        }
+        (Color::Rgb(r, g, b), ColorDepth::Ansi16) => nearest_ansi_color((r, g, b)),
+        (Color::Indexed(index), ColorDepth::Ansi16) => match index {
+            0..=15 => ANSI_COLORS[index as usize].0,
+            _ => nearest_ansi_color(indexed_to_rgb(index)),
+        },
+        _ => color,
+    }
+}
+
+// The 16 ANSI colours in index order, with their typical xterm values
+const ANSI_COLORS: [(Color, (u8, u8, u8)); 16] = [
+    (Color::Black, (0, 0, 0)),
+    (Color::Red, (205, 0, 0)),
+    (Color::Green, (0, 205, 0)),
+    (Color::Yellow, (205, 205, 0)),
+    (Color::Blue, (0, 0, 238)),
+    (Color::Magenta, (205, 0, 205)),
+    (Color::Cyan, (0, 205, 205)),
+    (Color::Gray, (229, 229, 229)),
+    (Color::DarkGray, (127, 127, 127)),
+    (Color::LightRed, (255, 0, 0)),
+    (Color::LightGreen, (0, 255, 0)),
+    (Color::LightYellow, (255, 255, 0)),
+    (Color::LightBlue, (92, 92, 255)),
+    (Color::LightMagenta, (255, 0, 255)),
+    (Color::LightCyan, (0, 255, 255)),
+    (Color::White, (255, 255, 255)),
+];
+
+fn nearest_ansi_color((r, g, b): (u8, u8, u8)) -> Color {
+    let distance = |(other_r, other_g, other_b): (u8, u8, u8)| {
+        (r as i32 - other_r as i32).pow(2)
+            + (g as i32 - other_g as i32).pow(2)
+            + (b as i32 - other_b as i32).pow(2)
+    };
+    ANSI_COLORS
+        .iter()
+        .min_by_key(|(_, rgb)| distance(*rgb))
+        .map(|(color, _)| *color)
+        .unwrap_or(Color::Reset)
+}
+
+/// The RGB value of a colour from the 6x6x6 cube or grey ramp of the 256 colour palette
+fn indexed_to_rgb(index: u8) -> (u8, u8, u8) {
+    match index {
+        0..=15 => ANSI_COLORS[index as usize].1,
+        16..=231 => {
+            let level = |value: u8| match value {
+                0 => 0,
+                _ => 55 + 40 * value,
+            };
+            let cube_index = index - 16;
+            (
+                level(cube_index / 36),
+                level((cube_index / 6) % 6),
+                level(cube_index % 6),
+            )
+        }
+        _ => {
+            let grey = 8 + 10 * (index - 232);
+            (grey, grey, grey)
+        }
+    }
+}
@@ -1,16 +1,42 @@
 use ratatui::{
-    backend::Backend,
-    layout::{Constraint, Direction, Layout, Rect},
-    style::{Color, Style},
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::Style,
     text::{Line, Span, Text},
-    widgets::{Block, Borders, Clear, List, ListItem, Paragraph, Wrap},
+    widgets::{
+        Block, Borders, Clear, List, ListItem, ListState, Paragraph, Scrollbar,
+        ScrollbarOrientation, ScrollbarState, Tabs, Wrap,
+    },
     Frame,
 };
 
-use crate::app::{App, CurrentScreen, KeyValuePair, PairInputMode, Section};
+use serde_json::value::Value as JsonValue;
+use unicode_width::UnicodeWidthChar;
+
+use crate::app::{
+    App, BodyEditorState, CurrentScreen, DiffState, GraphqlEditorState, GraphqlField, KeyValuePair,
+    MouseTarget, PairInputMode, SchemaBrowserState, Section,
+};
+use crate::config::LayoutMode;
+use crate::diff::{JsonChange, LineChange};
+use crate::json_tree::{describe_size, flatten, TreeLine, TreeLineKind};
+use crate::keymap::{context_for, describe_key, Action, KeymapContext};
+use crate::line_editor::LineEditor;
+use crate::notification::{describe_age, Notification, Severity};
+use crate::pager::{pager_lines, PagerState};
+use crate::text_editor::TextEditor;
+use crate::theme::Theme;
+use crate::websocket::{FrameDirection, WebSocketState};
+
+pub fn ui(frame: &mut Frame, app: &App) {
+    let mut mouse_targets = app.mouse_targets.borrow_mut();
+    mouse_targets.clear();
+    app.max_horizontal_scroll.set(0);
 
-pub fn ui<B: Backend>(frame: &mut Frame, app: &App) {
     // Create the layout sections.
+    let outer_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(1), Constraint::Min(1)])
+        .split(frame.size());
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
@@ -19,112 +45,185 @@ pub fn ui<B: Backend>(frame: &mut Frame, app: &App) {
             Constraint::Min(1),
             Constraint::Length(3),
         ])
-        .split(frame.size());
+        .split(outer_chunks[1]);
 
+    let tab_titles: Vec<Line> = app
+        .tab_requests()
+        .into_iter()
+        .enumerate()
+        .map(|(index, (method, endpoint))| {
+            Line::from(format!(
+                "{}: {} {}{}",
+                index + 1,
+                method,
+                shorten_endpoint(endpoint, 24),
+                match app.is_tab_dirty(index) {
+                    true => " *",
+                    false => "",
+                }
+            ))
+        })
+        .collect();
+    let tab_bar = Tabs::new(tab_titles.clone())
+        .select(app.active_tab)
+        .highlight_style(app.theme.selected);
+    frame.render_widget(tab_bar, outer_chunks[0]);
+    // Tabs are padded by a space on each side and separated by a divider
+    let mut tab_x = outer_chunks[0].x;
+    for (index, title) in tab_titles.iter().enumerate() {
+        let tab_width = title.width() as u16 + 2;
+        let tab_area = Rect::new(tab_x, outer_chunks[0].y, tab_width, 1);
+        mouse_targets.push((
+            tab_area.intersection(outer_chunks[0]),
+            MouseTarget::Tab(index),
+        ));
+        tab_x = tab_x.saturating_add(tab_width + 1);
+    }
+
+    let endpoint_chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Min(1), Constraint::Length(8)])
+        .split(chunks[0]);
     let endpoint_block = Block::default()
         .borders(Borders::ALL)
         .style(Style::default());
+    let method_text = format!("Method: {}", app.method);
+    let method_area = Rect {
+        width: method_text.len() as u16,
+        ..endpoint_block.inner(endpoint_chunks[0])
+    };
 
     let endpoint_input = Paragraph::new(Text::styled(
-        format!(
-            "Method: {} - Endpoint: {}",
-            app.method.clone(),
-            app.endpoint.clone()
-        ),
-        Style::default().fg(Color::Green),
+        format!("{} - Endpoint: {}", method_text, app.endpoint.clone()),
+        app.theme.endpoint,
     ))
     .block(endpoint_block);
-    frame.render_widget(endpoint_input, chunks[0]);
+    frame.render_widget(endpoint_input, endpoint_chunks[0]);
+    mouse_targets.push((endpoint_chunks[0], MouseTarget::Endpoint));
+    mouse_targets.push((
+        method_area.intersection(endpoint_chunks[0]),
+        MouseTarget::Method,
+    ));
 
-    let section_name_block = Block::default()
-        .borders(Borders::ALL)
-        .style(Style::default());
-    let section_name = Paragraph::new(Text::styled(
-        app.selected_section.to_string(),
-        Style::default().fg(Color::White),
-    ))
-    .block(section_name_block);
-    frame.render_widget(section_name, chunks[1]);
-
-    let mut list_items = Vec::<ListItem>::new();
-    let section_items = match app.selected_section {
-        Section::RequestBody(_) => &app.section_values.request_body,
-        Section::RequestHeaders(_) => &app.section_values.request_headers,
-        Section::ResponseBody(_) => &app.section_values.response_body,
-        Section::ResponseHeaders(_) => &app.section_values.response_headers,
-    };
-
-    for (index, (key, value)) in section_items.iter().enumerate() {
-        let selected_index = match app.selected_section {
-            Section::RequestBody(index) => index,
-            Section::RequestHeaders(index) => index,
-            Section::ResponseBody(index) => index,
-            Section::ResponseHeaders(index) => index,
-        };
+    let send_button = Paragraph::new(Text::styled("Send", app.theme.selected))
+        .alignment(Alignment::Center)
+        .block(Block::default().borders(Borders::ALL));
+    frame.render_widget(send_button, endpoint_chunks[1]);
+    mouse_targets.push((endpoint_chunks[1], MouseTarget::SendButton));
 
-        list_items.push(ListItem::new(Line::from(Span::styled(
-            format!("{: <25} : {}", key, value),
-            if let Some(selected_index) = selected_index {
-                if index as u8 == selected_index {
-                    Style::default().fg(Color::Magenta)
-                } else {
-                    Style::default()
-                }
-            } else {
-                Style::default()
-            },
-        ))));
+    match app.config.layout.mode {
+        LayoutMode::Single => {
+            let section_name_block = Block::default()
+                .borders(Borders::ALL)
+                .style(Style::default());
+            let section_name = Paragraph::new(Text::styled(
+                section_title(app, &app.selected_section),
+                app.theme.text,
+            ))
+            .block(section_name_block);
+            frame.render_widget(section_name, chunks[1]);
+            mouse_targets.push((chunks[1], MouseTarget::SectionName));
+            render_section_list(
+                frame,
+                app,
+                &app.selected_section,
+                chunks[2],
+                &mut mouse_targets,
+            );
+        }
+        LayoutMode::Split => {
+            let direction = match frame.size().width < app.config.layout.stack_below_width {
+                true => Direction::Vertical,
+                false => Direction::Horizontal,
+            };
+            let split_percent = app.config.layout.split_percent;
+            let panes = Layout::default()
+                .direction(direction)
+                .constraints([
+                    Constraint::Percentage(split_percent),
+                    Constraint::Percentage(100 - split_percent),
+                ])
+                .split(chunks[1].union(chunks[2]));
+            let (request_section, response_section) =
+                match app.selected_section.is_request_section() {
+                    true => (&app.selected_section, &app.unfocused_section),
+                    false => (&app.unfocused_section, &app.selected_section),
+                };
+            for (section, pane) in [(request_section, panes[0]), (response_section, panes[1])] {
+                let is_focused =
+                    section.is_request_section() == app.selected_section.is_request_section();
+                let pane_block = Block::default()
+                    .title(section_title(app, section))
+                    .borders(Borders::ALL)
+                    .border_style(match is_focused {
+                        true => app.theme.selected,
+                        false => app.theme.muted,
+                    });
+                let list_area = pane_block.inner(pane);
+                frame.render_widget(pane_block, pane);
+                mouse_targets.push((pane, MouseTarget::Pane(section.is_request_section())));
+                render_section_list(frame, app, section, list_area, &mut mouse_targets);
+            }
+        }
     }
 
-    // TODO: Sort this list by key
-    let list = List::new(list_items);
-
-    frame.render_widget(list, chunks[2]);
-    let current_navigation_text = vec![
+    let mut current_navigation_text = vec![
         // The first half of the text
         match app.current_screen {
-            CurrentScreen::Main => Span::styled("Normal Mode", Style::default().fg(Color::Green)),
+            CurrentScreen::Main if !app.mouse_captured => {
+                Span::styled("Normal Mode (mouse released)", app.theme.normal_mode)
+            }
+            CurrentScreen::Main => Span::styled("Normal Mode", app.theme.normal_mode),
             CurrentScreen::EndpointInput(_) => {
-                Span::styled("Editing endpoint", Style::default().fg(Color::Blue))
+                Span::styled("Editing endpoint", app.theme.input_mode)
             }
             CurrentScreen::PairInput(ref input_state) => match &input_state.mode {
-                PairInputMode::Add => {
-                    Span::styled("Adding a new item", Style::default().fg(Color::Red))
+                PairInputMode::Add => Span::styled("Adding a new item", app.theme.edit_mode),
+                PairInputMode::Edit(edit_key) => {
+                    Span::styled(format!("Editing {}", edit_key), app.theme.edit_mode)
                 }
-                PairInputMode::Edit(edit_key) => Span::styled(
-                    format!("Editing {}", edit_key),
-                    Style::default().fg(Color::Red),
-                ),
             },
-            CurrentScreen::Loading => Span::styled("Exiting", Style::default().fg(Color::LightRed)),
+            CurrentScreen::FilterInput(_) => {
+                Span::styled("Filtering response", app.theme.input_mode)
+            }
+            CurrentScreen::BodyEditor(_) => {
+                Span::styled("Editing request body", app.theme.edit_mode)
+            }
+            CurrentScreen::GraphqlEditor(_) => {
+                Span::styled("Editing GraphQL query", app.theme.edit_mode)
+            }
+            CurrentScreen::SchemaBrowser(_) => {
+                Span::styled("Browsing schema", app.theme.input_mode)
+            }
+            CurrentScreen::Pager(_) => Span::styled("Viewing response", app.theme.input_mode),
+            CurrentScreen::Diff(_) => Span::styled("Comparing responses", app.theme.input_mode),
+            CurrentScreen::AlertLog(_) => Span::styled("Viewing alerts", app.theme.input_mode),
+            CurrentScreen::SaveInput(_) | CurrentScreen::OverwritePrompt(_) => {
+                Span::styled("Saving request", app.theme.edit_mode)
+            }
+            CurrentScreen::ExitPrompt => Span::styled("Exiting", app.theme.exit_mode),
+            CurrentScreen::HarEntries(_) => {
+                Span::styled("Choosing an entry to replay", app.theme.input_mode)
+            }
+            CurrentScreen::HarExportInput(_) | CurrentScreen::HarOverwritePrompt(_) => {
+                Span::styled("Exporting history", app.theme.edit_mode)
+            }
+            CurrentScreen::WebSocket(_) => Span::styled("WebSocket", app.theme.input_mode),
+            CurrentScreen::Loading => Span::styled("Sending request", app.theme.input_mode),
         }
         .to_owned(),
     ];
+    if let Some(ref environment) = app.environment {
+        current_navigation_text.push(Span::styled(format!(" [{}]", environment), app.theme.muted));
+    }
 
     let mode_footer = Paragraph::new(Line::from(current_navigation_text))
         .block(Block::default().borders(Borders::ALL));
 
-    let current_keys_hint = {
-        match app.current_screen {
-            CurrentScreen::Main => Span::styled(
-                // TODO: Update this to the current keys
-                "[a]dd / [e]dit / [d]elete / [q]uit / Enter to submit",
-                Style::default().fg(Color::Red),
-            ),
-            CurrentScreen::EndpointInput(_) => Span::styled(
-                "[ESC] to cancel / Enter to submit",
-                Style::default().fg(Color::Red),
-            ),
-            CurrentScreen::PairInput(_) => Span::styled(
-                "(ESC) to cancel / (Tab) to switch boxes / Enter to submit",
-                Style::default().fg(Color::Red),
-            ),
-            CurrentScreen::Loading => Span::styled(
-                "[a]dd / [e]dit / [d]elete / [q]uit / Enter to submit",
-                Style::default().fg(Color::Red),
-            ),
-        }
-    };
+    let current_keys_hint = Span::styled(
+        app.keymap.hint(context_for(&app.current_screen)),
+        app.theme.hint,
+    );
 
     let key_notes_footer =
         Paragraph::new(Line::from(current_keys_hint)).block(Block::default().borders(Borders::ALL));
@@ -137,11 +236,36 @@ pub fn ui<B: Backend>(frame: &mut Frame, app: &App) {
     frame.render_widget(mode_footer, footer_chunks[0]);
     frame.render_widget(key_notes_footer, footer_chunks[1]);
 
-    if let CurrentScreen::EndpointInput(_) = &app.current_screen {
+    if let CurrentScreen::EndpointInput(editor) = &app.current_screen {
         let popup_block = Block::default()
             .title("Enter a new endpoint")
             .borders(Borders::NONE)
-            .style(Style::default().bg(Color::DarkGray));
+            .style(app.theme.popup);
+        let area = centered_rect(60, 25, frame.size());
+        frame.render_widget(popup_block, area);
+        let popup_chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .margin(1)
+            .constraints([Constraint::Percentage(100)])
+            .split(area);
+        let endpoint_block = Block::default()
+            .title("Endpoint")
+            .borders(Borders::ALL)
+            .style(app.theme.input);
+        render_line_editor(
+            frame,
+            editor,
+            endpoint_block,
+            popup_chunks[0],
+            !app.show_help,
+        );
+    }
+
+    if let CurrentScreen::FilterInput(input_state) = &app.current_screen {
+        let popup_block = Block::default()
+            .title("Filter the response body (e.g. $.items[0].name or .items[].name)")
+            .borders(Borders::NONE)
+            .style(app.theme.popup);
         let area = centered_rect(60, 25, frame.size());
         frame.render_widget(popup_block, area);
         let popup_chunks = Layout::default()
@@ -149,18 +273,68 @@ pub fn ui<B: Backend>(frame: &mut Frame, app: &App) {
             .margin(1)
             .constraints([Constraint::Percentage(100)])
             .split(area);
-        let mut endpoint_block = Block::default().title("Endpoint").borders(Borders::ALL);
-        let active_style = Style::default().bg(Color::LightYellow).fg(Color::Black);
-        endpoint_block = endpoint_block.style(active_style);
-        let endpoint_text = Paragraph::new(app.endpoint.clone()).block(endpoint_block);
-        frame.render_widget(endpoint_text, popup_chunks[0]);
+        let filter_block = Block::default()
+            .title("Filter")
+            .borders(Borders::ALL)
+            .style(app.theme.input);
+        render_line_editor(
+            frame,
+            &input_state.expression,
+            filter_block,
+            popup_chunks[0],
+            !app.show_help,
+        );
+    }
+
+    if let CurrentScreen::SaveInput(input_state) = &app.current_screen {
+        let popup_block = Block::default()
+            .title("Save the request")
+            .borders(Borders::NONE)
+            .style(app.theme.popup);
+        let area = centered_rect(60, 25, frame.size());
+        frame.render_widget(popup_block, area);
+        let popup_chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .margin(1)
+            .constraints([Constraint::Percentage(100)])
+            .split(area);
+        let name_block = Block::default()
+            .title("Name")
+            .borders(Borders::ALL)
+            .style(app.theme.input);
+        render_line_editor(
+            frame,
+            &input_state.name,
+            name_block,
+            popup_chunks[0],
+            !app.show_help,
+        );
+    }
+
+    if let CurrentScreen::HarExportInput(editor) = &app.current_screen {
+        let popup_block = Block::default()
+            .title("Export the history as HAR")
+            .borders(Borders::NONE)
+            .style(app.theme.popup);
+        let area = centered_rect(60, 25, frame.size());
+        frame.render_widget(popup_block, area);
+        let popup_chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .margin(1)
+            .constraints([Constraint::Percentage(100)])
+            .split(area);
+        let path_block = Block::default()
+            .title("File")
+            .borders(Borders::ALL)
+            .style(app.theme.input);
+        render_line_editor(frame, editor, path_block, popup_chunks[0], !app.show_help);
     }
 
     if let CurrentScreen::PairInput(input_state) = &app.current_screen {
         let popup_block = Block::default()
             .title("Enter a new key-value pair")
             .borders(Borders::NONE)
-            .style(Style::default().bg(Color::DarkGray));
+            .style(app.theme.popup);
         let area = centered_rect(60, 25, frame.size());
         frame.render_widget(popup_block, area);
         let popup_chunks = Layout::default()
@@ -170,36 +344,1291 @@ pub fn ui<B: Backend>(frame: &mut Frame, app: &App) {
             .split(area);
         let mut key_block = Block::default().title("Key").borders(Borders::ALL);
         let mut value_block = Block::default().title("Value").borders(Borders::ALL);
-        let active_style = Style::default().bg(Color::LightYellow).fg(Color::Black);
+        let active_style = app.theme.input;
         match input_state.selected_item {
             KeyValuePair::Key => key_block = key_block.style(active_style),
             KeyValuePair::Value => value_block = value_block.style(active_style),
         };
-        let key_text = Paragraph::new(input_state.key.clone()).block(key_block);
-        frame.render_widget(key_text, popup_chunks[0]);
-        let value_text = Paragraph::new(input_state.value.clone()).block(value_block);
-        frame.render_widget(value_text, popup_chunks[1]);
+        let is_key_selected = matches!(input_state.selected_item, KeyValuePair::Key);
+        render_line_editor(
+            frame,
+            &input_state.key,
+            key_block,
+            popup_chunks[0],
+            is_key_selected && !app.show_help,
+        );
+        render_line_editor(
+            frame,
+            &input_state.value,
+            value_block,
+            popup_chunks[1],
+            !is_key_selected && !app.show_help,
+        );
     }
 
-    if let CurrentScreen::Loading = app.current_screen {
-        frame.render_widget(Clear, frame.size()); //this clears the entire screen and anything already drawn
-        let popup_block = Block::default()
-            .title("Y/N")
-            .borders(Borders::NONE)
-            .style(Style::default().bg(Color::DarkGray));
+    if let CurrentScreen::BodyEditor(editor_state) = &app.current_screen {
+        let area = chunks[1].union(chunks[2]);
+        frame.render_widget(Clear, area);
+        render_body_editor(frame, app, editor_state, area);
+    }
 
-        let exit_text = Text::styled(
-            "Would you like to output the buffer as json? (y/n)",
-            Style::default().fg(Color::Red),
-        );
+    if let CurrentScreen::GraphqlEditor(editor_state) = &app.current_screen {
+        let area = chunks[1].union(chunks[2]);
+        frame.render_widget(Clear, area);
+        render_graphql_editor(frame, app, editor_state, area);
+    }
+
+    if let CurrentScreen::Pager(pager_state) = &app.current_screen {
+        frame.render_widget(Clear, frame.size());
+        render_pager(frame, app, pager_state);
+    }
+
+    if let CurrentScreen::Diff(diff_state) = &app.current_screen {
+        frame.render_widget(Clear, frame.size());
+        render_diff(frame, app, diff_state);
+    }
+
+    if let CurrentScreen::AlertLog(scroll) = app.current_screen {
+        frame.render_widget(Clear, frame.size());
+        render_alert_log(frame, app, scroll);
+    }
+
+    if let CurrentScreen::HarEntries(selected) = app.current_screen {
+        frame.render_widget(Clear, frame.size());
+        render_har_entries(frame, app, selected);
+    }
+
+    if let CurrentScreen::WebSocket(websocket_state) = &app.current_screen {
+        frame.render_widget(Clear, frame.size());
+        render_websocket(frame, app, websocket_state);
+    }
+
+    if let CurrentScreen::SchemaBrowser(browser_state) = &app.current_screen {
+        frame.render_widget(Clear, frame.size());
+        render_schema_browser(frame, app, browser_state);
+    }
+
+    if app.show_help {
+        render_help(frame, app);
+    }
+
+    if let CurrentScreen::ExitPrompt = app.current_screen {
+        let popup_block = Block::default()
+            .title("Unsaved changes")
+            .borders(Borders::ALL)
+            .style(app.theme.popup);
+        let exit_text = vec![
+            Line::from(Span::styled(
+                match app.dirty_tabs().len() {
+                    1 => "A request has unsaved changes. Save it before quitting?".to_string(),
+                    count => format!(
+                        "{} requests have unsaved changes. Save them before quitting?",
+                        count
+                    ),
+                },
+                app.theme.exit_mode,
+            )),
+            Line::from(""),
+            Line::from(Span::styled(
+                app.keymap.hint(KeymapContext::ExitPrompt),
+                app.theme.hint,
+            )),
+        ];
         // the `trim: false` will stop the text from being cut off when over the edge of the block
         let exit_paragraph = Paragraph::new(exit_text)
             .block(popup_block)
             .wrap(Wrap { trim: false });
 
-        let area = centered_rect(60, 25, frame.size());
+        let area = centered_rect_with_height(60, 6, frame.size());
+        frame.render_widget(Clear, area);
         frame.render_widget(exit_paragraph, area);
     }
+
+    if let CurrentScreen::OverwritePrompt(input_state) = &app.current_screen {
+        let popup_block = Block::default()
+            .title("Replace the saved request?")
+            .borders(Borders::ALL)
+            .style(app.theme.popup);
+        let prompt_text = vec![
+            Line::from(Span::styled(
+                format!(
+                    "A request named \"{}\" is already saved. Replace it?",
+                    input_state.name.text()
+                ),
+                app.theme.exit_mode,
+            )),
+            Line::from(""),
+            Line::from(Span::styled(
+                app.keymap.hint(KeymapContext::OverwritePrompt),
+                app.theme.hint,
+            )),
+        ];
+        let prompt_paragraph = Paragraph::new(prompt_text)
+            .block(popup_block)
+            .wrap(Wrap { trim: false });
+
+        let area = centered_rect_with_height(60, 6, frame.size());
+        frame.render_widget(Clear, area);
+        frame.render_widget(prompt_paragraph, area);
+    }
+
+    if let CurrentScreen::HarOverwritePrompt(editor) = &app.current_screen {
+        let popup_block = Block::default()
+            .title("Replace the file?")
+            .borders(Borders::ALL)
+            .style(app.theme.popup);
+        let prompt_text = vec![
+            Line::from(Span::styled(
+                format!("{} already exists. Replace it?", editor.text().trim()),
+                app.theme.exit_mode,
+            )),
+            Line::from(""),
+            Line::from(Span::styled(
+                app.keymap.hint(KeymapContext::OverwritePrompt),
+                app.theme.hint,
+            )),
+        ];
+        let prompt_paragraph = Paragraph::new(prompt_text)
+            .block(popup_block)
+            .wrap(Wrap { trim: false });
+
+        let area = centered_rect_with_height(60, 6, frame.size());
+        frame.render_widget(Clear, area);
+        frame.render_widget(prompt_paragraph, area);
+    }
+
+    render_toasts(frame, app);
+    if let Some(error) = app.notifications.current_error() {
+        render_error(frame, app, error);
+    }
+}
+
+fn severity_style(theme: &Theme, severity: Severity) -> Style {
+    match severity {
+        Severity::Info => theme.info,
+        Severity::Success => theme.success,
+        Severity::Warning => theme.warning,
+        Severity::Error => theme.error,
+    }
+}
+
+/// Stack recent notifications in the bottom right corner, above the footer
+fn render_toasts(frame: &mut Frame, app: &App) {
+    let toasts = app.notifications.visible_toasts();
+    if toasts.is_empty() {
+        return;
+    }
+    let screen = frame.size();
+    let lines: Vec<Line> = toasts
+        .iter()
+        .map(|toast| {
+            Line::from(Span::styled(
+                toast.message.clone(),
+                severity_style(&app.theme, toast.severity),
+            ))
+        })
+        .collect();
+    let width = lines
+        .iter()
+        .map(|line| line.width())
+        .max()
+        .unwrap_or_default() as u16
+        + 2;
+    let width = width.min(screen.width.saturating_sub(2));
+    let height = (lines.len() as u16 + 2).min(screen.height);
+    let area = Rect {
+        x: screen.right().saturating_sub(width + 1),
+        y: screen.bottom().saturating_sub(height + 3),
+        width,
+        height,
+    }
+    .intersection(screen);
+    let toast_block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(app.theme.muted)
+        .style(app.theme.popup);
+    frame.render_widget(Clear, area);
+    frame.render_widget(Paragraph::new(lines).block(toast_block), area);
+}
+
+/// A dialog with the oldest error which hasn't been dismissed, and its details
+fn render_error(frame: &mut Frame, app: &App, error: &Notification) {
+    let mut lines = vec![Line::from(Span::styled(
+        error.message.clone(),
+        app.theme.error,
+    ))];
+    if let Some(ref details) = error.details {
+        lines.push(Line::from(""));
+        lines.extend(details.lines().map(|line| Line::from(line.to_string())));
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        app.keymap.hint(KeymapContext::Alert),
+        app.theme.hint,
+    )));
+
+    let title = match app.notifications.pending_error_count() {
+        1 => "Error".to_string(),
+        count => format!("Error (1 of {})", count),
+    };
+    let width = centered_rect_with_height(60, 0, frame.size()).width;
+    // Estimate how many rows the wrapped text needs
+    let inner_width = width.saturating_sub(2).max(1) as usize;
+    let height: usize = lines
+        .iter()
+        .map(|line| line.width().div_ceil(inner_width).max(1))
+        .sum();
+    let area = centered_rect_with_height(60, height as u16 + 2, frame.size());
+    let error_block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_style(app.theme.error)
+        .style(app.theme.popup);
+    frame.render_widget(Clear, area);
+    frame.render_widget(
+        Paragraph::new(lines)
+            .wrap(Wrap { trim: false })
+            .block(error_block),
+        area,
+    );
+}
+
+/// Every notification so far, most recent first
+fn render_alert_log(frame: &mut Frame, app: &App, scroll: usize) {
+    let log_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(1), Constraint::Length(1)])
+        .split(frame.size());
+
+    let mut lines = Vec::<Line>::new();
+    for notification in app.notifications.log.iter().rev().skip(scroll) {
+        lines.push(Line::from(vec![
+            Span::styled(
+                format!("{: >8}  ", describe_age(notification.time)),
+                app.theme.muted,
+            ),
+            Span::styled(
+                format!("{: <8} ", notification.severity.label()),
+                severity_style(&app.theme, notification.severity),
+            ),
+            Span::raw(notification.message.clone()),
+        ]));
+        if let Some(ref details) = notification.details {
+            for line in details.lines() {
+                lines.push(Line::from(Span::styled(
+                    format!("{}{}", " ".repeat(19), line),
+                    app.theme.muted,
+                )));
+            }
+        }
+    }
+    if app.notifications.log.is_empty() {
+        lines.push(Line::from("  Nothing to show yet"));
+    }
+
+    let alert_log = Paragraph::new(lines).block(
+        Block::default()
+            .title("Alerts")
+            .borders(Borders::TOP | Borders::BOTTOM),
+    );
+    frame.render_widget(alert_log, log_chunks[0]);
+
+    let status_line = Span::styled(
+        format!(
+            "{} alerts - {}",
+            app.notifications.log.len(),
+            app.keymap.hint(KeymapContext::AlertLog)
+        ),
+        app.theme.hint,
+    );
+    frame.render_widget(Paragraph::new(Line::from(status_line)), log_chunks[1]);
+}
+
+fn render_har_entries(frame: &mut Frame, app: &App, selected: usize) {
+    let list_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(1), Constraint::Length(1)])
+        .split(frame.size());
+
+    // Keep the selected entry in view, between the top and bottom borders
+    let visible_rows = list_chunks[0].height.saturating_sub(2) as usize;
+    let scroll = (selected + 1).saturating_sub(visible_rows);
+    let lines: Vec<Line> = app
+        .har_entries
+        .iter()
+        .enumerate()
+        .skip(scroll)
+        .map(|(index, entry)| {
+            let status_style = match entry.response.status {
+                0 | 400.. => app.theme.error,
+                _ => app.theme.muted,
+            };
+            let mut line = Line::from(vec![
+                Span::styled(format!("{: <7} ", entry.request.method), app.theme.endpoint),
+                Span::styled(format!("{: >3}  ", entry.response.status), status_style),
+                Span::raw(entry.request.url.clone()),
+            ]);
+            if index == selected {
+                line.patch_style(app.theme.highlighted);
+            }
+            line
+        })
+        .collect();
+
+    let entry_list = Paragraph::new(lines).block(
+        Block::default()
+            .title("HAR entries")
+            .borders(Borders::TOP | Borders::BOTTOM),
+    );
+    frame.render_widget(entry_list, list_chunks[0]);
+
+    let status_line = Span::styled(
+        format!(
+            "{} of {} entries - {}",
+            selected + 1,
+            app.har_entries.len(),
+            app.keymap.hint(KeymapContext::HarEntries)
+        ),
+        app.theme.hint,
+    );
+    frame.render_widget(Paragraph::new(Line::from(status_line)), list_chunks[1]);
+}
+
+fn render_schema_browser(frame: &mut Frame, app: &App, browser_state: &SchemaBrowserState) {
+    let browser_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(1), Constraint::Length(1)])
+        .split(frame.size());
+
+    let items = app.schema_items();
+    let selected = browser_state.selected;
+    let visible_rows = browser_chunks[0].height.saturating_sub(2) as usize;
+    let scroll = (selected + 1).saturating_sub(visible_rows);
+    let lines: Vec<Line> = items
+        .iter()
+        .enumerate()
+        .skip(scroll)
+        .map(|(index, item)| {
+            let mut spans = vec![
+                Span::styled(
+                    match item.target {
+                        Some(_) => "▸ ",
+                        None => "  ",
+                    },
+                    app.theme.muted,
+                ),
+                Span::styled(item.label.clone(), app.theme.endpoint),
+            ];
+            if !item.type_name.is_empty() {
+                spans.push(Span::raw(format!(": {}", item.type_name)));
+            }
+            if let Some(ref description) = item.description {
+                // Only the first line of long descriptions fits
+                let description = description.lines().next().unwrap_or_default();
+                spans.push(Span::styled(
+                    format!("  # {}", description),
+                    app.theme.muted,
+                ));
+            }
+            let mut line = Line::from(spans);
+            if index == selected {
+                line.patch_style(app.theme.highlighted);
+            }
+            line
+        })
+        .collect();
+
+    let mut title = format!("GraphQL schema of {}", app.endpoint);
+    for (type_name, _) in browser_state.path.iter() {
+        title.push_str(&format!(" > {}", type_name));
+    }
+    let item_list = Paragraph::new(lines).block(
+        Block::default()
+            .title(title)
+            .borders(Borders::TOP | Borders::BOTTOM),
+    );
+    frame.render_widget(item_list, browser_chunks[0]);
+
+    let status_line = Span::styled(
+        format!(
+            "{} of {} - {}",
+            (selected + 1).min(items.len()),
+            items.len(),
+            app.keymap.hint(KeymapContext::SchemaBrowser)
+        ),
+        app.theme.hint,
+    );
+    frame.render_widget(Paragraph::new(Line::from(status_line)), browser_chunks[1]);
+}
+
+/// Render the message log of a WebSocket connection, and the message being composed
+fn render_websocket(frame: &mut Frame, app: &App, websocket_state: &WebSocketState) {
+    let websocket_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Min(1),
+            Constraint::Length(3),
+            Constraint::Length(1),
+        ])
+        .split(frame.size());
+
+    // The newest entries are at the bottom, unless scrolled back
+    let visible_rows = websocket_chunks[0].height.saturating_sub(2) as usize;
+    let end = websocket_state.log.len() - websocket_state.scroll.min(websocket_state.log.len());
+    let start = end.saturating_sub(visible_rows);
+    let lines: Vec<Line> = websocket_state.log[start..end]
+        .iter()
+        .map(|entry| {
+            let (arrow, style) = match entry.direction {
+                _ if entry.is_error => ("!", app.theme.error),
+                FrameDirection::Sent => ("→", app.theme.info),
+                FrameDirection::Received => ("←", app.theme.success),
+                FrameDirection::Status => ("•", app.theme.muted),
+            };
+            let text_style = match entry.direction {
+                _ if entry.is_error => app.theme.error,
+                FrameDirection::Status => app.theme.muted,
+                _ => Style::default(),
+            };
+            // Multi-line messages are shown on one line, like in the list of a section
+            let text = entry.text.replace('\n', " ");
+            Line::from(vec![
+                Span::styled(format!("{} ", entry.timestamp()), app.theme.muted),
+                Span::styled(format!("{} {: <7}", arrow, entry.kind), style),
+                Span::styled(text, text_style),
+            ])
+        })
+        .collect();
+
+    let state = match websocket_state.connection {
+        Some(ref connection) if connection.is_connected() => "connected",
+        Some(_) => "connecting",
+        None => "disconnected",
+    };
+    let log = Paragraph::new(lines).block(
+        Block::default()
+            .title(format!(
+                "WebSocket {} ({}, times in UTC)",
+                websocket_state.endpoint, state
+            ))
+            .borders(Borders::TOP | Borders::BOTTOM),
+    );
+    frame.render_widget(log, websocket_chunks[0]);
+
+    let composer_title = match websocket_state.is_closing {
+        true => "Close code and reason, e.g. 1000 done".to_string(),
+        false => format!("Message ({})", websocket_state.kind),
+    };
+    let composer_block = Block::default()
+        .title(composer_title)
+        .borders(Borders::ALL)
+        .style(app.theme.input);
+    render_line_editor(
+        frame,
+        &websocket_state.composer,
+        composer_block,
+        websocket_chunks[1],
+        !app.show_help,
+    );
+
+    let status_line = match websocket_state.message {
+        Some(ref message) => Span::styled(message.clone(), app.theme.error),
+        None => Span::styled(
+            format!(
+                "{} messages - {}",
+                websocket_state.log.len(),
+                app.keymap.hint(KeymapContext::WebSocket)
+            ),
+            app.theme.hint,
+        ),
+    };
+    frame.render_widget(Paragraph::new(Line::from(status_line)), websocket_chunks[2]);
+}
+
+/// List every key binding for the current screen, grouping the keys which share an action
+fn render_help(frame: &mut Frame, app: &App) {
+    let context = context_for(&app.current_screen);
+    let mut actions = Vec::<(Action, Vec<String>)>::new();
+    for binding in app.keymap.bindings_for(context) {
+        let key = describe_key(binding.code, binding.modifiers);
+        match actions
+            .iter_mut()
+            .find(|(action, _)| *action == binding.action)
+        {
+            Some((_, keys)) => keys.push(key),
+            None => actions.push((binding.action, vec![key])),
+        }
+    }
+
+    let key_style = app.theme.help_key;
+    let key_width = actions
+        .iter()
+        .map(|(_, keys)| keys.join(", ").chars().count())
+        .max()
+        .unwrap_or_default();
+    let help_lines: Vec<Line> = actions
+        .iter()
+        .map(|(action, keys)| {
+            Line::from(vec![
+                Span::styled(
+                    format!(" {: >width$}  ", keys.join(", "), width = key_width),
+                    key_style,
+                ),
+                Span::raw(action.description()),
+            ])
+        })
+        .collect();
+
+    // Split the bindings into columns if they don't fit the height of the terminal
+    let rows_available = frame.size().height.saturating_sub(2).max(1) as usize;
+    let column_count = help_lines.len().div_ceil(rows_available).max(1);
+    let row_count = help_lines.len().div_ceil(column_count);
+    let width_percent = match column_count {
+        1 => 60,
+        _ => 90,
+    };
+    let area = centered_rect_with_height(width_percent, row_count as u16 + 2, frame.size());
+    let help_block = Block::default()
+        .title("Help (press any key to close)")
+        .borders(Borders::ALL)
+        .style(app.theme.popup);
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints(vec![
+            Constraint::Ratio(1, column_count as u32);
+            column_count
+        ])
+        .split(help_block.inner(area));
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(help_block, area);
+    for (column, column_lines) in columns.iter().zip(help_lines.chunks(row_count)) {
+        frame.render_widget(Paragraph::new(column_lines.to_vec()), *column);
+    }
+}
+
+/// Render a text input, scrolled to keep the cursor in view, and place the cursor if it has focus
+fn render_line_editor(
+    frame: &mut Frame,
+    editor: &LineEditor,
+    block: Block,
+    area: Rect,
+    is_focused: bool,
+) {
+    let inner_area = block.inner(area);
+    let cursor_column = editor.cursor_column() as u16;
+    let scroll = cursor_column.saturating_sub(inner_area.width.saturating_sub(1));
+    let paragraph = Paragraph::new(editor.text().to_string())
+        .scroll((0, scroll))
+        .block(block);
+    frame.render_widget(paragraph, area);
+    if is_focused && inner_area.width > 0 && inner_area.height > 0 {
+        frame.set_cursor(inner_area.x + cursor_column - scroll, inner_area.y);
+    }
+}
+
+fn section_title(app: &App, section: &Section) -> String {
+    match (section, &app.response_filter) {
+        (Section::ResponseBody(_), Some(expression)) => {
+            format!("{} (filter: {})", section, expression)
+        }
+        (Section::RequestBody(_), _) => match app.section_values.graphql {
+            Some(ref graphql) if !graphql.operation_name.is_empty() => {
+                format!("{} (GraphQL: {})", section, graphql.operation_name)
+            }
+            Some(_) => format!("{} (GraphQL)", section),
+            None if app.section_values.raw_request_body.is_some() => format!("{} (raw)", section),
+            None => section.to_string(),
+        },
+        _ => section.to_string(),
+    }
+}
+
+/// Render the items of a section, scrolled to keep the selected item in view
+fn render_section_list(
+    frame: &mut Frame,
+    app: &App,
+    section: &Section,
+    area: Rect,
+    mouse_targets: &mut Vec<(Rect, MouseTarget)>,
+) {
+    // GraphQL errors are listed above the data rather than in the tree
+    let graphql_errors = match section {
+        Section::ResponseBody(_) => app.graphql_errors().clone(),
+        _ => Vec::new(),
+    };
+    let area = match graphql_errors.is_empty() {
+        true => area,
+        false => {
+            let errors_height = (graphql_errors.len() as u16 + 2).min(area.height / 2);
+            let error_chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Length(errors_height), Constraint::Min(1)])
+                .split(area);
+            let error_lines: Vec<Line> = graphql_errors
+                .into_iter()
+                .map(|error| Line::from(Span::styled(error, app.theme.error)))
+                .collect();
+            let errors = Paragraph::new(error_lines)
+                .wrap(Wrap { trim: false })
+                .block(
+                    Block::default()
+                        .title("Errors")
+                        .borders(Borders::ALL)
+                        .border_style(app.theme.error),
+                );
+            frame.render_widget(errors, error_chunks[0]);
+            error_chunks[1]
+        }
+    };
+
+    // The response body is shown as a tree instead
+    let section_items = match section {
+        Section::RequestBody(_) => Some(&app.section_values.request_body),
+        Section::RequestHeaders(_) => Some(&app.section_values.request_headers),
+        Section::ResponseBody(_) => None,
+        Section::ResponseHeaders(_) => Some(&app.section_values.response_headers),
+    };
+    let selected_index = match *section {
+        Section::RequestBody(index) => index,
+        Section::RequestHeaders(index) => index,
+        Section::ResponseBody(index) => index,
+        Section::ResponseHeaders(index) => index,
+    };
+
+    // Leave a column free for the scrollbar
+    let list_width = area.width.saturating_sub(1) as usize;
+    let displayed_response_body = app.displayed_response_body();
+    let list_lines = match (section, &*displayed_response_body) {
+        (Section::RequestBody(_), _) if app.section_values.graphql.is_some() => {
+            let query = app
+                .section_values
+                .graphql
+                .as_ref()
+                .map(|graphql| graphql.query.as_str())
+                .unwrap_or_default();
+            numbered_lines(query.lines(), app.theme.muted)
+        }
+        (Section::RequestBody(_), _) if app.section_values.raw_request_body.is_some() => {
+            let raw_body = app
+                .section_values
+                .raw_request_body
+                .as_deref()
+                .unwrap_or_default();
+            numbered_lines(raw_body.lines(), app.theme.muted)
+        }
+        (Section::ResponseBody(_), Ok(response_body)) => {
+            json_tree_lines(&flatten(response_body, &app.response_tree), &app.theme)
+        }
+        (Section::ResponseBody(_), Err(err)) => {
+            vec![Line::from(Span::styled(err, app.theme.error))]
+        }
+        // TODO: Sort this list by key
+        _ => section_items
+            .into_iter()
+            .flatten()
+            .map(|(key, value)| Line::from(format!("{: <25} : {}", key, value)))
+            .collect(),
+    };
+    let list_length = list_lines.len();
+    let longest_line = list_lines.iter().map(Line::width).max().unwrap_or_default();
+    app.max_horizontal_scroll.set(
+        app.max_horizontal_scroll
+            .get()
+            .max(longest_line.saturating_sub(list_width)),
+    );
+    let list_items: Vec<ListItem> = list_lines
+        .into_iter()
+        .map(|line| {
+            ListItem::new(fit_line(
+                line,
+                list_width,
+                app.horizontal_scroll,
+                app.wrap_values,
+            ))
+        })
+        .collect();
+
+    let item_heights: Vec<u16> = list_items.iter().map(|item| item.height() as u16).collect();
+
+    let list = List::new(list_items).highlight_style(app.theme.highlighted);
+    let mut list_state = ListState::default().with_selected(selected_index);
+    frame.render_stateful_widget(list, area, &mut list_state);
+
+    let is_request_pane = section.is_request_section();
+    mouse_targets.push((area, MouseTarget::Pane(is_request_pane)));
+    let mut item_y = area.y;
+    for (index, height) in item_heights.iter().enumerate().skip(list_state.offset()) {
+        if item_y >= area.bottom() {
+            break;
+        }
+        let item_area = Rect::new(area.x, item_y, area.width, *height).intersection(area);
+        mouse_targets.push((item_area, MouseTarget::ListItem(is_request_pane, index)));
+        item_y += height;
+    }
+
+    if list_length > area.height as usize {
+        let scrollbar = Scrollbar::default().orientation(ScrollbarOrientation::VerticalRight);
+        let mut scrollbar_state = ScrollbarState::new(list_length).position(list_state.offset());
+        frame.render_stateful_widget(scrollbar, area, &mut scrollbar_state);
+    }
+}
+
+/// Prefix each line with its line number
+fn numbered_lines<'a, 'b>(
+    lines: impl Iterator<Item = &'b str>,
+    gutter_style: Style,
+) -> Vec<Line<'a>> {
+    let lines: Vec<&str> = lines.collect();
+    let gutter_width = lines.len().to_string().len();
+    lines
+        .into_iter()
+        .enumerate()
+        .map(|(line_number, line)| {
+            Line::from(vec![
+                Span::styled(
+                    format!("{: >width$} │ ", line_number + 1, width = gutter_width),
+                    gutter_style,
+                ),
+                Span::raw(line.to_string()),
+            ])
+        })
+        .collect()
+}
+
+/// Render the raw request body editor, with line numbers, bracket matching and JSON errors
+fn render_body_editor(frame: &mut Frame, app: &App, editor_state: &BodyEditorState, area: Rect) {
+    let editor = &editor_state.editor;
+    let json_error = editor.json_error();
+    let (cursor_row, cursor_char) = editor.cursor();
+
+    let editor_block = Block::default()
+        .title("Request Body (raw)")
+        .borders(Borders::ALL)
+        .border_style(app.theme.selected);
+    let editor_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(1), Constraint::Length(1)])
+        .split(editor_block.inner(area));
+    frame.render_widget(editor_block, area);
+
+    render_text_editor(
+        frame,
+        app,
+        editor,
+        json_error.as_ref().map(|error| error.line),
+        editor_chunks[0],
+        !app.show_help,
+    );
+
+    let status_line = match (&editor_state.message, &json_error) {
+        (Some(message), _) => Span::styled(message.clone(), app.theme.error),
+        (None, Some(error)) => {
+            Span::styled(format!("Invalid JSON: {}", error.message), app.theme.error)
+        }
+        (None, None) => Span::styled(
+            format!(
+                "Line {}, column {} - {}",
+                cursor_row + 1,
+                cursor_char + 1,
+                app.keymap.hint(KeymapContext::BodyEditor)
+            ),
+            app.theme.hint,
+        ),
+    };
+    frame.render_widget(Paragraph::new(Line::from(status_line)), editor_chunks[1]);
+}
+
+/// Render the query, variables and operation name of a GraphQL request
+fn render_graphql_editor(
+    frame: &mut Frame,
+    app: &App,
+    editor_state: &GraphqlEditorState,
+    area: Rect,
+) {
+    let editor_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Min(3),
+            Constraint::Length(6),
+            Constraint::Length(3),
+            Constraint::Length(1),
+        ])
+        .split(area);
+    let focus = editor_state.focus;
+    let field_block = |title: &str, field: GraphqlField| {
+        Block::default()
+            .title(title.to_string())
+            .borders(Borders::ALL)
+            .border_style(match focus == field {
+                true => app.theme.selected,
+                false => app.theme.muted,
+            })
+    };
+    // The variables are optional, so only complain about them once there are some
+    let variables_error = match editor_state.variables.text().trim().is_empty() {
+        true => None,
+        false => editor_state.variables.json_error(),
+    };
+
+    let query_block = field_block("Query (GraphQL)", GraphqlField::Query);
+    let query_area = query_block.inner(editor_chunks[0]);
+    frame.render_widget(query_block, editor_chunks[0]);
+    render_text_editor(
+        frame,
+        app,
+        &editor_state.query,
+        None,
+        query_area,
+        focus == GraphqlField::Query && !app.show_help,
+    );
+
+    let variables_block = field_block("Variables (JSON)", GraphqlField::Variables);
+    let variables_area = variables_block.inner(editor_chunks[1]);
+    frame.render_widget(variables_block, editor_chunks[1]);
+    render_text_editor(
+        frame,
+        app,
+        &editor_state.variables,
+        variables_error.as_ref().map(|error| error.line),
+        variables_area,
+        focus == GraphqlField::Variables && !app.show_help,
+    );
+
+    render_line_editor(
+        frame,
+        &editor_state.operation_name,
+        field_block("Operation name", GraphqlField::OperationName),
+        editor_chunks[2],
+        focus == GraphqlField::OperationName && !app.show_help,
+    );
+
+    let status_line = match (&editor_state.message, &variables_error) {
+        (Some(message), _) => Span::styled(message.clone(), app.theme.hint),
+        (None, Some(error)) => Span::styled(
+            format!("Invalid variables: {}", error.message),
+            app.theme.error,
+        ),
+        (None, None) => {
+            let schema_note = match app.schema() {
+                Some(_) => "",
+                None => "No schema yet - ",
+            };
+            Span::styled(
+                format!(
+                    "{}{}",
+                    schema_note,
+                    app.keymap.hint(KeymapContext::GraphqlEditor)
+                ),
+                app.theme.hint,
+            )
+        }
+    };
+    frame.render_widget(Paragraph::new(Line::from(status_line)), editor_chunks[3]);
+}
+
+/// Render the visible lines of a text editor with line numbers and matching brackets, and place
+/// the cursor if it has focus
+fn render_text_editor(
+    frame: &mut Frame,
+    app: &App,
+    editor: &TextEditor,
+    error_line: Option<usize>,
+    area: Rect,
+    is_focused: bool,
+) {
+    let (cursor_row, _) = editor.cursor();
+    let gutter_width = editor.lines().len().to_string().len();
+    let gutter_columns = gutter_width as u16 + 3;
+    let text_width = area.width.saturating_sub(gutter_columns);
+    let cursor_column = editor.cursor_column() as u16;
+    let horizontal_scroll = match is_focused {
+        true => cursor_column.saturating_sub(text_width.saturating_sub(1)) as usize,
+        false => 0,
+    };
+    let brackets = match is_focused {
+        true => editor.matching_bracket(),
+        false => None,
+    };
+
+    let visible_lines: Vec<Line> = editor
+        .lines()
+        .iter()
+        .enumerate()
+        .skip(editor.scroll)
+        .take(area.height as usize)
+        .map(|(row, line)| {
+            let gutter_style = match error_line {
+                Some(error_line) if error_line == row => app.theme.error,
+                _ => app.theme.muted,
+            };
+            let mut spans = vec![Span::styled(
+                format!("{: >width$} │ ", row + 1, width = gutter_width),
+                gutter_style,
+            )];
+            let mut text = Vec::<Span>::new();
+            for (column, c) in line.chars().enumerate() {
+                let is_bracket = brackets.is_some_and(|(bracket, matching)| {
+                    bracket == (row, column) || matching == (row, column)
+                });
+                let style = match is_bracket {
+                    true => app.theme.search_match,
+                    false => Style::default(),
+                };
+                match text.last_mut() {
+                    Some(span) if span.style == style => span.content.to_mut().push(c),
+                    _ => text.push(Span::styled(c.to_string(), style)),
+                }
+            }
+            spans.extend(
+                fit_line(Line::from(text), 0, horizontal_scroll, false)
+                    .lines
+                    .into_iter()
+                    .flat_map(|line| line.spans),
+            );
+            Line::from(spans)
+        })
+        .collect();
+    frame.render_widget(Paragraph::new(visible_lines), area);
+
+    let cursor_y = cursor_row.saturating_sub(editor.scroll) as u16;
+    if is_focused && cursor_y < area.height {
+        frame.set_cursor(
+            area.x + gutter_columns + cursor_column - horizontal_scroll as u16,
+            area.y + cursor_y,
+        );
+    }
+}
+
+/// Render the raw response full screen, with line numbers and search matches highlighted
+fn render_pager(frame: &mut Frame, app: &App, pager_state: &PagerState) {
+    let pager_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(1), Constraint::Length(1)])
+        .split(frame.size());
+
+    let lines = match app.last_response {
+        Some(ref response) => pager_lines(response, pager_state),
+        None => Vec::new(),
+    };
+    let gutter_width = lines.len().to_string().len();
+    let match_style = app.theme.search_match;
+    let current_match_line = pager_state
+        .current_match
+        .map(|index| pager_state.matches[index]);
+
+    let visible_lines: Vec<Line> = lines
+        .iter()
+        .enumerate()
+        .skip(pager_state.scroll)
+        .take(pager_chunks[0].height as usize)
+        .map(|(line_number, line)| {
+            let gutter_style = match Some(line_number) == current_match_line {
+                true => match_style,
+                false => app.theme.muted,
+            };
+            let mut spans = vec![Span::styled(
+                format!("{: >width$} │ ", line_number + 1, width = gutter_width),
+                gutter_style,
+            )];
+            // A carriage return would move the cursor back over the line
+            spans.extend(highlight_matches(
+                line.strip_suffix('\r').unwrap_or(line),
+                pager_state.search.text(),
+                match_style,
+            ));
+            Line::from(spans)
+        })
+        .collect();
+
+    let title = format!(
+        "{} ({}{})",
+        pager_state.content,
+        match pager_state.pretty {
+            true => "pretty",
+            false => "raw",
+        },
+        match pager_state.wrap {
+            true => ", wrapped",
+            false => "",
+        }
+    );
+    let mut pager = Paragraph::new(visible_lines).block(
+        Block::default()
+            .title(title)
+            .borders(Borders::TOP | Borders::BOTTOM),
+    );
+    if pager_state.wrap {
+        pager = pager.wrap(Wrap { trim: false });
+    }
+    frame.render_widget(pager, pager_chunks[0]);
+
+    let status_line = match pager_state.is_searching {
+        true => Line::from(format!("/{}", pager_state.search.text())),
+        false => Line::from(Span::styled(
+            format!(
+                "Line {}/{}{} - {}",
+                (pager_state.scroll + 1).min(lines.len()),
+                lines.len(),
+                match (pager_state.search.is_empty(), pager_state.current_match) {
+                    (true, _) => String::new(),
+                    (false, Some(index)) =>
+                        format!(" - match {}/{}", index + 1, pager_state.matches.len()),
+                    (false, None) => " - no matches".to_string(),
+                },
+                app.keymap.hint(KeymapContext::Pager)
+            ),
+            app.theme.hint,
+        )),
+    };
+    frame.render_widget(Paragraph::new(status_line), pager_chunks[1]);
+    if pager_state.is_searching && !app.show_help {
+        let status_area = pager_chunks[1];
+        let cursor_x = (status_area.x + 1 + pager_state.search.cursor_column() as u16)
+            .min(status_area.right().saturating_sub(1));
+        frame.set_cursor(cursor_x, status_area.y);
+    }
+}
+
+/// Render a structural diff of the response bodies and a textual diff of the response headers
+fn render_diff(frame: &mut Frame, app: &App, diff_state: &DiffState) {
+    let diff_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(1), Constraint::Length(1)])
+        .split(frame.size());
+
+    let baseline = match app.history.get(diff_state.baseline_index) {
+        Some(baseline) => baseline,
+        None => return,
+    };
+    let added_style = app.theme.added;
+    let removed_style = app.theme.removed;
+    let changed_style = app.theme.changed;
+    let heading_style = app.theme.text;
+
+    let (body_changes, header_changes) = match app.response_changes(diff_state.baseline_index) {
+        Some(changes) => changes,
+        None => return,
+    };
+
+    let mut lines = vec![Line::from(Span::styled("Body", heading_style))];
+    if body_changes.is_empty() {
+        lines.push(Line::from("  No changes"));
+    }
+    for change in body_changes {
+        lines.push(match change {
+            JsonChange::Added(path, value) => {
+                Line::from(Span::styled(format!("+ {}: {}", path, value), added_style))
+            }
+            JsonChange::Removed(path, value) => Line::from(Span::styled(
+                format!("- {}: {}", path, value),
+                removed_style,
+            )),
+            JsonChange::Changed(path, old_value, new_value) => Line::from(Span::styled(
+                format!("~ {}: {} -> {}", path, old_value, new_value),
+                changed_style,
+            )),
+        });
+    }
+
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled("Headers", heading_style)));
+    for change in header_changes {
+        lines.push(match change {
+            LineChange::Unchanged(line) => Line::from(format!("  {}", line)),
+            LineChange::Added(line) => Line::from(Span::styled(format!("+ {}", line), added_style)),
+            LineChange::Removed(line) => {
+                Line::from(Span::styled(format!("- {}", line), removed_style))
+            }
+        });
+    }
+
+    let scroll = diff_state.scroll.min(lines.len().saturating_sub(1));
+    let title = format!(
+        "Comparing with #{} {} {}{}",
+        diff_state.baseline_index + 1,
+        baseline.method,
+        baseline.endpoint,
+        match app.pinned_baseline == Some(diff_state.baseline_index) {
+            true => " (pinned)",
+            false => "",
+        }
+    );
+    let diff = Paragraph::new(lines).scroll((scroll as u16, 0)).block(
+        Block::default()
+            .title(title)
+            .borders(Borders::TOP | Borders::BOTTOM),
+    );
+    frame.render_widget(diff, diff_chunks[0]);
+
+    let status_line = Span::styled(
+        format!(
+            "Baseline {}/{} - {}",
+            diff_state.baseline_index + 1,
+            app.history.len(),
+            app.keymap.hint(KeymapContext::Diff)
+        ),
+        app.theme.hint,
+    );
+    frame.render_widget(Paragraph::new(Line::from(status_line)), diff_chunks[1]);
+}
+
+/// Split a line into spans, highlighting every case insensitive occurrence of the query
+fn highlight_matches<'a>(line: &str, query: &str, match_style: Style) -> Vec<Span<'a>> {
+    if query.is_empty() {
+        return vec![Span::raw(line.to_string())];
+    }
+    let lowercase_line = line.to_lowercase();
+    let lowercase_query = query.to_lowercase();
+    // Lowercasing can change byte lengths, in which case highlighting would be misaligned
+    if lowercase_line.len() != line.len() {
+        return vec![Span::raw(line.to_string())];
+    }
+
+    let mut spans = Vec::<Span>::new();
+    let mut position = 0;
+    while let Some(offset) = lowercase_line[position..].find(&lowercase_query) {
+        let start = position + offset;
+        let end = start + lowercase_query.len();
+        if !line.is_char_boundary(start) || !line.is_char_boundary(end) {
+            break;
+        }
+        spans.push(Span::raw(line[position..start].to_string()));
+        spans.push(Span::styled(line[start..end].to_string(), match_style));
+        position = end;
+    }
+    spans.push(Span::raw(line[position..].to_string()));
+    spans
+}
+
+/// Render the visible lines of a JSON tree as indented, syntax highlighted lines
+fn json_tree_lines<'a>(lines: &[TreeLine], theme: &Theme) -> Vec<Line<'a>> {
+    let punctuation_style = theme.json_punctuation;
+    let size_style = theme.muted;
+
+    lines
+        .iter()
+        .map(|line| {
+            let mut spans = vec![Span::raw("  ".repeat(line.depth))];
+            if let Some(ref key) = line.key {
+                spans.push(Span::styled(key.clone(), theme.json_key));
+                spans.push(Span::styled(": ", punctuation_style));
+            }
+            let (open, close) = match line.value {
+                JsonValue::Array(_) => ("[", "]"),
+                _ => ("{", "}"),
+            };
+            match line.kind {
+                TreeLineKind::Scalar => spans.push(json_scalar_span(line.value, theme)),
+                TreeLineKind::Open => {
+                    spans.push(Span::styled(open, punctuation_style));
+                    spans.push(Span::styled(
+                        format!("  {}", describe_size(line.value)),
+                        size_style,
+                    ));
+                }
+                TreeLineKind::Collapsed => {
+                    spans.push(Span::styled(
+                        format!("{}…{}", open, close),
+                        punctuation_style,
+                    ));
+                    spans.push(Span::styled(
+                        format!("  {}", describe_size(line.value)),
+                        size_style,
+                    ));
+                }
+                TreeLineKind::Close => spans.push(Span::styled(close, punctuation_style)),
+            }
+            Line::from(spans)
+        })
+        .collect()
+}
+
+/// Fit a line to the viewport width, either by wrapping it or by scrolling it horizontally
+fn fit_line(line: Line, width: usize, horizontal_scroll: usize, wrap: bool) -> Text {
+    let wrap = wrap && width > 0;
+    // Columns are counted by display width, so wide characters take up two
+    let mut rows: Vec<Vec<(char, Style)>> = vec![Vec::new()];
+    let mut row_width = 0;
+    let mut skipped_width = 0;
+    for (c, style) in line
+        .spans
+        .iter()
+        .flat_map(|span| span.content.chars().map(move |c| (c, span.style)))
+    {
+        let char_width = c.width().unwrap_or(0);
+        if !wrap {
+            if skipped_width < horizontal_scroll {
+                skipped_width += char_width;
+                continue;
+            }
+        } else if row_width + char_width > width && row_width > 0 {
+            rows.push(Vec::new());
+            row_width = 0;
+        }
+        row_width += char_width;
+        if let Some(row) = rows.last_mut() {
+            row.push((c, style));
+        }
+    }
+
+    Text::from(
+        rows.into_iter()
+            .map(|row| {
+                let mut spans = Vec::<Span>::new();
+                for (c, style) in row {
+                    match spans.last_mut() {
+                        Some(span) if span.style == style => span.content.to_mut().push(c),
+                        _ => spans.push(Span::styled(c.to_string(), style)),
+                    }
+                }
+                Line::from(spans)
+            })
+            .collect::<Vec<Line>>(),
+    )
+}
+
+fn json_scalar_span<'a>(value: &JsonValue, theme: &Theme) -> Span<'a> {
+    match value {
+        JsonValue::String(_) => Span::styled(value.to_string(), theme.json_string),
+        JsonValue::Number(_) => Span::styled(value.to_string(), theme.json_number),
+        JsonValue::Bool(_) => Span::styled(value.to_string(), theme.json_bool),
+        _ => Span::styled(value.to_string(), theme.json_null),
+    }
+}
+
+/// Strip the scheme from an endpoint and truncate it to fit in the tab bar
+fn shorten_endpoint(endpoint: &str, max_length: usize) -> String {
+    let endpoint = match endpoint.split_once("://") {
+        Some((_, without_scheme)) => without_scheme,
+        None => endpoint,
+    };
+    match endpoint.chars().count() {
+        0 => "(new)".to_string(),
+        length if length > max_length => format!(
+            "{}…",
+            endpoint.chars().take(max_length - 1).collect::<String>()
+        ),
+        _ => endpoint.to_string(),
+    }
+}
+
+/// helper function to create a centered rect with a fixed height, clipped to the available rect `r`
+fn centered_rect_with_height(percent_x: u16, height: u16, r: Rect) -> Rect {
+    let height = height.min(r.height);
+    let popup_area = Rect {
+        y: r.y + (r.height - height) / 2,
+        height,
+        ..r
+    };
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage((100 - percent_x) / 2),
+            Constraint::Percentage(percent_x),
+            Constraint::Percentage((100 - percent_x) / 2),
+        ])
+        .split(popup_area)[1]
 }
 
 /// helper function to create a centered rect using up certain percentage of the available rect `r`
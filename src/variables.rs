@@ -0,0 +1,102 @@
+use serde_json::value::Value as JsonValue;
+use std::collections::HashMap;
+
+/// Replace every `{{name}}` with its value, noting the names which have no value.
+/// Unknown variables are left as they are.
+pub fn substitute(
+    text: &str,
+    variables: &HashMap<String, String>,
+    unknown: &mut Vec<String>,
+) -> String {
+    let mut output = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let Some(length) = rest[start + 2..].find("}}") else {
+            break;
+        };
+        let name = rest[start + 2..start + 2 + length].trim();
+        if name.contains('{') {
+            // Only the innermost braces can be a variable
+            output.push_str(&rest[..start + 1]);
+            rest = &rest[start + 1..];
+            continue;
+        }
+        output.push_str(&rest[..start]);
+        match variables.get(name) {
+            Some(value) => output.push_str(value),
+            None => {
+                output.push_str(&rest[start..start + length + 4]);
+                if !unknown.iter().any(|unknown_name| unknown_name == name) {
+                    unknown.push(name.to_string());
+                }
+            }
+        }
+        rest = &rest[start + length + 4..];
+    }
+    output.push_str(rest);
+    output
+}
+
+/// Substitute variables into the string values of headers or body pairs
+pub fn substitute_values(
+    values: &HashMap<String, JsonValue>,
+    variables: &HashMap<String, String>,
+    unknown: &mut Vec<String>,
+) -> HashMap<String, JsonValue> {
+    values
+        .iter()
+        .map(|(key, value)| match value {
+            JsonValue::String(text) => (
+                key.clone(),
+                JsonValue::String(substitute(text, variables, unknown)),
+            ),
+            _ => (key.clone(), value.clone()),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn variables() -> HashMap<String, String> {
+        HashMap::from([
+            ("host".to_string(), "example.com".to_string()),
+            ("id".to_string(), "1".to_string()),
+        ])
+    }
+
+    #[test]
+    fn substitutes_known_variables() {
+        let mut unknown = Vec::new();
+        let text = substitute(
+            "https://{{host}}/users/{{ id }}?token={{token}}&again={{token}}",
+            &variables(),
+            &mut unknown,
+        );
+        assert_eq!(
+            text,
+            "https://example.com/users/1?token={{token}}&again={{token}}"
+        );
+        assert_eq!(unknown, vec!["token"]);
+    }
+
+    #[test]
+    fn nested_braces() {
+        let mut unknown = Vec::new();
+        assert_eq!(substitute("{{{id}}}", &variables(), &mut unknown), "{1}");
+        assert_eq!(substitute("{{a{{id}}", &variables(), &mut unknown), "{{a1");
+        assert!(unknown.is_empty());
+    }
+
+    #[test]
+    fn unterminated_braces() {
+        let mut unknown = Vec::new();
+        assert_eq!(
+            substitute("{{id}} and {{host", &variables(), &mut unknown),
+            "1 and {{host"
+        );
+        assert_eq!(substitute("}}{{", &variables(), &mut unknown), "}}{{");
+        assert!(unknown.is_empty());
+    }
+}
@@ -0,0 +1,530 @@
+use crate::line_editor::LineEditor;
+use futures::{SinkExt, StreamExt};
+use serde_json::value::Value as JsonValue;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::http::{HeaderName, HeaderValue};
+use tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
+use tokio_tungstenite::tungstenite::protocol::CloseFrame;
+use tokio_tungstenite::tungstenite::Message;
+
+/// How the text being composed is sent
+#[derive(Clone, Copy, PartialEq)]
+pub enum MessageKind {
+    Text,
+    Json,   // Text which has to be valid JSON
+    Binary, // Written as hex, e.g. "de ad be ef"
+}
+
+impl MessageKind {
+    pub fn next(self) -> MessageKind {
+        match self {
+            MessageKind::Text => MessageKind::Json,
+            MessageKind::Json => MessageKind::Binary,
+            MessageKind::Binary => MessageKind::Text,
+        }
+    }
+}
+
+impl std::fmt::Display for MessageKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MessageKind::Text => write!(f, "Text"),
+            MessageKind::Json => write!(f, "JSON"),
+            MessageKind::Binary => write!(f, "Binary (hex)"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum FrameDirection {
+    Sent,
+    Received,
+    Status, // Connecting, closing and errors
+}
+
+/// A line of the message log
+pub struct LogEntry {
+    pub time: SystemTime,
+    pub direction: FrameDirection,
+    pub kind: &'static str, // e.g. "text" or "ping"
+    pub text: String,
+    pub is_error: bool,
+}
+
+impl LogEntry {
+    fn frame(direction: FrameDirection, message: &Message) -> LogEntry {
+        let (kind, text) = match message {
+            Message::Text(text) => ("text", text.clone()),
+            Message::Binary(data) => ("binary", describe_bytes(data)),
+            Message::Ping(data) => ("ping", describe_bytes(data)),
+            Message::Pong(data) => ("pong", describe_bytes(data)),
+            Message::Close(Some(frame)) => (
+                "close",
+                format!("{} {}", u16::from(frame.code), frame.reason),
+            ),
+            Message::Close(None) => ("close", String::new()),
+            Message::Frame(frame) => ("frame", describe_bytes(frame.payload())),
+        };
+        LogEntry {
+            time: SystemTime::now(),
+            direction,
+            kind,
+            text: text.trim_end().to_string(),
+            is_error: false,
+        }
+    }
+
+    pub fn status(text: impl Into<String>) -> LogEntry {
+        LogEntry {
+            time: SystemTime::now(),
+            direction: FrameDirection::Status,
+            kind: "status",
+            text: text.into(),
+            is_error: false,
+        }
+    }
+
+    pub fn error(text: impl Into<String>) -> LogEntry {
+        LogEntry {
+            is_error: true,
+            kind: "error",
+            ..LogEntry::status(text)
+        }
+    }
+
+    /// The time of day in UTC, such as 14:03:09.125
+    pub fn timestamp(&self) -> String {
+        let since_epoch = self.time.duration_since(UNIX_EPOCH).unwrap_or_default();
+        let seconds_of_day = since_epoch.as_secs() % 86400;
+        format!(
+            "{:02}:{:02}:{:02}.{:03}",
+            seconds_of_day / 3600,
+            seconds_of_day % 3600 / 60,
+            seconds_of_day % 60,
+            since_epoch.subsec_millis()
+        )
+    }
+}
+
+/// The byte count and hex dump of a binary payload
+fn describe_bytes(data: &[u8]) -> String {
+    let hex: Vec<String> = data.iter().map(|byte| format!("{:02x}", byte)).collect();
+    match data.len() {
+        0 => String::new(),
+        1 => format!("1 byte: {}", hex.join(" ")),
+        length => format!("{} bytes: {}", length, hex.join(" ")),
+    }
+}
+
+fn parse_hex(text: &str) -> Result<Vec<u8>, String> {
+    let digits: String = text.chars().filter(|c| !c.is_whitespace()).collect();
+    if !digits.len().is_multiple_of(2) || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(
+            "Write binary messages as pairs of hex digits, e.g. \"de ad be ef\"".to_string(),
+        );
+    }
+    (0..digits.len())
+        .step_by(2)
+        .map(|index| {
+            u8::from_str_radix(&digits[index..index + 2], 16).map_err(|err| err.to_string())
+        })
+        .collect()
+}
+
+pub fn build_message(kind: MessageKind, text: &str) -> Result<Message, String> {
+    match kind {
+        MessageKind::Text => Ok(Message::Text(text.to_string())),
+        MessageKind::Json => match serde_json::from_str::<JsonValue>(text) {
+            Ok(_) => Ok(Message::Text(text.to_string())),
+            Err(err) => Err(format!("Invalid JSON: {}", err)),
+        },
+        MessageKind::Binary => parse_hex(text).map(Message::Binary),
+    }
+}
+
+/// A close frame from a status code and an optional reason, e.g. "1000 done"
+pub fn build_close_message(text: &str) -> Result<Message, String> {
+    let (code, reason) = match text.trim().split_once(char::is_whitespace) {
+        Some((code, reason)) => (code, reason.trim()),
+        None => (text.trim(), ""),
+    };
+    let code = match code {
+        "" => 1000,
+        _ => code
+            .parse::<u16>()
+            .ok()
+            // Other codes are reserved, or can't be sent in a close frame
+            .filter(|code| matches!(code, 1000..=1003 | 1007..=1011 | 3000..=4999))
+            .ok_or_else(|| format!("Invalid close code \"{}\", expected e.g. 1000", code))?,
+    };
+    Ok(Message::Close(Some(CloseFrame {
+        code: CloseCode::from(code),
+        reason: reason.to_string().into(),
+    })))
+}
+
+/// An open connection, whose frames are sent and received by a background task
+pub struct WebSocketConnection {
+    outgoing: mpsc::UnboundedSender<Message>,
+    incoming: mpsc::UnboundedReceiver<LogEntry>,
+    is_connected: Arc<AtomicBool>, // Set once the handshake has finished
+}
+
+impl WebSocketConnection {
+    /// Start connecting in the background, so the UI keeps drawing while the handshake happens.
+    /// Only an invalid endpoint or header fails straight away; anything after that is logged.
+    pub fn connect(
+        endpoint: &str,
+        headers: &[(String, String)],
+        timeout: Duration,
+    ) -> Result<WebSocketConnection, String> {
+        let mut request = endpoint
+            .into_client_request()
+            .map_err(|err| format!("Invalid WebSocket endpoint \"{}\": {}", endpoint, err))?;
+        for (key, value) in headers.iter() {
+            let name = HeaderName::from_bytes(key.as_bytes())
+                .map_err(|_| format!("Invalid header name \"{}\"", key))?;
+            let value = HeaderValue::from_str(value)
+                .map_err(|_| format!("Invalid value for the {} header", key))?;
+            request.headers_mut().append(name, value);
+        }
+
+        let (outgoing, mut outgoing_receiver) = mpsc::unbounded_channel::<Message>();
+        let (incoming_sender, incoming) = mpsc::unbounded_channel::<LogEntry>();
+        let is_connected = Arc::new(AtomicBool::new(false));
+        let task_is_connected = is_connected.clone();
+        tokio::spawn(async move {
+            // Frames queued while connecting are sent once connected
+            let stream = match tokio::time::timeout(
+                timeout,
+                tokio_tungstenite::connect_async(request),
+            )
+            .await
+            {
+                Ok(Ok((stream, _))) => stream,
+                Ok(Err(err)) => {
+                    let _ = incoming_sender.send(LogEntry::error(err.to_string()));
+                    return;
+                }
+                Err(_) => {
+                    let _ = incoming_sender.send(LogEntry::error(format!(
+                        "Timed out after {} seconds",
+                        timeout.as_secs_f64()
+                    )));
+                    return;
+                }
+            };
+            task_is_connected.store(true, Ordering::Relaxed);
+            let _ = incoming_sender.send(LogEntry::status("Connected"));
+            let (mut sink, mut stream) = stream.split();
+            loop {
+                tokio::select! {
+                    message = outgoing_receiver.recv() => match message {
+                        Some(message) => {
+                            let entry = LogEntry::frame(FrameDirection::Sent, &message);
+                            if let Err(err) = sink.send(message).await {
+                                let _ = incoming_sender.send(LogEntry::error(err.to_string()));
+                                break;
+                            }
+                            let _ = incoming_sender.send(entry);
+                        }
+                        // The connection was dropped, so say goodbye
+                        None => {
+                            let _ = sink.send(Message::Close(None)).await;
+                            break;
+                        }
+                    },
+                    frame = stream.next() => match frame {
+                        Some(Ok(message)) => {
+                            let _ = incoming_sender
+                                .send(LogEntry::frame(FrameDirection::Received, &message));
+                        }
+                        Some(Err(err)) => {
+                            let _ = incoming_sender.send(LogEntry::error(err.to_string()));
+                            break;
+                        }
+                        None => {
+                            let _ = incoming_sender.send(LogEntry::status("Disconnected"));
+                            break;
+                        }
+                    },
+                }
+            }
+        });
+
+        Ok(WebSocketConnection {
+            outgoing,
+            incoming,
+            is_connected,
+        })
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.is_connected.load(Ordering::Relaxed)
+    }
+
+    /// Queue a frame, returning false if the connection has ended
+    pub fn send(&self, message: Message) -> bool {
+        self.outgoing.send(message).is_ok()
+    }
+
+    /// The frames sent and received since last time, and whether the connection is still open
+    pub fn receive(&mut self) -> (Vec<LogEntry>, bool) {
+        let mut entries = Vec::new();
+        loop {
+            match self.incoming.try_recv() {
+                Ok(entry) => entries.push(entry),
+                Err(mpsc::error::TryRecvError::Empty) => return (entries, true),
+                Err(mpsc::error::TryRecvError::Disconnected) => return (entries, false),
+            }
+        }
+    }
+}
+
+pub struct WebSocketState {
+    pub endpoint: String, // With its variables filled in
+    pub headers: Vec<(String, String)>,
+    pub timeout: Duration,                       // For the handshake
+    pub connection: Option<WebSocketConnection>, // None once the connection has ended
+    pub log: Vec<LogEntry>,
+    pub composer: LineEditor,
+    pub kind: MessageKind,
+    pub is_closing: bool, // Whether the composer holds a close code and reason
+    pub scroll: usize,    // Entries scrolled back from the newest
+    pub message: Option<String>, // Shown in the status line, e.g. if the JSON is invalid
+}
+
+impl WebSocketState {
+    pub fn new(
+        endpoint: String,
+        headers: Vec<(String, String)>,
+        timeout: Duration,
+    ) -> WebSocketState {
+        WebSocketState {
+            endpoint,
+            headers,
+            timeout,
+            connection: None,
+            log: Vec::new(),
+            composer: LineEditor::new(""),
+            kind: MessageKind::Text,
+            is_closing: false,
+            scroll: 0,
+            message: None,
+        }
+    }
+
+    pub fn connect(&mut self) {
+        // Dropping a previous connection closes it
+        self.connection = None;
+        self.log
+            .push(LogEntry::status(format!("Connecting to {}", self.endpoint)));
+        match WebSocketConnection::connect(&self.endpoint, &self.headers, self.timeout) {
+            Ok(connection) => self.connection = Some(connection),
+            Err(err) => self.log.push(LogEntry::error(err)),
+        }
+        self.scroll = 0;
+    }
+
+    /// Add the frames which have arrived to the log
+    pub fn receive(&mut self) {
+        let Some(ref mut connection) = self.connection else {
+            return;
+        };
+        let (entries, is_open) = connection.receive();
+        if !entries.is_empty() && self.scroll > 0 {
+            // Stay on the same entries while scrolled back
+            self.scroll += entries.len();
+        }
+        self.log.extend(entries);
+        if !is_open {
+            self.connection = None;
+        }
+    }
+
+    pub fn send(&mut self, message: Message) -> bool {
+        match self.connection {
+            Some(ref connection) if connection.send(message) => {
+                self.message = None;
+                true
+            }
+            _ => {
+                self.message = Some("Not connected".to_string());
+                false
+            }
+        }
+    }
+
+    pub fn ping(&mut self) {
+        self.send(Message::Ping(Vec::new()));
+    }
+
+    pub fn scroll_by(&mut self, amount: isize) {
+        let max_scroll = self.log.len().saturating_sub(1);
+        self.scroll = self.scroll.saturating_add_signed(amount).min(max_scroll);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    #[test]
+    fn parses_hex() {
+        assert_eq!(parse_hex("de ad BE ef"), Ok(vec![0xde, 0xad, 0xbe, 0xef]));
+        assert_eq!(parse_hex("dead\nbeef"), Ok(vec![0xde, 0xad, 0xbe, 0xef]));
+        assert_eq!(parse_hex(""), Ok(Vec::new()));
+        assert!(parse_hex("abc").is_err());
+        assert!(parse_hex("zz").is_err());
+    }
+
+    #[test]
+    fn builds_messages() {
+        assert_eq!(
+            build_message(MessageKind::Text, "not {json"),
+            Ok(Message::Text("not {json".to_string()))
+        );
+        assert_eq!(
+            build_message(MessageKind::Json, "{\"a\": 1}"),
+            Ok(Message::Text("{\"a\": 1}".to_string()))
+        );
+        assert!(build_message(MessageKind::Json, "not {json").is_err());
+        assert_eq!(
+            build_message(MessageKind::Binary, "01 ff"),
+            Ok(Message::Binary(vec![0x01, 0xff]))
+        );
+    }
+
+    #[test]
+    fn builds_close_messages() {
+        let close = |code: u16, reason: &str| {
+            Ok(Message::Close(Some(CloseFrame {
+                code: CloseCode::from(code),
+                reason: reason.to_string().into(),
+            })))
+        };
+        assert_eq!(build_close_message(""), close(1000, ""));
+        assert_eq!(build_close_message(" 4001 "), close(4001, ""));
+        assert_eq!(
+            build_close_message("1001 going away now"),
+            close(1001, "going away now")
+        );
+        assert!(build_close_message("done").is_err());
+        for code in [
+            "0", "999", "1004", "1005", "1006", "1012", "1015", "2999", "5000",
+        ] {
+            assert_eq!(
+                build_close_message(code),
+                Err(format!(
+                    "Invalid close code \"{}\", expected e.g. 1000",
+                    code
+                ))
+            );
+        }
+    }
+
+    /// Everything logged until the connection ends
+    async fn receive_all(connection: &mut WebSocketConnection) -> Vec<LogEntry> {
+        let mut log = Vec::new();
+        for _ in 0..500 {
+            let (entries, is_open) = connection.receive();
+            log.extend(entries);
+            if !is_open {
+                return log;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("The connection didn't end");
+    }
+
+    fn frames(log: &[LogEntry], direction: FrameDirection) -> Vec<(&str, &str)> {
+        log.iter()
+            .filter(|entry| entry.direction == direction)
+            .map(|entry| (entry.kind, entry.text.as_str()))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn round_trips_through_an_echo_server() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("ws://{}", listener.local_addr().unwrap());
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut websocket = tokio_tungstenite::accept_async(stream).await.unwrap();
+            // Pings are answered and closes acknowledged by tungstenite itself
+            while let Some(Ok(message)) = websocket.next().await {
+                if message.is_text() || message.is_binary() {
+                    websocket.send(message).await.unwrap();
+                }
+            }
+        });
+
+        let mut connection =
+            WebSocketConnection::connect(&endpoint, &[], Duration::from_secs(5)).unwrap();
+        // Queued before the handshake has finished
+        assert!(connection.send(Message::Text("hello".to_string())));
+        assert!(connection.send(Message::Binary(vec![0xde, 0xad])));
+        assert!(connection.send(Message::Ping(vec![0x01])));
+        assert!(connection.send(build_close_message("1000 done").unwrap()));
+        let log = receive_all(&mut connection).await;
+        server.await.unwrap();
+
+        assert_eq!(
+            frames(&log, FrameDirection::Sent),
+            vec![
+                ("text", "hello"),
+                ("binary", "2 bytes: de ad"),
+                ("ping", "1 byte: 01"),
+                ("close", "1000 done"),
+            ]
+        );
+        assert_eq!(
+            frames(&log, FrameDirection::Received),
+            vec![
+                ("text", "hello"),
+                ("binary", "2 bytes: de ad"),
+                ("pong", "1 byte: 01"),
+                ("close", "1000 done"),
+            ]
+        );
+        assert_eq!(
+            frames(&log, FrameDirection::Status),
+            vec![("status", "Connected"), ("status", "Disconnected")]
+        );
+        assert!(log.iter().all(|entry| !entry.is_error));
+    }
+
+    #[tokio::test]
+    async fn handshake_times_out() {
+        // Accept the connection but never answer the handshake
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("ws://{}", listener.local_addr().unwrap());
+        let server = tokio::spawn(async move {
+            let (_stream, _) = listener.accept().await.unwrap();
+            tokio::time::sleep(Duration::from_secs(5)).await;
+        });
+
+        let mut connection =
+            WebSocketConnection::connect(&endpoint, &[], Duration::from_millis(200)).unwrap();
+        let log = receive_all(&mut connection).await;
+        server.abort();
+        assert!(!connection.is_connected());
+        assert_eq!(log.len(), 1);
+        assert!(log[0].is_error);
+        assert_eq!(log[0].text, "Timed out after 0.2 seconds");
+    }
+
+    #[test]
+    fn rejects_invalid_headers() {
+        let headers = [("bad header".to_string(), "value".to_string())];
+        assert!(
+            WebSocketConnection::connect("ws://localhost", &headers, Duration::from_secs(1))
+                .is_err()
+        );
+    }
+}